@@ -0,0 +1,64 @@
+use crate::carddb::ScryfallEntry;
+use log::{info, warn};
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+//Bump whenever ScryfallEntry or the tokenizer changes, so old caches are rebuilt
+const VERSION: u64 = 1;
+const HEADER_LEN: usize = 16;
+
+//The tokenized entries are cached next to the file they came from,
+//which is only read again when its contents change
+pub fn cache_path(path: &Path) -> PathBuf {
+    path.with_extension("cache")
+}
+//The hash only has to be stable for as long as a cache is kept around,
+//a toolchain that hashes differently just rebuilds the cache
+pub fn hash(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    hasher.write_u64(VERSION);
+    hasher.write(data);
+    hasher.finish()
+}
+//None when there's no cache for data with this hash, or it can't be read
+pub fn load(path: &Path, hash: u64) -> Option<Vec<ScryfallEntry>> {
+    let bytes = fs::read(path).ok()?;
+    if bytes.len() < HEADER_LEN
+        || bytes[..8] != VERSION.to_le_bytes()
+        || bytes[8..HEADER_LEN] != hash.to_le_bytes()
+    {
+        return None;
+    }
+    match rmp_serde::from_slice(&bytes[HEADER_LEN..]) {
+        Ok(entries) => {
+            info!("Read the card database from {}", path.display());
+            Some(entries)
+        }
+        Err(err) => {
+            warn!("Ignoring unreadable cache {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+//Writes to a temporary file first, so a cache being written
+//is never read by a server or test starting at the same time
+pub fn store(path: &Path, hash: u64, entries: &[&ScryfallEntry]) {
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&VERSION.to_le_bytes());
+    bytes.extend_from_slice(&hash.to_le_bytes());
+    if let Err(err) = rmp_serde::encode::write(&mut bytes, entries) {
+        warn!("Couldn't encode the card database cache: {}", err);
+        return;
+    }
+    let write = WRITES.fetch_add(1, Ordering::Relaxed);
+    let temp = path.with_extension(format!("cache.{}.{}", std::process::id(), write));
+    let written = fs::write(&temp, bytes).and_then(|()| fs::rename(&temp, path));
+    if let Err(err) = written {
+        warn!("Couldn't write the card database cache {}: {}", path.display(), err);
+        let _ = fs::remove_file(&temp);
+    }
+}
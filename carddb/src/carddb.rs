@@ -1,10 +1,12 @@
+use crate::cache;
 use crate::parse_clause::parse_clause;
 use crate::parse_clauseeffect::parse_cont_effect;
 use crate::parse_constraint::parse_constraint;
 use crate::parse_non_body::parse_cost_line;
 use crate::parse_non_body::parse_pt;
 use crate::parse_non_body::parse_type_line;
-use crate::spawn_error::SpawnError;
+use crate::query::Searchable;
+use crate::spawn_error::{CardPart, ParseError, SpawnError};
 use crate::tokenize::tokenize;
 use crate::util::parse_number;
 use common::ability::Ability;
@@ -43,14 +45,15 @@ use nom::multi::many0;
 use nom::multi::many1;
 use nom::sequence::delimited;
 use nom::IResult;
-use serde_derive::Deserialize;
+use once_cell::sync::OnceCell;
+use serde_derive::{Deserialize, Serialize};
 use serde_json;
-use serde_with::{serde_as, BorrowCow};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use texttoken::owned_tokens;
 use texttoken::{tokens, Token, Tokens};
@@ -62,6 +65,9 @@ enum ParsedLine {
 }
 pub struct CardDB {
     scryfall: HashMap<Token, ScryfallEntry>,
+    pub(crate) lowercase: HashMap<String, Token>, //Names by their lowercase
+    pub(crate) searchable: Vec<Searchable>,       //Sorted by name
+    pub(crate) spawnable: OnceCell<Vec<&'static str>>, //Worked out the first time it's asked for
 }
 
 impl fmt::Debug for CardDB {
@@ -69,38 +75,39 @@ impl fmt::Debug for CardDB {
         f.debug_struct("CardDB").finish()
     }
 }
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug)]
 #[allow(dead_code)]
 pub struct ScryfallImageUrls {
     pub small: Option<String>,
     pub normal: Option<String>,
     pub large: Option<String>,
 }
-#[serde_as]
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Legality {
+    Legal,
+    NotLegal,
+    Banned,
+    Restricted,
+}
+#[derive(Deserialize, Serialize, Debug)]
 #[allow(dead_code)]
 pub struct ScryfallEntry {
-    #[serde_as(as = "Option<BorrowCow>")]
     pub object: Option<Cow<'static, str>>,
-    #[serde_as(as = "BorrowCow")]
     pub name: Cow<'static, str>,
     pub image_uris: Option<ScryfallImageUrls>,
-    #[serde_as(as = "Option<BorrowCow>")]
     pub mana_cost: Option<Cow<'static, str>>,
-    #[serde_as(as = "Option<BorrowCow>")]
     pub type_line: Option<Cow<'static, str>>,
-    pub tokenized_type_line: Option<Vec<Token>>, //Will be tokeized upon construction
-    #[serde_as(as = "Option<BorrowCow>")]
+    pub tokenized_type_line: Option<Vec<Cow<'static, str>>>, //Will be tokeized upon construction
     pub lang: Option<Cow<'static, str>>,
-    pub color_identity: Option<Vec<Token>>,
+    pub set: Option<Cow<'static, str>>,
+    pub color_identity: Option<Vec<Cow<'static, str>>>,
     pub cmc: Option<f64>,
-    #[serde_as(as = "Option<BorrowCow>")]
     pub power: Option<Cow<'static, str>>,
-    #[serde_as(as = "Option<BorrowCow>")]
     pub toughness: Option<Cow<'static, str>>,
-    #[serde_as(as = "Option<BorrowCow>")]
     pub oracle_text: Option<Cow<'static, str>>,
-    pub tokenized_oracle_text: Option<Vec<Token>>, //Will be tokeized upon construction
+    pub tokenized_oracle_text: Option<Vec<Cow<'static, str>>>, //Will be tokeized upon construction
+    pub legalities: Option<HashMap<String, Legality>>,
 }
 pub fn nom_error<'a>(
     tokens: &'a Tokens,
@@ -111,7 +118,13 @@ pub fn nom_error<'a>(
     })
 }
 
+//Where the oracle database is, when it isn't passed in explicitly
+pub const PATH_VAR: &str = "ORACLE_CARDS";
+
 fn find_path() -> Result<PathBuf, std::io::Error> {
+    if let Some(path) = std::env::var_os(PATH_VAR) {
+        return Ok(PathBuf::from(path));
+    }
     //let path = "../oracle-cards-20230120100202.json";
     let current_dir = std::env::current_dir()?;
     let dir_copy = current_dir.clone();
@@ -120,6 +133,9 @@ fn find_path() -> Result<PathBuf, std::io::Error> {
         let entry = entry?;
         let path = entry.path();
         let last = path.file_stem();
+        if path.extension().map_or(true, |ext| ext != "json") {
+            continue;
+        }
         if let Some(last) = last {
             if let Some(last) = last.to_str() {
                 if last.contains("oracle-cards-") {
@@ -128,45 +144,119 @@ fn find_path() -> Result<PathBuf, std::io::Error> {
             }
         }
     }
-    panic!("Failed to find scryfall oracle database");
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!(
+            "no oracle-cards-*.json here or in the parent directory, and {} isn't set",
+            PATH_VAR
+        ),
+    ))
 }
+//Every card the test suites use, small enough to keep in the repository
+pub const FIXTURE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/cards.json");
 impl CardDB {
+    //The database at $ORACLE_CARDS, or the oracle-cards-*.json
+    //in the working directory or its parent
     pub fn new() -> Self {
-        println!("Initializing card database");
         let path = find_path().expect("Failed to find scryfall oracle database");
-        let data = fs::read_to_string(path).expect("Couldn't open file");
-        let data: &'static str = Box::leak(data.into_boxed_str());
-        let desered: Vec<ScryfallEntry> = serde_json::from_str(&data).expect("failed to parse!");
+        CardDB::from_path(&path).expect("Couldn't read scryfall oracle database")
+    }
+    //Reads a Scryfall oracle cards dump, or anything else in its format.
+    //The tokenized cards are cached next to it, so later starts skip
+    //parsing the JSON unless the file changes
+    pub fn from_path(path: &Path) -> io::Result<Self> {
+        println!("Initializing card database from {}", path.display());
+        let data = fs::read_to_string(path)?;
+        let hash = cache::hash(data.as_bytes());
+        let cache_path = cache::cache_path(path);
+        if let Some(entries) = cache::load(&cache_path, hash) {
+            return Ok(CardDB::from_entries(entries));
+        }
+        let db = CardDB::from_json(&data)?;
+        let entries: Vec<&ScryfallEntry> = db.scryfall.values().collect();
+        cache::store(&cache_path, hash, &entries);
+        Ok(db)
+    }
+    //For binaries, the database given as "--cards <path>" or else the
+    //one CardDB::new would find. Returns the rest of the arguments
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> io::Result<(Self, Vec<String>)> {
+        let mut path = None;
+        let mut rest = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "--cards" {
+                let Some(value) = args.next() else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "--cards needs the path of a card database",
+                    ));
+                };
+                path = Some(PathBuf::from(value));
+            } else {
+                rest.push(arg);
+            }
+        }
+        let path = match path {
+            Some(path) => path,
+            None => find_path()?,
+        };
+        Ok((CardDB::from_path(&path)?, rest))
+    }
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut data = String::new();
+        reader.read_to_string(&mut data)?;
+        Ok(CardDB::from_json(&data)?)
+    }
+    pub fn fixture() -> Self {
+        CardDB::from_path(Path::new(FIXTURE_PATH)).expect("Couldn't read the card fixture")
+    }
+    //The database tests share. Built with the fixture feature it's the
+    //fixture, so tests run without a Scryfall dump
+    pub fn for_tests() -> Self {
+        if cfg!(feature = "fixture") {
+            CardDB::fixture()
+        } else {
+            CardDB::new()
+        }
+    }
+    fn from_json(data: &str) -> serde_json::Result<Self> {
+        let mut desered: Vec<ScryfallEntry> = serde_json::from_str(data)?;
+        //Entries own their text, so the tokens are copied out of it
+        let owned = |tokens: Vec<Cow<str>>| -> Vec<Token> {
+            tokens
+                .into_iter()
+                .map(|item| item.into_owned().into())
+                .collect()
+        };
+        for card in &mut desered {
+            card.tokenized_type_line = card
+                .type_line
+                .as_ref()
+                .map(|line| owned(tokenize(line, None)));
+            card.tokenized_oracle_text = card
+                .oracle_text
+                .as_ref()
+                .map(|line| owned(tokenize(line, Some(&card.name))));
+        }
+        Ok(CardDB::from_entries(desered))
+    }
+    fn from_entries(entries: Vec<ScryfallEntry>) -> Self {
         let mut byname = HashMap::new();
-        for mut card in desered {
-            card.tokenized_type_line =
-                card.type_line
-                    .as_ref()
-                    .map(|line: &Cow<'static, str>| match line {
-                        Cow::Borrowed(line) => tokenize(line, None),
-                        Cow::Owned(line) => tokenize(line, None)
-                            .into_iter()
-                            .map(|item| item.into_owned().into())
-                            .collect(),
-                    });
-            card.tokenized_oracle_text = card.oracle_text.as_ref().map(|line| match line {
-                Cow::Borrowed(line) => tokenize(line, Some(&card.name)),
-                Cow::Owned(line) => tokenize(line, Some(&card.name))
-                    .into_iter()
-                    .map(|item| item.into_owned().into())
-                    .collect(),
-            });
+        let mut lowercase = HashMap::new();
+        let mut searchable = Vec::new();
+        for card in entries {
+            lowercase.insert(card.name.to_lowercase(), card.name.clone());
+            searchable.push(Searchable::new(&card));
             byname.insert(card.name.clone(), card);
         }
-        CardDB { scryfall: byname }
-    }
-    //Precondition: card_name is the name of a valid magic card.
-    //Will panic if that is not the case.
-    pub fn spawn_card(&self, card_name: &'static str, owner: PlayerId) -> CardEnt {
-        self.try_spawn_card(card_name, owner)
-            .expect("couldn't spawn card")
+        searchable.sort_by(|a, b| a.name.cmp(&b.name));
+        CardDB {
+            scryfall: byname,
+            lowercase,
+            searchable,
+            spawnable: OnceCell::new(),
+        }
     }
-
     pub fn try_spawn_card(
         &self,
         card_name: &'static str,
@@ -179,14 +269,25 @@ impl CardDB {
         let scryfall: &ScryfallEntry = self
             .scryfall
             .get(card_name)
-            .ok_or(SpawnError::CardNotFoundError(card_name))?;
-        parse_cost_line(&mut card, scryfall).unwrap();
+            .ok_or_else(|| SpawnError::CardNotFound(card_name.to_owned()))?;
+        parse_cost_line(&mut card, scryfall).map_err(|err| {
+            let text = scryfall.mana_cost.as_deref().unwrap_or("");
+            ParseError::from_text(card_name, CardPart::ManaCost, text, err)
+        })?;
         debug!("parsed cost line");
-        parse_type_line(&mut card, scryfall)?;
+        parse_type_line(&mut card, scryfall).map_err(|err| {
+            let text = scryfall.type_line.as_deref().unwrap_or("");
+            let tokens = scryfall.tokenized_type_line.as_deref().unwrap_or(&[]);
+            ParseError::from_tokens(card_name, CardPart::TypeLine, text, tokens, err)
+        })?;
         debug!("parsed type line");
         parse_pt(&mut card, scryfall);
         debug!("parsed P/T");
-        parse_body(&mut card, scryfall)?;
+        parse_body(&mut card, scryfall).map_err(|err| {
+            let text = scryfall.oracle_text.as_deref().unwrap_or("");
+            let tokens = scryfall.tokenized_oracle_text.as_deref().unwrap_or(&[]);
+            ParseError::from_tokens(card_name, CardPart::OracleText, text, tokens, err)
+        })?;
         debug!("parsed body");
         card.art_url = (&scryfall.image_uris)
             .as_ref()
@@ -195,6 +296,17 @@ impl CardDB {
         card.printed = Some(Box::new(card.clone()));
         Ok(card)
     }
+    pub fn entries(&self) -> impl Iterator<Item = &ScryfallEntry> {
+        self.scryfall.values()
+    }
+    pub fn entry(&self, card_name: &str) -> Option<&ScryfallEntry> {
+        self.scryfall.get(card_name)
+    }
+    //Looks up the database's copy of a card name, so it
+    //can be used where a &'static str is needed
+    pub fn card_name(&'static self, card_name: &str) -> Option<&'static str> {
+        self.scryfall.get(card_name).map(|entry| &*entry.name)
+    }
 }
 
 fn parse_body<'a>(
@@ -0,0 +1,153 @@
+use crate::carddb::CardDB;
+use crate::spawn_error::{CardPart, SpawnError};
+use common::entities::PlayerId;
+use common::panic::panic_message;
+use serde_derive::Serialize;
+use std::collections::BTreeMap;
+use std::num::NonZeroU64;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+const CARD_TYPES: [&str; 9] = [
+    "Artifact",
+    "Battle",
+    "Creature",
+    "Enchantment",
+    "Instant",
+    "Kindred",
+    "Land",
+    "Planeswalker",
+    "Sorcery",
+];
+const EXAMPLES: usize = 5;
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct Coverage {
+    pub cards: usize,
+    pub spawned: usize,
+}
+impl Coverage {
+    pub fn percent(&self) -> f64 {
+        self.spawned as f64 / self.cards.max(1) as f64 * 100.0
+    }
+    fn count(&mut self, spawned: bool) {
+        self.cards += 1;
+        if spawned {
+            self.spawned += 1;
+        }
+    }
+}
+//The failures that stopped at the same tokens
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct StoppedAt {
+    pub span: String,
+    pub cards: usize,
+    pub examples: Vec<String>, //The first few cards by name
+}
+//Which cards the parser can spawn. Everything is sorted,
+//so reports from two commits can be diffed
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct CoverageReport {
+    pub total: Coverage,
+    pub by_set: BTreeMap<String, Coverage>,
+    pub by_type: BTreeMap<String, Coverage>, //Cards with more than one type count for each
+    pub by_kind: BTreeMap<String, usize>,
+    pub by_span: Vec<StoppedAt>,            //Most common first
+    pub failures: BTreeMap<String, String>, //Why each card that didn't spawn failed
+}
+struct Failure {
+    kind: &'static str,
+    span: String,
+    message: String,
+}
+
+//Tries to spawn every card in the database. Cards that make the
+//parser panic are counted as failures, the panics are still
+//printed unless the caller swaps out the panic hook
+pub fn coverage(db: &'static CardDB) -> CoverageReport {
+    let owner = PlayerId::from(NonZeroU64::new(1).unwrap());
+    let mut report = CoverageReport::default();
+    let mut spans: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for entry in db.entries() {
+        let name: &'static str = &entry.name;
+        let failure = match catch_unwind(AssertUnwindSafe(|| db.try_spawn_card(name, owner))) {
+            Ok(Ok(_)) => None,
+            Ok(Err(err)) => Some(Failure {
+                kind: kind(&err),
+                span: span(&err),
+                message: err.to_string(),
+            }),
+            Err(panic) => {
+                let message =
+                    panic_message(panic).unwrap_or_else(|| "the parser panicked".to_owned());
+                Some(Failure {
+                    kind: "Panic",
+                    span: format!("panicked: {}", message),
+                    message,
+                })
+            }
+        };
+        let spawned = failure.is_none();
+        report.total.count(spawned);
+        let set = entry.set.as_deref().unwrap_or("unknown").to_owned();
+        report.by_set.entry(set).or_default().count(spawned);
+        for card_type in card_types(entry.type_line.as_deref().unwrap_or("")) {
+            report.by_type.entry(card_type).or_default().count(spawned);
+        }
+        if let Some(failure) = failure {
+            *report.by_kind.entry(failure.kind.to_owned()).or_default() += 1;
+            spans.entry(failure.span).or_default().push(name.to_owned());
+            report.failures.insert(name.to_owned(), failure.message);
+        }
+    }
+    report.by_span = spans
+        .into_iter()
+        .map(|(span, mut cards)| {
+            cards.sort();
+            StoppedAt {
+                span,
+                cards: cards.len(),
+                examples: cards.into_iter().take(EXAMPLES).collect(),
+            }
+        })
+        .collect();
+    report.by_span.sort_by(|a, b| b.cards.cmp(&a.cards));
+    report
+}
+fn kind(err: &SpawnError) -> &'static str {
+    match err {
+        SpawnError::Parse(err) => match err.part {
+            CardPart::ManaCost => "ManaCost",
+            CardPart::TypeLine => "TypeLine",
+            CardPart::OracleText => "OracleText",
+        },
+        SpawnError::CardNotFound(_) => "CardNotFound",
+    }
+}
+//The first few tokens the innermost parser was looking at when it gave up
+fn span(err: &SpawnError) -> String {
+    let span = match err {
+        SpawnError::Parse(err) => err.near(),
+        SpawnError::CardNotFound(name) => name.clone(),
+    };
+    if span.is_empty() {
+        "(end of text)".to_owned()
+    } else {
+        span
+    }
+}
+//The card types before the dash, on each face of the card
+fn card_types(type_line: &str) -> Vec<String> {
+    let mut types: Vec<String> = Vec::new();
+    for face in type_line.split("//") {
+        let before_dash = face.split('—').next().unwrap_or("");
+        for word in before_dash.split_whitespace() {
+            if CARD_TYPES.contains(&word) && !types.iter().any(|t| t == word) {
+                types.push(word.to_owned());
+            }
+        }
+    }
+    if types.is_empty() {
+        types.push("Other".to_owned());
+    }
+    types
+}
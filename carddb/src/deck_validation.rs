@@ -0,0 +1,235 @@
+use crate::carddb::{CardDB, Legality, ScryfallEntry};
+use common::format::Format;
+use schemars::JsonSchema;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+pub const CONSTRUCTED_MIN: usize = 60;
+pub const LIMITED_MIN: usize = 40;
+pub const COMMANDER_SIZE: usize = 100; //Includes the commander
+pub const MAX_COPIES: usize = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum DeckViolation {
+    CardNotFound {
+        name: String,
+    },
+    TooFewCards {
+        min: usize,
+        found: usize,
+    },
+    WrongSize {
+        size: usize,
+        found: usize,
+    },
+    TooManyCopies {
+        name: String,
+        max: usize,
+        found: usize,
+    },
+    NotLegal {
+        name: String,
+    },
+    Banned {
+        name: String,
+    },
+    OutsideColorIdentity {
+        name: String,
+    },
+    MissingCommander,
+    InvalidCommander {
+        name: String,
+    },
+}
+impl fmt::Display for DeckViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CardNotFound { name } => write!(f, "no card named {}", name),
+            Self::TooFewCards { min, found } => {
+                write!(f, "deck has {} cards, at least {} are needed", found, min)
+            }
+            Self::WrongSize { size, found } => {
+                write!(f, "deck has {} cards, it must have exactly {}", found, size)
+            }
+            Self::TooManyCopies { name, max, found } => {
+                write!(
+                    f,
+                    "{} copies of {}, at most {} are allowed",
+                    found, name, max
+                )
+            }
+            Self::NotLegal { name } => write!(f, "{} isn't legal in this format", name),
+            Self::Banned { name } => write!(f, "{} is banned in this format", name),
+            Self::OutsideColorIdentity { name } => {
+                write!(f, "{} is outside the commander's color identity", name)
+            }
+            Self::MissingCommander => write!(f, "commander decks need a commander"),
+            Self::InvalidCommander { name } => {
+                write!(
+                    f,
+                    "{} can't be a commander, it isn't a legendary creature",
+                    name
+                )
+            }
+        }
+    }
+}
+
+fn type_line(entry: &ScryfallEntry) -> &str {
+    entry.type_line.as_deref().unwrap_or("")
+}
+//Basic lands and cards like Relentless Rats ignore the copy limit
+fn unlimited_copies(entry: &ScryfallEntry) -> bool {
+    type_line(entry).contains("Basic")
+        || entry.oracle_text.as_deref().map_or(false, |text| {
+            text.contains("A deck can have any number of cards named")
+        })
+}
+fn legality(entry: &ScryfallEntry, format: Format) -> Legality {
+    let Some(key) = format.scryfall_key() else {
+        return Legality::Legal;
+    };
+    entry
+        .legalities
+        .as_ref()
+        .and_then(|legalities| legalities.get(key))
+        .copied()
+        .unwrap_or(Legality::NotLegal)
+}
+
+//Checks a deck against the construction rules of a format.
+//For commander the commander is passed separately and isn't part of the deck.
+//Every violation found is returned, not just the first one
+pub fn validate_deck(
+    db: &CardDB,
+    format: Format,
+    deck: &[&str],
+    commander: Option<&str>,
+) -> Result<(), Vec<DeckViolation>> {
+    let mut violations = Vec::new();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for name in deck {
+        *counts.entry(name).or_insert(0) += 1;
+    }
+    let mut entries = Vec::new();
+    for (&name, &count) in counts.iter() {
+        match db.entry(name) {
+            Some(entry) => entries.push((name, count, entry)),
+            None => violations.push(DeckViolation::CardNotFound {
+                name: name.to_owned(),
+            }),
+        }
+    }
+    //Report cards in a stable order
+    entries.sort_by_key(|(name, _, _)| *name);
+    let max_copies = match format {
+        Format::Casual | Format::Limited => None,
+        Format::Standard => Some(MAX_COPIES),
+        Format::Commander => Some(1),
+    };
+    match format {
+        Format::Casual => {}
+        Format::Standard => {
+            if deck.len() < CONSTRUCTED_MIN {
+                violations.push(DeckViolation::TooFewCards {
+                    min: CONSTRUCTED_MIN,
+                    found: deck.len(),
+                });
+            }
+        }
+        Format::Limited => {
+            if deck.len() < LIMITED_MIN {
+                violations.push(DeckViolation::TooFewCards {
+                    min: LIMITED_MIN,
+                    found: deck.len(),
+                });
+            }
+        }
+        Format::Commander => {
+            let found = deck.len() + usize::from(commander.is_some());
+            if found != COMMANDER_SIZE {
+                violations.push(DeckViolation::WrongSize {
+                    size: COMMANDER_SIZE,
+                    found,
+                });
+            }
+            validate_commander(db, commander, &entries, &mut violations);
+        }
+    }
+    for &(name, count, entry) in &entries {
+        match legality(entry, format) {
+            Legality::Legal => {}
+            Legality::NotLegal => violations.push(DeckViolation::NotLegal {
+                name: name.to_owned(),
+            }),
+            Legality::Banned => violations.push(DeckViolation::Banned {
+                name: name.to_owned(),
+            }),
+            Legality::Restricted => {
+                if count > 1 {
+                    violations.push(DeckViolation::TooManyCopies {
+                        name: name.to_owned(),
+                        max: 1,
+                        found: count,
+                    });
+                }
+            }
+        }
+        if let Some(max) = max_copies
+        && count > max
+        && !unlimited_copies(entry) {
+            violations.push(DeckViolation::TooManyCopies {
+                name: name.to_owned(),
+                max,
+                found: count,
+            });
+        }
+    }
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+fn validate_commander(
+    db: &CardDB,
+    commander: Option<&str>,
+    entries: &[(&str, usize, &ScryfallEntry)],
+    violations: &mut Vec<DeckViolation>,
+) {
+    let Some(name) = commander else {
+        violations.push(DeckViolation::MissingCommander);
+        return;
+    };
+    let Some(entry) = db.entry(name) else {
+        violations.push(DeckViolation::CardNotFound {
+            name: name.to_owned(),
+        });
+        return;
+    };
+    let line = type_line(entry);
+    if !(line.contains("Legendary") && line.contains("Creature")) {
+        violations.push(DeckViolation::InvalidCommander {
+            name: name.to_owned(),
+        });
+    }
+    if legality(entry, Format::Commander) == Legality::Banned {
+        violations.push(DeckViolation::Banned {
+            name: name.to_owned(),
+        });
+    }
+    let identity = entry.color_identity.clone().unwrap_or_default();
+    for &(card, _, entry) in entries {
+        let outside = entry
+            .color_identity
+            .iter()
+            .flatten()
+            .any(|color| !identity.contains(color));
+        if outside {
+            violations.push(DeckViolation::OutsideColorIdentity {
+                name: card.to_owned(),
+            });
+        }
+    }
+}
@@ -0,0 +1,223 @@
+use crate::carddb::CardDB;
+use crate::deck_validation::DeckViolation;
+use common::entities::PlayerId;
+use schemars::JsonSchema;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+
+//The most copies of one card a deck entry can ask for. This is well above
+//any legal deck size and keeps the total from overflowing
+pub const MAX_ENTRY_COUNT: usize = 250;
+//The most cards a whole deck can have. Resolving a deck allocates one name
+//per card, so this is checked before any card is looked up
+pub const MAX_DECK_SIZE: usize = 1000;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct DeckEntry {
+    pub count: usize,
+    pub name: String,
+}
+//Clients can send a deck either as the usual
+//"4 Card Name" text or as a list of entries
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum DeckSubmission {
+    Text(String),
+    List(Vec<DeckEntry>),
+    WithCommander {
+        commander: String,
+        deck: Vec<DeckEntry>,
+    },
+}
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Decklist {
+    pub commander: Option<String>,
+    pub entries: Vec<DeckEntry>,
+}
+//A deck that has been checked against the card database
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResolvedDeck {
+    pub commander: Option<&'static str>,
+    pub cards: Vec<&'static str>,
+}
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum CardErrorReason {
+    MalformedLine(usize), //The line number, starting from 1
+    NotFound,
+    ParseFailed(String),
+    TooManyCopies(usize), //The count that was asked for
+}
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct CardError {
+    pub name: String,
+    pub reason: CardErrorReason,
+}
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub enum DeckError {
+    Empty,
+    TooManyCards(usize), //How many cards the deck had
+    Cards(Vec<CardError>),
+    Illegal(Vec<DeckViolation>),
+}
+
+fn too_many_copies(entry: &DeckEntry) -> Option<CardError> {
+    (entry.count > MAX_ENTRY_COUNT).then(|| CardError {
+        name: entry.name.clone(),
+        reason: CardErrorReason::TooManyCopies(entry.count),
+    })
+}
+fn nonempty(entries: &[DeckEntry]) -> Result<Vec<DeckEntry>, DeckError> {
+    let errors: Vec<CardError> = entries.iter().filter_map(too_many_copies).collect();
+    if !errors.is_empty() {
+        return Err(DeckError::Cards(errors));
+    }
+    Ok(entries
+        .iter()
+        .filter(|entry| entry.count > 0)
+        .cloned()
+        .collect())
+}
+impl DeckSubmission {
+    pub fn decklist(&self) -> Result<Decklist, DeckError> {
+        let decklist = match self {
+            Self::Text(text) => parse_decklist(text)?,
+            Self::List(entries) => Decklist {
+                commander: None,
+                entries: nonempty(entries)?,
+            },
+            Self::WithCommander { commander, deck } => Decklist {
+                commander: Some(commander.clone()),
+                entries: nonempty(deck)?,
+            },
+        };
+        if decklist.entries.is_empty() {
+            return Err(DeckError::Empty);
+        }
+        let cards: usize = decklist.entries.iter().map(|entry| entry.count).sum();
+        let size = cards + decklist.commander.iter().count();
+        if size > MAX_DECK_SIZE {
+            return Err(DeckError::TooManyCards(size));
+        }
+        Ok(decklist)
+    }
+    //Checks that every card in the deck exists and can be spawned,
+    //returning the deck as one name per card
+    pub fn resolve(&self, db: &'static CardDB) -> Result<ResolvedDeck, DeckError> {
+        let decklist = self.decklist()?;
+        let mut resolver = Resolver {
+            db,
+            checked: HashMap::new(),
+            errors: Vec::new(),
+        };
+        let commander = decklist
+            .commander
+            .and_then(|commander| resolver.resolve(&commander));
+        let mut cards = Vec::new();
+        for entry in decklist.entries {
+            if let Some(name) = resolver.resolve(&entry.name) {
+                for _ in 0..entry.count {
+                    cards.push(name);
+                }
+            }
+        }
+        if resolver.errors.is_empty() {
+            Ok(ResolvedDeck { commander, cards })
+        } else {
+            Err(DeckError::Cards(resolver.errors))
+        }
+    }
+}
+struct Resolver {
+    db: &'static CardDB,
+    checked: HashMap<&'static str, bool>, //Whether each card could be spawned
+    errors: Vec<CardError>,
+}
+impl Resolver {
+    fn resolve(&mut self, name: &str) -> Option<&'static str> {
+        let Some(found) = self.db.find(name).map(|entry| &*entry.name) else {
+            self.errors.push(CardError {
+                name: name.to_owned(),
+                reason: CardErrorReason::NotFound,
+            });
+            return None;
+        };
+        if let Some(&spawned) = self.checked.get(found) {
+            return spawned.then_some(found);
+        }
+        let owner = PlayerId::from(NonZeroU64::new(1).unwrap());
+        let spawned = match self.db.try_spawn_card(found, owner) {
+            Ok(_) => true,
+            Err(err) => {
+                self.errors.push(CardError {
+                    name: found.to_owned(),
+                    reason: CardErrorReason::ParseFailed(err.summary()),
+                });
+                false
+            }
+        };
+        self.checked.insert(found, spawned);
+        spawned.then_some(found)
+    }
+}
+
+//Parses decklists of the form
+//4 Card Name
+//2x Other Card
+//Blank lines and comments are skipped. A single card under a "Commander"
+//header is the deck's commander, the cards under a "Deck" header are the
+//main deck and everything after a "Sideboard" header is ignored
+pub fn parse_decklist(text: &str) -> Result<Decklist, DeckError> {
+    let mut decklist = Decklist::default();
+    let mut errors = Vec::new();
+    let mut in_commander = false;
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("//") || line.starts_with('#') {
+            continue;
+        }
+        if line.eq_ignore_ascii_case("deck") {
+            in_commander = false;
+            continue;
+        }
+        if line.eq_ignore_ascii_case("commander") {
+            in_commander = true;
+            continue;
+        }
+        if line.eq_ignore_ascii_case("sideboard") {
+            break;
+        }
+        match parse_line(line) {
+            Some(entry) if entry.count > MAX_ENTRY_COUNT => errors.push(CardError {
+                reason: CardErrorReason::TooManyCopies(entry.count),
+                name: entry.name,
+            }),
+            Some(entry) if in_commander && entry.count == 1 && decklist.commander.is_none() => {
+                decklist.commander = Some(entry.name);
+            }
+            Some(entry) if !in_commander => decklist.entries.push(entry),
+            _ => errors.push(CardError {
+                name: line.to_owned(),
+                reason: CardErrorReason::MalformedLine(i + 1),
+            }),
+        }
+    }
+    if errors.is_empty() {
+        Ok(decklist)
+    } else {
+        Err(DeckError::Cards(errors))
+    }
+}
+fn parse_line(line: &str) -> Option<DeckEntry> {
+    let (count, name) = line.split_once(char::is_whitespace)?;
+    let count = count.strip_suffix(['x', 'X']).unwrap_or(count);
+    let count: usize = count.parse().ok()?;
+    let name = name.trim();
+    if count == 0 || name.is_empty() {
+        return None;
+    }
+    Some(DeckEntry {
+        count,
+        name: name.to_owned(),
+    })
+}
@@ -1,16 +1,28 @@
 #![feature(let_chains)]
 
+mod cache;
 pub mod carddb;
+pub mod coverage;
+pub mod deck_validation;
+pub mod decklist;
 mod parse_clause;
 mod parse_clauseeffect;
 mod parse_constraint;
 mod parse_non_body;
-mod spawn_error;
+pub mod query;
+pub mod spawn_error;
 mod token_builder;
 mod tokenize;
 mod util;
 #[cfg(test)]
 mod tests {
 
+    mod carddb_tests;
+    mod common_test;
+    mod coverage_tests;
+    mod deck_validation_tests;
+    mod decklist_tests;
+    mod parse_error_tests;
+    mod query_tests;
     mod spawn_tests;
 }
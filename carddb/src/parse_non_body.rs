@@ -33,7 +33,12 @@ pub fn parse_cost_line<'a>(
     if let Some(manatext) = entry.mana_cost.as_ref() {
         let (rest, manas) = parse_mana(&manatext)?;
         if rest.len() > 0 {
-            panic!("parser error!");
+            return Err(nom::Err::Error(VerboseError {
+                errors: vec![(
+                    rest,
+                    nom::error::VerboseErrorKind::Context("unexpected text after the mana cost"),
+                )],
+            }));
         }
         for mana in manas {
             card.costs.push(Cost::Mana(mana));
@@ -0,0 +1,190 @@
+use crate::carddb::{CardDB, ScryfallEntry};
+use common::entities::PlayerId;
+use serde_derive::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::num::NonZeroU64;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use texttoken::Token;
+
+//How close a name has to be to count as a fuzzy match, from 0 to 1
+const FUZZY_THRESHOLD: f64 = 0.85;
+
+//Every filter that's set has to match. Text is matched ignoring case
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CardQuery {
+    pub name: Option<String>,      //Part of the name
+    pub type_line: Option<String>, //Part of the type line
+    pub colors: Option<String>,    //Color identity within these, like "WU". "C" for colorless
+    pub min_cmc: Option<f64>,
+    pub max_cmc: Option<f64>,
+    pub oracle: Option<String>, //Part of the rules text
+    pub spawnable: bool,        //Only cards the engine can play
+}
+//What a deck builder shows for a card
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct CardSummary {
+    pub name: String,
+    pub mana_cost: Option<String>,
+    pub type_line: Option<String>,
+    pub oracle_text: Option<String>,
+    pub power: Option<String>,
+    pub toughness: Option<String>,
+    pub cmc: Option<f64>,
+    pub color_identity: Vec<String>,
+    pub image: Option<String>,
+}
+impl From<&ScryfallEntry> for CardSummary {
+    fn from(entry: &ScryfallEntry) -> Self {
+        let text = |text: &Option<Cow<str>>| text.as_deref().map(str::to_owned);
+        CardSummary {
+            name: entry.name.to_string(),
+            mana_cost: text(&entry.mana_cost),
+            type_line: text(&entry.type_line),
+            oracle_text: text(&entry.oracle_text),
+            power: text(&entry.power),
+            toughness: text(&entry.toughness),
+            cmc: entry.cmc,
+            color_identity: entry
+                .color_identity
+                .iter()
+                .flatten()
+                .map(|color| color.to_string())
+                .collect(),
+            image: entry.image_uris.as_ref().and_then(|images| {
+                (images.normal.as_ref())
+                    .or(images.large.as_ref())
+                    .or(images.small.as_ref())
+                    .cloned()
+            }),
+        }
+    }
+}
+//Each card's text lowercased once when the database is built,
+//so searches don't redo it for every card
+pub(crate) struct Searchable {
+    pub(crate) name: Token,
+    lower_name: String,
+    type_line: String,
+    oracle: String,
+}
+impl Searchable {
+    pub(crate) fn new(entry: &ScryfallEntry) -> Self {
+        let lower = |text: Option<&str>| text.unwrap_or("").to_lowercase();
+        Searchable {
+            name: entry.name.clone(),
+            lower_name: entry.name.to_lowercase(),
+            type_line: lower(entry.type_line.as_deref()),
+            oracle: lower(entry.oracle_text.as_deref()),
+        }
+    }
+}
+impl CardQuery {
+    //The text filters come lowercased
+    fn matches(
+        &self,
+        text: [Option<&str>; 3],
+        card: &Searchable,
+        entry: &ScryfallEntry,
+        spawnable: &[&str],
+    ) -> bool {
+        let [name, type_line, oracle] = text;
+        let contains =
+            |text: &str, part: Option<&str>| part.map_or(true, |part| text.contains(part));
+        let cmc = entry.cmc.unwrap_or(0.0);
+        contains(&card.lower_name, name)
+            && contains(&card.type_line, type_line)
+            && contains(&card.oracle, oracle)
+            && self.min_cmc.map_or(true, |min| cmc >= min)
+            && self.max_cmc.map_or(true, |max| cmc <= max)
+            && self.colors.as_ref().map_or(true, |colors| {
+                let colors = colors.to_uppercase();
+                (entry.color_identity.iter().flatten()).all(|color| colors.contains(&**color))
+            })
+            && (!self.spawnable || spawnable.binary_search(&&*card.name).is_ok())
+    }
+}
+impl CardDB {
+    //The card with this name, in any case
+    pub fn find(&self, name: &str) -> Option<&ScryfallEntry> {
+        let name = self.lowercase.get(&name.trim().to_lowercase())?;
+        self.entry(name)
+    }
+    //Cards with names like this one, best match first. Names that
+    //start with or contain it come before ones that are misspelled
+    pub fn fuzzy(&self, name: &str, limit: usize) -> Vec<&ScryfallEntry> {
+        let wanted = name.trim().to_lowercase();
+        let mut scored: Vec<(f64, &Searchable)> = self
+            .searchable
+            .iter()
+            .filter_map(|card| {
+                let candidate = &card.lower_name;
+                let score = if *candidate == wanted {
+                    3.0
+                } else if candidate.starts_with(&wanted) {
+                    2.0
+                } else if candidate.contains(&wanted) {
+                    1.0
+                } else {
+                    strsim::jaro_winkler(&wanted, candidate)
+                };
+                (score >= FUZZY_THRESHOLD).then_some((score, card))
+            })
+            .collect();
+        //The cards are already in name order and the sort is stable
+        scored.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        scored
+            .into_iter()
+            .take(limit)
+            .filter_map(|(_, card)| self.entry(&card.name))
+            .collect()
+    }
+    //The cards matching a query, sorted by name. None if it asks
+    //for cards that spawn before they've been worked out
+    pub fn search(&self, query: &CardQuery) -> Option<Vec<&ScryfallEntry>> {
+        let spawnable = match (query.spawnable, self.spawnable()) {
+            (false, _) => &[],
+            (true, Some(spawnable)) => spawnable,
+            (true, None) => return None,
+        };
+        let lower = |part: &Option<String>| part.as_deref().map(str::to_lowercase);
+        let text = [
+            lower(&query.name),
+            lower(&query.type_line),
+            lower(&query.oracle),
+        ];
+        let text = [text[0].as_deref(), text[1].as_deref(), text[2].as_deref()];
+        let found = self
+            .searchable
+            .iter()
+            .filter_map(|card| Some((card, self.entry(&card.name)?)))
+            .filter(|(card, entry)| query.matches(text, card, entry, spawnable))
+            .map(|(_, entry)| entry)
+            .collect();
+        Some(found)
+    }
+    //The names of every card the parser can spawn, sorted,
+    //once check_spawnable has worked them out
+    pub fn spawnable(&self) -> Option<&[&'static str]> {
+        self.spawnable.get().map(|names| names.as_slice())
+    }
+    //Tries spawning every card, which takes a while, so it's only done
+    //the first time. Cards that make the parser panic are left out,
+    //the panics still go to the panic hook
+    pub fn check_spawnable(&'static self) -> &[&'static str] {
+        self.spawnable.get_or_init(|| {
+            let owner = PlayerId::from(NonZeroU64::new(1).unwrap());
+            let mut names: Vec<&'static str> = self
+                .entries()
+                .map(|entry| &*entry.name)
+                .filter(|&name| {
+                    catch_unwind(AssertUnwindSafe(|| self.try_spawn_card(name, owner)))
+                        .map_or(false, |spawned| spawned.is_ok())
+                })
+                .collect();
+            names.sort();
+            names
+        })
+    }
+}
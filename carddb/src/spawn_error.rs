@@ -1,14 +1,176 @@
-use nom::error::VerboseError;
+use nom::error::{VerboseError, VerboseErrorKind};
+use std::fmt;
+use texttoken::{Token, Tokens};
 
-use texttoken::Tokens;
+//How many tokens after where parsing stopped are shown
+const NEAR_TOKENS: usize = 4;
 
-#[derive(Debug, Clone)]
-pub enum SpawnError<'a> {
-    Nom(nom::Err<VerboseError<&'a Tokens>>),
-    CardNotFoundError(&'a str),
-}
-impl<'a> From<nom::Err<VerboseError<&'a Tokens>>> for SpawnError<'a> {
-    fn from(err: nom::Err<VerboseError<&'a Tokens>>) -> Self {
-        Self::Nom(err)
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpawnError {
+    CardNotFound(String),
+    Parse(ParseError),
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardPart {
+    ManaCost,
+    TypeLine,
+    OracleText,
+}
+//Where and why the parser gave up on a card
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub card: String,
+    pub part: CardPart,
+    pub line_number: usize,          //Counting from 1, only oracle text has more than one line
+    pub line: String,                //The line as it's printed on the card
+    pub tokens: Vec<String>,         //The line as the parser saw it
+    pub token: usize,                //Where in tokens parsing stopped, tokens.len() at the end
+    pub contexts: Vec<&'static str>, //What was being parsed there, innermost first
+}
+impl ParseError {
+    //For errors from parsing all of text's tokens, whose input is what was left of them
+    pub(crate) fn from_tokens(
+        card: &str,
+        part: CardPart,
+        text: &str,
+        all: &[Token],
+        err: nom::Err<VerboseError<&Tokens>>,
+    ) -> Self {
+        let left = first_input(&err).map_or(0, |rest| rest.len());
+        let at = all.len().saturating_sub(left);
+        let start = all[..at]
+            .iter()
+            .rposition(|token| token == "\n")
+            .map_or(0, |i| i + 1);
+        let end = all[at..]
+            .iter()
+            .position(|token| token == "\n")
+            .map_or(all.len(), |i| at + i);
+        let line_number = all[..start].iter().filter(|token| *token == "\n").count() + 1;
+        let line = text.lines().nth(line_number - 1).unwrap_or("");
+        ParseError {
+            card: card.to_owned(),
+            part,
+            line_number,
+            line: line.to_owned(),
+            tokens: all[start..end].iter().map(|token| token.to_string()).collect(),
+            token: at - start,
+            contexts: contexts(&err),
+        }
+    }
+    //For errors from parsing text character by character
+    pub(crate) fn from_text(
+        card: &str,
+        part: CardPart,
+        text: &str,
+        err: nom::Err<VerboseError<&str>>,
+    ) -> Self {
+        let left = first_input(&err).map_or(0, |rest| rest.len());
+        let at = text.len().saturating_sub(left);
+        ParseError {
+            card: card.to_owned(),
+            part,
+            line_number: 1,
+            line: text.to_owned(),
+            tokens: text.chars().map(|c| c.to_string()).collect(),
+            token: text[..at].chars().count(),
+            contexts: contexts(&err),
+        }
+    }
+    //Just the first line of the diagnostic, without the tokens
+    pub fn summary(&self) -> String {
+        let mut summary = format!("couldn't parse {}'s {}", self.card, self.part);
+        if self.part == CardPart::OracleText {
+            summary += &format!(", line {}", self.line_number);
+        }
+        if self.contexts.is_empty() {
+            summary + ": unsupported text"
+        } else {
+            summary + ": " + &self.contexts.join(", while parsing ")
+        }
+    }
+    //The first few tokens from where parsing stopped
+    pub fn near(&self) -> String {
+        let near: Vec<&str> = self
+            .tokens
+            .iter()
+            .skip(self.token)
+            .take(NEAR_TOKENS)
+            .map(|token| token.as_str())
+            .collect();
+        match self.part {
+            CardPart::ManaCost => near.concat(),
+            _ => near.join(" "),
+        }
+    }
+}
+fn first_input<T: Copy>(err: &nom::Err<VerboseError<T>>) -> Option<T> {
+    match err {
+        nom::Err::Incomplete(_) => None,
+        nom::Err::Error(err) | nom::Err::Failure(err) => err.errors.first().map(|(at, _)| *at),
+    }
+}
+fn contexts<T>(err: &nom::Err<VerboseError<T>>) -> Vec<&'static str> {
+    match err {
+        nom::Err::Incomplete(_) => vec!["ran out of text"],
+        nom::Err::Error(err) | nom::Err::Failure(err) => err
+            .errors
+            .iter()
+            .filter_map(|(_, kind)| match kind {
+                VerboseErrorKind::Context(message) => Some(*message),
+                _ => None,
+            })
+            .collect(),
+    }
+}
+impl fmt::Display for CardPart {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let part = match self {
+            CardPart::ManaCost => "mana cost",
+            CardPart::TypeLine => "type line",
+            CardPart::OracleText => "card text",
+        };
+        write!(f, "{}", part)
+    }
+}
+//Reads like
+//  couldn't parse Griselbrand's card text, line 1: unsupported text
+//    Pay 7 life: Draw seven cards.
+//    pay 7 life : draw seven card .
+//    ^
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.summary())?;
+        writeln!(f, "  {}", self.line)?;
+        let separator = if self.part == CardPart::ManaCost { "" } else { " " };
+        let before: Vec<&str> = self.tokens[..self.token].iter().map(|t| t.as_str()).collect();
+        let mut offset = before.join(separator).chars().count();
+        if !before.is_empty() {
+            offset += separator.len();
+        }
+        writeln!(f, "  {}", self.tokens.join(separator))?;
+        write!(f, "  {}^", " ".repeat(offset))
+    }
+}
+impl fmt::Display for SpawnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CardNotFound(name) => write!(f, "no card named {}", name),
+            Self::Parse(err) => write!(f, "{}", err),
+        }
+    }
+}
+impl SpawnError {
+    //A one line description, for showing to players
+    pub fn summary(&self) -> String {
+        match self {
+            Self::CardNotFound(_) => self.to_string(),
+            Self::Parse(err) => err.summary(),
+        }
+    }
+}
+impl From<ParseError> for SpawnError {
+    fn from(err: ParseError) -> Self {
+        Self::Parse(err)
     }
 }
@@ -0,0 +1,61 @@
+use crate::carddb::{CardDB, FIXTURE_PATH};
+use common::entities::PlayerId;
+use std::num::NonZeroU64;
+
+#[test]
+fn reads_a_database_from_a_reader() {
+    let json = r#"[{
+        "object": "card",
+        "name": "Plains",
+        "mana_cost": "",
+        "type_line": "Basic Land — Plains",
+        "oracle_text": "({T}: Add {W}.)",
+        "color_identity": [],
+        "cmc": 0.0
+    }]"#;
+    let db: &'static CardDB = Box::leak(Box::new(CardDB::from_reader(json.as_bytes()).unwrap()));
+    let owner = PlayerId::from(NonZeroU64::new(1).unwrap());
+    let name = db.card_name("Plains").unwrap();
+    assert_eq!(db.try_spawn_card(name, owner).unwrap().name, "Plains");
+    assert!(db.card_name("Island").is_none());
+}
+#[test]
+fn malformed_databases_are_errors() {
+    assert!(CardDB::from_reader("[{\"name\": 3}]".as_bytes()).is_err());
+    assert!(CardDB::from_path(std::path::Path::new("no-such-cards.json")).is_err());
+}
+#[test]
+fn databases_are_cached_until_their_file_changes() {
+    let dir = std::env::temp_dir().join(format!("carddb-cache-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("oracle-cards-test.json");
+    let cache = dir.join("oracle-cards-test.cache");
+    let card = |name: &str| {
+        format!(
+            r#"[{{"name": "{}", "mana_cost": "{{W}}", "type_line": "Creature — Human",
+                "oracle_text": "Vigilance", "power": "1", "toughness": "1"}}]"#,
+            name
+        )
+    };
+    std::fs::write(&path, card("Grizzled Recruit")).unwrap();
+    CardDB::from_path(&path).unwrap();
+    assert!(cache.exists());
+    let cached: &'static CardDB = Box::leak(Box::new(CardDB::from_path(&path).unwrap()));
+    let owner = PlayerId::from(NonZeroU64::new(1).unwrap());
+    let name = cached.card_name("Grizzled Recruit").unwrap();
+    let spawned = cached.try_spawn_card(name, owner).unwrap();
+    assert_eq!(spawned.pt.map(|pt| (pt.power, pt.toughness)), Some((1, 1)));
+    std::fs::write(&path, card("Seasoned Recruit")).unwrap();
+    let changed = CardDB::from_path(&path).unwrap();
+    assert!(changed.entry("Seasoned Recruit").is_some());
+    assert!(changed.entry("Grizzled Recruit").is_none());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+#[test]
+fn binaries_take_the_database_from_their_arguments() {
+    let args = ["deck.txt", "--cards", FIXTURE_PATH, "--games", "3"];
+    let (db, rest) = CardDB::from_args(args.map(String::from)).unwrap();
+    assert!(db.entry("Plains").is_some());
+    assert_eq!(rest, vec!["deck.txt", "--games", "3"]);
+    assert!(CardDB::from_args(["--cards".to_owned()]).is_err());
+}
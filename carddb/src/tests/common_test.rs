@@ -0,0 +1,9 @@
+use crate::carddb::CardDB;
+use once_cell::sync::OnceCell;
+
+static CARDDB: OnceCell<CardDB> = OnceCell::new();
+
+//The database the tests share, read once for the whole test run
+pub fn db() -> &'static CardDB {
+    CARDDB.get_or_init(|| CardDB::for_tests())
+}
@@ -0,0 +1,55 @@
+use crate::carddb::CardDB;
+use crate::coverage::{coverage, Coverage};
+
+fn db(json: &str) -> &'static CardDB {
+    Box::leak(Box::new(CardDB::from_reader(json.as_bytes()).unwrap()))
+}
+#[test]
+fn failures_are_grouped_by_where_parsing_stopped() {
+    let db = db(r#"[
+        {"name": "Plains", "set": "m21", "mana_cost": "",
+         "type_line": "Basic Land — Plains", "oracle_text": "({T}: Add {W}.)"},
+        {"name": "Gibberish", "set": "m21", "mana_cost": "{W}",
+         "type_line": "Creature — Human", "oracle_text": "Frobnicate the widgets.",
+         "power": "1", "toughness": "1"},
+        {"name": "Nonsense", "set": "znr", "mana_cost": "{U}",
+         "type_line": "Instant", "oracle_text": "Frobnicate the widgets."},
+        {"name": "Costless", "set": "znr", "type_line": "Artifact Creature — Golem",
+         "power": "1", "toughness": "1"}
+    ]"#);
+    let report = coverage(db);
+    assert_eq!(
+        report.total,
+        Coverage {
+            cards: 4,
+            spawned: 1
+        }
+    );
+    assert_eq!(
+        report.by_set["m21"],
+        Coverage {
+            cards: 2,
+            spawned: 1
+        }
+    );
+    assert_eq!(report.by_set["znr"].percent(), 0.0);
+    assert_eq!(report.by_type["Creature"].cards, 2);
+    assert_eq!(report.by_type["Artifact"].cards, 1);
+    assert_eq!(report.by_kind["OracleText"], 2);
+    assert_eq!(report.by_kind["ManaCost"], 1);
+    let widgets = &report.by_span[0];
+    assert_eq!(widgets.cards, 2);
+    assert!(widgets.span.starts_with("frobnicate"), "{}", widgets.span);
+    assert_eq!(widgets.examples, vec!["Gibberish", "Nonsense"]);
+    let failed: Vec<&String> = report.failures.keys().collect();
+    assert_eq!(failed, vec!["Costless", "Gibberish", "Nonsense"]);
+}
+#[test]
+fn the_fixture_keeps_one_card_that_cant_be_parsed() {
+    let db: &'static CardDB = Box::leak(Box::new(CardDB::fixture()));
+    let report = coverage(db);
+    let failed: Vec<&String> = report.failures.keys().collect();
+    assert_eq!(failed, vec!["Griselbrand"]);
+    assert_eq!(report.total.spawned, report.total.cards - 1);
+    assert_eq!(report.by_kind["OracleText"], 1);
+}
@@ -0,0 +1,89 @@
+use crate::deck_validation::{validate_deck, DeckViolation};
+use crate::tests::common_test::db;
+use common::format::Format;
+
+fn deck(cards: &[(usize, &'static str)]) -> Vec<&'static str> {
+    let mut deck = Vec::new();
+    for &(count, name) in cards {
+        for _ in 0..count {
+            deck.push(name);
+        }
+    }
+    deck
+}
+#[test]
+fn standard_deck() {
+    let legal = deck(&[
+        (4, "Alpine Watchdog"),
+        (4, "Baneslayer Angel"),
+        (52, "Plains"),
+    ]);
+    assert_eq!(validate_deck(db(), Format::Standard, &legal, None), Ok(()));
+    let illegal = deck(&[(5, "Alpine Watchdog"), (4, "Murder"), (40, "Plains")]);
+    let violations = validate_deck(db(), Format::Standard, &illegal, None).unwrap_err();
+    assert!(violations.contains(&DeckViolation::TooFewCards { min: 60, found: 49 }));
+    assert!(violations.contains(&DeckViolation::TooManyCopies {
+        name: "Alpine Watchdog".to_owned(),
+        max: 4,
+        found: 5
+    }));
+    assert!(violations.contains(&DeckViolation::NotLegal {
+        name: "Murder".to_owned()
+    }));
+    assert_eq!(violations.len(), 3);
+}
+#[test]
+fn limited_deck() {
+    let legal = deck(&[(10, "Murder"), (30, "Plains")]);
+    assert_eq!(validate_deck(db(), Format::Limited, &legal, None), Ok(()));
+    let short = deck(&[(39, "Plains")]);
+    assert_eq!(
+        validate_deck(db(), Format::Limited, &short, None),
+        Err(vec![DeckViolation::TooFewCards { min: 40, found: 39 }])
+    );
+}
+#[test]
+fn commander_deck() {
+    let legal = deck(&[
+        (1, "Alpine Watchdog"),
+        (1, "Baneslayer Angel"),
+        (97, "Plains"),
+    ]);
+    let commander = Some("Isamaru, Hound of Konda");
+    assert_eq!(
+        validate_deck(db(), Format::Commander, &legal, commander),
+        Ok(())
+    );
+    let illegal = deck(&[(2, "Alpine Watchdog"), (1, "Murder"), (96, "Plains")]);
+    let violations = validate_deck(db(), Format::Commander, &illegal, commander).unwrap_err();
+    assert_eq!(
+        violations,
+        vec![
+            DeckViolation::OutsideColorIdentity {
+                name: "Murder".to_owned()
+            },
+            DeckViolation::TooManyCopies {
+                name: "Alpine Watchdog".to_owned(),
+                max: 1,
+                found: 2
+            },
+        ]
+    );
+    assert_eq!(
+        validate_deck(db(), Format::Commander, &legal, None),
+        Err(vec![
+            DeckViolation::WrongSize {
+                size: 100,
+                found: 99
+            },
+            DeckViolation::MissingCommander
+        ])
+    );
+    let banned = validate_deck(db(), Format::Commander, &legal, Some("Griselbrand")).unwrap_err();
+    assert!(banned.contains(&DeckViolation::Banned {
+        name: "Griselbrand".to_owned()
+    }));
+    assert!(banned.contains(&DeckViolation::OutsideColorIdentity {
+        name: "Plains".to_owned()
+    }));
+}
@@ -0,0 +1,126 @@
+use crate::decklist::{CardError, CardErrorReason, DeckEntry, DeckError, DeckSubmission};
+use crate::tests::common_test::db;
+
+#[test]
+fn parse_text_decklist() {
+    let text = "Deck\n4 Plains\n\n// creatures\n2x Baneslayer Angel\nSideboard\n3 Murder";
+    let entries = DeckSubmission::Text(text.to_owned())
+        .decklist()
+        .unwrap()
+        .entries;
+    assert_eq!(
+        entries,
+        vec![
+            DeckEntry {
+                count: 4,
+                name: "Plains".to_owned()
+            },
+            DeckEntry {
+                count: 2,
+                name: "Baneslayer Angel".to_owned()
+            },
+        ]
+    );
+}
+#[test]
+fn malformed_lines() {
+    let err = DeckSubmission::Text("4 Plains\nPlains\n0 Murder".to_owned())
+        .decklist()
+        .unwrap_err();
+    let DeckError::Cards(cards) = err else {
+        panic!("expected card errors");
+    };
+    let reasons: Vec<_> = cards.into_iter().map(|card| card.reason).collect();
+    assert_eq!(
+        reasons,
+        vec![
+            CardErrorReason::MalformedLine(2),
+            CardErrorReason::MalformedLine(3)
+        ]
+    );
+}
+#[test]
+fn json_decklist() {
+    let submission: DeckSubmission =
+        serde_json::from_str(r#"[{"count":3,"name":"Plains"},{"count":1,"name":"Murder"}]"#)
+            .unwrap();
+    let deck = submission.resolve(db()).unwrap();
+    assert_eq!(deck.cards, vec!["Plains", "Plains", "Plains", "Murder"]);
+    assert_eq!(deck.commander, None);
+}
+#[test]
+fn unknown_cards_are_reported() {
+    let submission = DeckSubmission::Text("4 Plains\n2 Not A Real Card".to_owned());
+    let err = submission.resolve(db()).unwrap_err();
+    assert_eq!(
+        err,
+        DeckError::Cards(vec![CardError {
+            name: "Not A Real Card".to_owned(),
+            reason: CardErrorReason::NotFound
+        }])
+    );
+}
+#[test]
+fn empty_deck() {
+    let submission = DeckSubmission::Text("// nothing here\n".to_owned());
+    assert_eq!(submission.decklist(), Err(DeckError::Empty));
+}
+#[test]
+fn commander_decklist() {
+    let text = "Commander\n1 Isamaru, Hound of Konda\n\nDeck\n1 Alpine Watchdog\n98 Plains";
+    let deck = DeckSubmission::Text(text.to_owned()).resolve(db()).unwrap();
+    assert_eq!(deck.commander, Some("Isamaru, Hound of Konda"));
+    assert_eq!(deck.cards.len(), 99);
+    let submission: DeckSubmission = serde_json::from_str(
+        r#"{"commander":"Isamaru, Hound of Konda","deck":[{"count":99,"name":"Plains"}]}"#,
+    )
+    .unwrap();
+    let deck = submission.resolve(db()).unwrap();
+    assert_eq!(deck.commander, Some("Isamaru, Hound of Konda"));
+    assert_eq!(deck.cards.len(), 99);
+}
+#[test]
+fn oversized_counts_are_rejected() {
+    let err = DeckSubmission::Text("4 Plains\n1000000000000 Plains".to_owned())
+        .resolve(db())
+        .unwrap_err();
+    assert_eq!(
+        err,
+        DeckError::Cards(vec![CardError {
+            name: "Plains".to_owned(),
+            reason: CardErrorReason::TooManyCopies(1000000000000)
+        }])
+    );
+    let submission: DeckSubmission =
+        serde_json::from_str(r#"[{"count":1000000000000,"name":"Plains"}]"#).unwrap();
+    assert_eq!(
+        submission.resolve(db()),
+        Err(DeckError::Cards(vec![CardError {
+            name: "Plains".to_owned(),
+            reason: CardErrorReason::TooManyCopies(1000000000000)
+        }]))
+    );
+}
+#[test]
+fn oversized_decks_are_rejected() {
+    let text = "250 Plains\n250 Island\n250 Swamp\n250 Mountain\n250 Forest";
+    assert_eq!(
+        DeckSubmission::Text(text.to_owned()).decklist(),
+        Err(DeckError::TooManyCards(1250))
+    );
+    let text = "Commander\n1 Isamaru, Hound of Konda\nDeck\n250 Plains\n250 Island\n250 Swamp\n250 Mountain";
+    assert_eq!(
+        DeckSubmission::Text(text.to_owned()).resolve(db()),
+        Err(DeckError::TooManyCards(1001))
+    );
+}
+#[test]
+fn card_names_ignore_case() {
+    let deck = DeckSubmission::Text("4 plains\n1 MURDER".to_owned())
+        .resolve(db())
+        .unwrap();
+    assert_eq!(
+        deck.cards,
+        vec!["Plains", "Plains", "Plains", "Plains", "Murder"]
+    );
+}
@@ -0,0 +1,59 @@
+use crate::carddb::CardDB;
+use crate::spawn_error::{CardPart, SpawnError};
+use common::entities::PlayerId;
+use std::num::NonZeroU64;
+
+fn spawn_error(json: &str, name: &str) -> SpawnError {
+    let db: &'static CardDB = Box::leak(Box::new(CardDB::from_reader(json.as_bytes()).unwrap()));
+    let owner = PlayerId::from(NonZeroU64::new(1).unwrap());
+    let name = db.card_name(name).unwrap_or("Missing");
+    db.try_spawn_card(name, owner).unwrap_err()
+}
+#[test]
+fn parse_errors_point_at_the_line_and_token() {
+    let err = spawn_error(
+        r#"[{"name": "Gibberish", "mana_cost": "{W}", "type_line": "Creature — Human",
+            "oracle_text": "Flying\nLifelink\nWhen Gibberish enters, frobnicate the widgets.",
+            "power": "1", "toughness": "1"}]"#,
+        "Gibberish",
+    );
+    let SpawnError::Parse(err) = &err else {
+        panic!("expected a parse error, got {:?}", err)
+    };
+    assert_eq!(err.card, "Gibberish");
+    assert_eq!(err.part, CardPart::OracleText);
+    assert_eq!(err.line_number, 3);
+    assert_eq!(err.line, "When Gibberish enters, frobnicate the widgets.");
+    assert_eq!(err.tokens.first().map(|t| t.as_str()), Some("when"));
+    assert!(err.token < err.tokens.len());
+    let diagnostic = err.to_string();
+    assert!(
+        diagnostic.starts_with("couldn't parse Gibberish's card text, line 3"),
+        "{}",
+        diagnostic
+    );
+    assert!(diagnostic.contains(&err.line), "{}", diagnostic);
+    assert!(diagnostic.ends_with('^'), "{}", diagnostic);
+}
+#[test]
+fn text_after_the_mana_cost_is_an_error() {
+    let err = spawn_error(
+        r#"[{"name": "Misprint", "mana_cost": "{W}oops", "type_line": "Instant",
+            "oracle_text": "Draw a card."}]"#,
+        "Misprint",
+    );
+    let SpawnError::Parse(err) = &err else {
+        panic!("expected a parse error, got {:?}", err)
+    };
+    assert_eq!(err.part, CardPart::ManaCost);
+    assert_eq!(err.token, 3);
+    assert_eq!(err.near(), "oops");
+    assert_eq!(err.contexts, vec!["unexpected text after the mana cost"]);
+    assert!(err.to_string().ends_with("\n     ^"), "{}", err);
+}
+#[test]
+fn missing_cards_are_errors() {
+    let err = spawn_error("[]", "Missing");
+    assert_eq!(err, SpawnError::CardNotFound("Missing".to_owned()));
+    assert_eq!(err.summary(), "no card named Missing");
+}
@@ -0,0 +1,95 @@
+use crate::carddb::CardDB;
+use crate::query::{CardQuery, CardSummary};
+
+fn db() -> &'static CardDB {
+    let json = r#"[
+        {"name": "Plains", "mana_cost": "", "type_line": "Basic Land — Plains",
+         "oracle_text": "({T}: Add {W}.)", "color_identity": ["W"], "cmc": 0.0},
+        {"name": "Staunch Shieldmate", "mana_cost": "{W}", "type_line": "Creature — Dwarf Soldier",
+         "power": "1", "toughness": "3", "color_identity": ["W"], "cmc": 1.0,
+         "image_uris": {"small": "https://example.com/small.jpg", "normal": "https://example.com/normal.jpg"}},
+        {"name": "Gibberish", "mana_cost": "{1}{U}", "type_line": "Creature — Human Wizard",
+         "oracle_text": "Frobnicate the widgets.", "power": "1", "toughness": "1",
+         "color_identity": ["U"], "cmc": 2.0},
+        {"name": "Shield Wall", "mana_cost": "{1}{W}{U}", "type_line": "Instant",
+         "oracle_text": "Creatures you control get +0/+2 until end of turn.",
+         "color_identity": ["U", "W"], "cmc": 3.0}
+    ]"#;
+    Box::leak(Box::new(CardDB::from_reader(json.as_bytes()).unwrap()))
+}
+fn names(found: Vec<&crate::carddb::ScryfallEntry>) -> Vec<&str> {
+    found.iter().map(|entry| &*entry.name).collect()
+}
+#[test]
+fn names_are_found_in_any_case() {
+    let db = db();
+    assert_eq!(
+        db.find("staunch SHIELDMATE").map(|e| &*e.name),
+        Some("Staunch Shieldmate")
+    );
+    assert!(db.find("Staunch").is_none());
+    assert_eq!(
+        names(db.fuzzy("shield", 5)),
+        vec!["Shield Wall", "Staunch Shieldmate"]
+    );
+    assert_eq!(
+        names(db.fuzzy("Stanch Sheildmate", 5)),
+        vec!["Staunch Shieldmate"]
+    );
+    assert!(db.fuzzy("Island", 5).is_empty());
+}
+#[test]
+fn searches_combine_filters() {
+    let db = db();
+    let query = |query: CardQuery| names(db.search(&query).unwrap());
+    let creatures = CardQuery {
+        type_line: Some("creature".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(
+        query(creatures.clone()),
+        vec!["Gibberish", "Staunch Shieldmate"]
+    );
+    let white = CardQuery {
+        colors: Some("w".to_owned()),
+        ..creatures.clone()
+    };
+    assert_eq!(query(white), vec!["Staunch Shieldmate"]);
+    let cheap = CardQuery {
+        min_cmc: Some(1.0),
+        max_cmc: Some(2.0),
+        ..Default::default()
+    };
+    assert_eq!(query(cheap), vec!["Gibberish", "Staunch Shieldmate"]);
+    let text = CardQuery {
+        oracle: Some("UNTIL END OF TURN".to_owned()),
+        ..Default::default()
+    };
+    assert_eq!(query(text), vec!["Shield Wall"]);
+    let playable = CardQuery {
+        spawnable: true,
+        ..creatures
+    };
+    assert!(db.search(&playable).is_none());
+    db.check_spawnable();
+    assert_eq!(query(playable), vec!["Staunch Shieldmate"]);
+}
+#[test]
+fn spawnable_cards_leave_out_parse_failures() {
+    let db = db();
+    assert!(db.spawnable().is_none());
+    let spawnable = db.check_spawnable();
+    assert_eq!(db.spawnable(), Some(spawnable));
+    assert!(spawnable.contains(&"Plains"));
+    assert!(!spawnable.contains(&"Gibberish"));
+}
+#[test]
+fn summaries_prefer_the_normal_image() {
+    let summary = CardSummary::from(db().find("Staunch Shieldmate").unwrap());
+    assert_eq!(
+        summary.image.as_deref(),
+        Some("https://example.com/normal.jpg")
+    );
+    assert_eq!(summary.color_identity, vec!["W"]);
+    assert_eq!(summary.power.as_deref(), Some("1"));
+}
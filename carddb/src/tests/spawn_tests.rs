@@ -1,10 +1,9 @@
-use crate::{carddb::CardDB, spawn_error::SpawnError};
+use crate::carddb::CardDB;
+use crate::tests::common_test::db;
 use std::num::NonZeroU64;
 
 use common::{card_entities::CardEnt, entities::PlayerId};
-use once_cell::sync::OnceCell;
 use test_log;
-static CARDDB: OnceCell<CardDB> = OnceCell::new();
 
 #[test_log::test]
 fn card_tests() {
@@ -23,9 +22,6 @@ fn card_tests() {
     test_card(db(), "Concordia Pegasus");
 }
 #[allow(dead_code)]
-fn db() -> &'static CardDB {
-    CARDDB.get_or_init(|| CardDB::new())
-}
 #[test_log::test]
 fn revitalize_test() {
     test_card(db(), "Revitalize");
@@ -93,17 +89,9 @@ fn falconer_adept() {
 #[allow(dead_code)]
 fn test_card(db: &CardDB, card_name: &'static str) -> CardEnt {
     let spawned = db.try_spawn_card(card_name, PlayerId::from(NonZeroU64::new(1).unwrap()));
-    if spawned.is_err() {
-        println!("card {} failed to spawn", card_name);
+    if let Err(err) = &spawned {
+        println!("card {} failed to spawn\n{}", card_name, err);
     }
-    if let Err(err)=&spawned
-        && let SpawnError::Nom(err)=err
-        && let nom::Err::Error(err)=err{
-            println!("spawn error[");
-            for error in &err.errors{
-                println!("{:?}",error);
-            } 
-        }
     let spawned = spawned.unwrap();
     //println!("{:?}", spawned);
     spawned
@@ -2,7 +2,8 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
 use mtg_log_macro::MTGLoggable;
-use crate::log::{MTGLog,GameContext};
+use crate::entities::CardId;
+use crate::log::{MTGLog,GameContext,LogCards};
 
 use crate::cost::Cost;
 use crate::mana::ManaCostSymbol;
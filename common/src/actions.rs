@@ -1,6 +1,6 @@
 use mtg_log_macro::MTGLoggable;
 use schemars::JsonSchema;
-use crate::log::{MTGLog, GameContext};
+use crate::log::{MTGLog, GameContext, LogCards};
 use serde::{Deserialize, Serialize};
 
 use crate::{
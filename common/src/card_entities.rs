@@ -2,7 +2,7 @@ use crate::{
     cardtypes::{Subtypes, Supertypes, Types},
     counters::Counter,
     entities::MIN_CARDID,
-    hashset_obj::HashSetObj,
+    hashset_obj::{HashSetObj, StableState},
     mana::Color,
     spellabil::Clause,
 };
@@ -20,7 +20,7 @@ use crate::{
 };
 
 use mtg_log_macro::MTGLoggable;
-use crate::log::{MTGLog,GameContext};
+use crate::log::{MTGLog,GameContext,LogCards};
 #[derive(Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema, Debug, MTGLoggable)]
 pub enum EntType {
     RealCard,
@@ -51,7 +51,7 @@ pub struct CardEnt {
     #[derivative(Default(value = "PlayerId::from(NonZeroU64::new(MIN_CARDID-1).unwrap())"))]
     pub owner: PlayerId,
     pub ent_type: EntType,
-    pub known_to: HashSet<PlayerId>, //What players know the front side of this card?
+    pub known_to: HashSet<PlayerId, StableState>, //What players know the front side of this card?
     pub pt: Option<PT>,
     controller: Option<PlayerId>,
     pub types: Types,
@@ -68,6 +68,7 @@ pub struct CardEnt {
     pub counters: Vec<Counter>,
     pub cast: bool,
     pub enchanting_or_equipping: Option<TargetId>,
+    pub commander: bool, //Stays set as the card changes zones
 }
 impl CardEnt {
     pub fn has_keyword(&self, keyword: KeywordAbility) -> bool {
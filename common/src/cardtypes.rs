@@ -8,7 +8,8 @@ use std::str::FromStr;
 use strum_macros::EnumString;
 use texttoken::Tokens;
 use mtg_log_macro::MTGLoggable;
-use crate::log::{MTGLog,GameContext};
+use crate::entities::CardId;
+use crate::log::{MTGLog,GameContext,LogCards};
 
 use crate::hashset_obj::HashSetObj;
 
@@ -4,7 +4,7 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
 use mtg_log_macro::MTGLoggable;
-use crate::log::{MTGLog,GameContext};
+use crate::log::{MTGLog,GameContext,LogCards};
 
 #[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq, MTGLoggable)]
 pub enum Cost {
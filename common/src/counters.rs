@@ -1,7 +1,8 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use mtg_log_macro::MTGLoggable;
-use crate::log::{MTGLog,GameContext};
+use crate::entities::CardId;
+use crate::log::{MTGLog,GameContext,LogCards};
 
 #[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq, Copy, MTGLoggable)]
 pub enum Counter {
@@ -3,6 +3,8 @@ use std::{collections::HashMap, hash::Hash, num::NonZeroU64};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::hashset_obj::StableState;
+
 #[derive(Clone, JsonSchema, Serialize, Debug)]
 pub struct EntMap<K, V>
 where
@@ -10,7 +12,7 @@ where
     V: JsonSchema,
 {
     #[serde(flatten)]
-    ents: HashMap<K, V>,
+    ents: HashMap<K, V, StableState>,
     #[serde(skip)]
     count: NonZeroU64,
 }
@@ -36,7 +38,7 @@ where
         }
         //This unsafe is fine becuase serde-json doesn't borrow from its input
         let buf_ref: &[u8] = unsafe { std::mem::transmute(&*buf) };
-        let base: HashMap<K, V> =
+        let base: HashMap<K, V, StableState> =
             serde_json::from_slice(&buf_ref).map_err(serde::de::Error::custom)?;
         Ok(Self {
             ents: base,
@@ -61,7 +63,7 @@ where
 {
     pub fn new() -> Self {
         Self {
-            ents: HashMap::new(),
+            ents: HashMap::default(),
             count: NonZeroU64::new(1).unwrap(),
         }
     }
@@ -4,7 +4,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use serde::{Deserializer, Serializer};
 use mtg_log_macro::MTGLoggable;
-use crate::log::{MTGLog,GameContext};
+use crate::log::{MTGLog,GameContext,LogCards};
 
 #[derive(
     Copy, Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize, JsonSchema, PartialOrd, Ord,
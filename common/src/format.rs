@@ -0,0 +1,23 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+//The deck construction rules a game is played under
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema)]
+pub enum Format {
+    #[default]
+    Casual, //Anything goes, no deck construction rules are checked
+    Standard,
+    Limited,
+    Commander,
+}
+impl Format {
+    //The key for this format in scryfall's legalities,
+    //formats without one don't have a ban list
+    pub fn scryfall_key(&self) -> Option<&'static str> {
+        match self {
+            Self::Casual | Self::Limited => None,
+            Self::Standard => Some("standard"),
+            Self::Commander => Some("commander"),
+        }
+    }
+}
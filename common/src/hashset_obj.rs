@@ -2,15 +2,21 @@
 //This will make the front end far simpler and reduce bugs there
 use schemars::JsonSchema;
 use serde::{self, Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
-use std::hash::Hash;
+use std::hash::{BuildHasherDefault, Hash};
+
+//Hashes the same way in every process, so iteration order only depends on what
+//was inserted. Options sent to players come from iterating these, and replays
+//need them in the same order to pick the same ones
+pub type StableState = BuildHasherDefault<DefaultHasher>;
 #[derive(Clone, Debug, Serialize, JsonSchema)]
 #[serde(transparent)]
 pub struct HashSetObj<T>
 where
     T: Hash + Eq,
 {
-    base: HashMap<T, ()>,
+    base: HashMap<T, (), StableState>,
 }
 
 impl<T> HashSetObj<T>
@@ -28,7 +34,7 @@ where
     }
     pub fn new() -> Self {
         HashSetObj {
-            base: HashMap::new(),
+            base: HashMap::default(),
         }
     }
     pub fn contains(&self, value: &T) -> bool {
@@ -79,7 +85,7 @@ where
         }
         //This unsafe is fine becuase serde-json doesn't borrow from its input
         let buf_ref: &[u8] = unsafe { std::mem::transmute(&*buf) };
-        let base: HashMap<T, ()> =
+        let base: HashMap<T, (), StableState> =
             serde_json::from_slice(&buf_ref).map_err(serde::de::Error::custom)?;
         Ok(Self { base })
     }
@@ -98,7 +104,7 @@ where
     T: Hash + Eq,
 {
     fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
-        let mut c = HashMap::new();
+        let mut c = HashMap::default();
 
         for i in iter {
             c.insert(i, ());
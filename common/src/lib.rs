@@ -7,9 +7,11 @@ pub mod cardtypes;
 pub mod cost;
 pub mod counters;
 pub mod entities;
+pub mod format;
 pub mod hashset_obj;
 pub mod log;
 pub mod mana;
+pub mod panic;
 pub mod spellabil;
 pub mod token_attribute;
 pub mod zones;
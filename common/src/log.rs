@@ -1,4 +1,7 @@
 use crate::{ entities::{CardId, PlayerId}, ent_maps::EntMap, card_entities::CardEnt};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 pub struct GameContext<'a>{
     pub cards: &'a EntMap<CardId,CardEnt>,
@@ -8,12 +11,63 @@ pub trait MTGLog{
     type LogType;
     fn mtg_log(&self, game_context: &GameContext) -> Self::LogType;
 }
-#[allow(dead_code)] //Not dead, used for logging
-#[derive(Clone,Debug)]
+#[derive(Clone,Debug,Serialize,Deserialize,JsonSchema)]
 pub struct CardIdContext{
-    card_id: CardId,
-    name: Option<String>,
-    controller: Option<PlayerId>,
+    pub card_id: CardId,
+    pub name: Option<String>,
+    pub controller: Option<PlayerId>,
+}
+//The cards a logged value names, for working out who may see it. Each group is one
+//card, a card that changed zones is one group of its id before and after the move
+pub trait LogCards{
+    fn log_cards(&self, cards: &mut Vec<Vec<CardId>>);
+    fn cards(&self) -> Vec<Vec<CardId>>{
+        let mut cards=Vec::new();
+        self.log_cards(&mut cards);
+        cards
+    }
+}
+impl LogCards for CardIdContext{
+    fn log_cards(&self, cards: &mut Vec<Vec<CardId>>){
+        cards.push(vec![self.card_id]);
+    }
+}
+impl <T:LogCards> LogCards for Option<T>{
+    fn log_cards(&self, cards: &mut Vec<Vec<CardId>>){
+        if let Some(x)=self{
+            x.log_cards(cards);
+        }
+    }
+}
+impl <T:LogCards> LogCards for Vec<T>{
+    fn log_cards(&self, cards: &mut Vec<Vec<CardId>>){
+        for x in self{
+            x.log_cards(cards);
+        }
+    }
+}
+impl <T:LogCards> LogCards for Box<T>{
+    fn log_cards(&self, cards: &mut Vec<Vec<CardId>>){
+        T::log_cards(self, cards);
+    }
+}
+impl <K:LogCards,V:LogCards> LogCards for (K,V){
+    fn log_cards(&self, cards: &mut Vec<Vec<CardId>>){
+        self.0.log_cards(cards);
+        self.1.log_cards(cards);
+    }
+}
+impl LogCards for i64{
+    fn log_cards(&self, _cards: &mut Vec<Vec<CardId>>){}
+}
+impl LogCards for String{
+    fn log_cards(&self, _cards: &mut Vec<Vec<CardId>>){}
+}
+impl LogCards for PlayerId{
+    fn log_cards(&self, _cards: &mut Vec<Vec<CardId>>){}
+}
+impl LogCards for bool{
+    fn log_cards(&self, _cards: &mut Vec<Vec<CardId>>){}
 }
 impl MTGLog for CardId{
     type LogType = CardIdContext;
@@ -72,4 +126,17 @@ impl MTGLog for PlayerId{
     fn mtg_log(&self, _game_context: &GameContext) -> Self::LogType{
         *self
     }
-}
\ No newline at end of file
+}
+impl MTGLog for bool{
+    type LogType = bool;
+    fn mtg_log(&self, _game_context: &GameContext) -> Self::LogType{
+        *self
+    }
+}
+//Logged keys aren't always valid map keys once serialized, so maps are logged as pairs
+impl <K:MTGLog,V:MTGLog> MTGLog for HashMap<K,V>{
+    type LogType = Vec<(<K as MTGLog>::LogType,<V as MTGLog>::LogType)>;
+    fn mtg_log(&self, game_context: &GameContext) -> Self::LogType{
+        self.iter().map(|(k,v)| (k.mtg_log(game_context),v.mtg_log(game_context))).collect()
+    }
+}
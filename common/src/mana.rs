@@ -4,7 +4,8 @@ use serde::Deserialize;
 use serde_derive::Serialize;
 use strum::EnumString;
 use mtg_log_macro::MTGLoggable;
-use crate::log::{MTGLog,GameContext};
+use crate::entities::CardId;
+use crate::log::{MTGLog,GameContext,LogCards};
 
 #[derive(
     Clone, Copy, Debug, PartialEq, Serialize, Deserialize, Enum, JsonSchema, EnumString, Eq, Hash, MTGLoggable
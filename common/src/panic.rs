@@ -0,0 +1,12 @@
+use std::any::Any;
+
+//The message a caught panic was raised with, if it was raised with a string
+pub fn panic_message(panic: Box<dyn Any + Send>) -> Option<String> {
+    match panic.downcast::<String>() {
+        Ok(message) => Some(*message),
+        Err(panic) => panic
+            .downcast::<&'static str>()
+            .ok()
+            .map(|message| message.to_string()),
+    }
+}
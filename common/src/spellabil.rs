@@ -6,7 +6,7 @@ use crate::entities::CardId;
 use crate::mana::ManaCostSymbol;
 use crate::{entities::TargetId, token_attribute::TokenAttribute};
 use mtg_log_macro::MTGLoggable;
-use crate::log::{MTGLog,GameContext};
+use crate::log::{MTGLog,GameContext,LogCards};
 use schemars::JsonSchema;
 use serde::Deserialize;
 use serde_derive::Serialize;
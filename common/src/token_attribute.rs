@@ -1,7 +1,8 @@
 use mtg_log_macro::MTGLoggable;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use crate::log::{MTGLog,GameContext};
+use crate::entities::CardId;
+use crate::log::{MTGLog,GameContext,LogCards};
 
 use crate::{
     ability::Ability,
@@ -1,8 +1,10 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use mtg_log_macro::MTGLoggable;
+use crate::entities::CardId;
 use crate::log::MTGLog;
 use crate::log::GameContext;
+use crate::log::LogCards;
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, JsonSchema, MTGLoggable)]
 pub enum Zone {
@@ -0,0 +1,500 @@
+use crate::client_message::{AskPair, AskSelectN, GameState, RequestId};
+use crate::decision::{Answer, AskContext, DecisionProvider, Gone};
+use crate::game::Phase;
+use async_trait::async_trait;
+use common::ability::Ability;
+use common::actions::Action;
+use common::card_entities::{CardEnt, PT};
+use common::cost::Cost;
+use common::entities::{CardId, PlayerId, TargetId};
+use common::hashset_obj::HashSetObj;
+use common::mana::{Color, ManaCostSymbol};
+use common::spellabil::{Affected, Clause, ClauseEffect, ContEffect};
+use std::collections::{HashMap, HashSet};
+
+//A computer player. It follows the game like any client, through the states
+//it's sent, and answers each ask with a few rules of thumb: play a land, cast
+//the most expensive spell it can pay for, attack when no blocker can eat the
+//attacker, block when the attack would be lethal, target the opponent's best cards
+#[derive(Default)]
+pub struct AiClient {
+    game: Option<GameState>,
+    rejections: usize, //In a row, for the ask being answered
+}
+impl AiClient {
+    //Answers from a state it's handed instead of one it's been sent
+    pub(crate) fn seeing(game: GameState) -> Self {
+        AiClient {
+            game: Some(game),
+            rejections: 0,
+        }
+    }
+    //Rejections shouldn't happen, but if one does the smallest answer is
+    //tried next. If even that is rejected the AI gives up and forfeits
+    fn choose<T>(
+        &mut self,
+        smallest: impl FnOnce() -> T,
+        choose: impl FnOnce(&GameState, PlayerId) -> T,
+    ) -> Answer<T> {
+        match self.rejections {
+            0 => {}
+            1 => return Ok(smallest()),
+            _ => return Err(Gone),
+        }
+        let game = self.game.as_ref().expect("states are sent before asks");
+        let me = game.player.expect("players are sent states as themselves");
+        Ok(choose(game, me))
+    }
+}
+//The fewest entities the ask allows
+fn smallest<T>(ask: &AskSelectN<T>) -> impl FnOnce() -> HashSetObj<usize> {
+    let min = ask.min.try_into().unwrap_or(0);
+    move || (0..min).collect()
+}
+#[async_trait]
+impl DecisionProvider for AiClient {
+    async fn state(&mut self, state: &GameState) {
+        self.game = Some(state.clone());
+    }
+    async fn select_action(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.choose(smallest(ask), |game, me| choose_action(game, me, ask))
+    }
+    async fn select_targets(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.choose(smallest(ask), |game, me| choose_targets(game, me, ask))
+    }
+    async fn select_attackers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        self.choose(HashMap::new, |game, me| choose_attacks(game, me, ask))
+    }
+    async fn select_blockers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        self.choose(HashMap::new, |game, me| choose_blocks(game, me, ask))
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.choose(smallest(ask), |game, me| choose_discards(game, me, ask))
+    }
+    async fn command_zone(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.choose(smallest(ask), |_, _| (0..ask.ents.len()).collect())
+    }
+    async fn rejected(&mut self, id: RequestId, reason: &str) {
+        log::warn!("AI response to {} was rejected: {}", id, reason);
+        self.rejections += 1;
+    }
+    async fn accepted(&mut self, _id: RequestId) {
+        self.rejections = 0;
+    }
+}
+
+fn mana_value(costs: &[Cost]) -> usize {
+    costs.iter().filter(|cost| cost.is_mana()).count()
+}
+fn power_toughness(card: &CardEnt) -> Option<PT> {
+    card.pt.as_ref().map(|pt| PT {
+        power: pt.power,
+        toughness: pt.toughness - card.damaged,
+    })
+}
+//How much the AI cares about a card, bigger creatures and pricier spells are worth more
+fn card_value(card: &CardEnt) -> i64 {
+    let body = card.pt.as_ref().map_or(0, |pt| pt.power + pt.toughness);
+    body + mana_value(&card.costs) as i64
+}
+fn sorted_cards(game: &GameState) -> Vec<(CardId, &CardEnt)> {
+    let mut cards: Vec<(CardId, &CardEnt)> =
+        game.cards.iter().map(|(&id, card)| (id, card)).collect();
+    cards.sort_by_key(|(id, _)| *id);
+    cards
+}
+//The opponents' creatures that could block the player's attackers
+fn possible_blockers(game: &GameState, me: PlayerId) -> Vec<PT> {
+    sorted_cards(game)
+        .into_iter()
+        .filter(|(id, card)| {
+            game.game.battlefield.contains(id)
+                && card.types.is_creature()
+                && !card.tapped
+                && card.get_controller() != me
+        })
+        .filter_map(|(_, card)| power_toughness(card))
+        .collect()
+}
+
+//The colors of mana an ability taps for, if it's a mana ability that only costs tapping
+fn mana_ability_colors(card: &CardEnt, index: usize) -> Option<Vec<Color>> {
+    let Some(Ability::Activated(ability)) = card.abilities.get(index) else {
+        return None;
+    };
+    if card.tapped || ability.costs != vec![Cost::Selftap] {
+        return None;
+    }
+    let mut colors = Vec::new();
+    for clause in &ability.effect {
+        let ClauseEffect::AddMana(symbols) = &clause.effect else {
+            return None;
+        };
+        colors.extend(symbols.iter().filter_map(|symbol| match symbol {
+            ManaCostSymbol::Generic => None,
+            symbol => symbol.spendable_colors().first().copied(),
+        }));
+    }
+    Some(colors)
+}
+//The mana costs left unpaid after spending what's available, paid the same way the engine does
+fn unpaid(costs: &[Cost], available: &[Color]) -> Vec<ManaCostSymbol> {
+    let mut available = available.to_vec();
+    let mut symbols: Vec<ManaCostSymbol> = costs
+        .iter()
+        .filter_map(|cost| match cost {
+            Cost::Mana(symbol) => Some(*symbol),
+            Cost::Selftap => None,
+        })
+        .collect();
+    symbols.sort();
+    symbols
+        .into_iter()
+        .filter(|symbol| {
+            let spendable = symbol.spendable_colors();
+            match available.iter().position(|color| spendable.contains(color)) {
+                Some(i) => {
+                    available.remove(i);
+                    false
+                }
+                None => true,
+            }
+        })
+        .collect()
+}
+fn mana_pool(game: &GameState, me: PlayerId) -> Vec<Color> {
+    match game.players.get(&me) {
+        Some(player) => player
+            .mana_pool
+            .iter()
+            .filter_map(|mana| game.game.mana.get(*mana))
+            .map(|mana| mana.color)
+            .collect(),
+        None => Vec::new(),
+    }
+}
+fn mana_sources(game: &GameState, ask: &AskSelectN<Action>) -> Vec<(usize, Vec<Color>)> {
+    ask.ents
+        .iter()
+        .enumerate()
+        .filter_map(|(i, action)| match action {
+            Action::ActivateAbility { source, index } => {
+                let colors = mana_ability_colors(game.cards.get(source)?, *index)?;
+                Some((i, colors))
+            }
+            _ => None,
+        })
+        .collect()
+}
+//The spells the player could pay for with its pool and untapped mana sources
+pub(crate) fn castable(game: &GameState, me: PlayerId, ask: &AskSelectN<Action>) -> Vec<usize> {
+    let mut available = mana_pool(game, me);
+    for (_, colors) in mana_sources(game, ask) {
+        available.extend(colors);
+    }
+    ask.ents
+        .iter()
+        .enumerate()
+        .filter_map(|(i, action)| match action {
+            Action::Cast(option)
+                if option.possible_to_take && unpaid(&option.costs, &available).is_empty() =>
+            {
+                Some(i)
+            }
+            _ => None,
+        })
+        .collect()
+}
+//Casts the spell once the pool pays for it, until then taps a mana source towards it
+pub(crate) fn cast(
+    game: &GameState,
+    me: PlayerId,
+    ask: &AskSelectN<Action>,
+    spell: usize,
+) -> HashSetObj<usize> {
+    let mut choice = HashSetObj::new();
+    let Some(Action::Cast(option)) = ask.ents.get(spell) else {
+        return choice;
+    };
+    let missing = unpaid(&option.costs, &mana_pool(game, me));
+    if missing.is_empty() {
+        choice.insert(spell);
+        return choice;
+    }
+    //Tap whatever pays for a colored symbol first, then anything for the generic ones
+    let sources = mana_sources(game, ask);
+    let colored = sources.iter().find(|(_, colors)| {
+        missing.iter().any(|symbol| {
+            *symbol != ManaCostSymbol::Generic
+                && colors
+                    .iter()
+                    .any(|color| symbol.spendable_colors().contains(color))
+        })
+    });
+    if let Some((source, _)) = colored.or(sources.first()) {
+        choice.insert(*source);
+    }
+    choice
+}
+pub(crate) fn choose_action(
+    game: &GameState,
+    me: PlayerId,
+    ask: &AskSelectN<Action>,
+) -> HashSetObj<usize> {
+    let mut choice = HashSetObj::new();
+    let main_phase = matches!(game.game.phase, Some(Phase::FirstMain | Phase::SecondMain));
+    if game.game.active_player != me || !game.game.stack.is_empty() || !main_phase {
+        return choice;
+    }
+    if let Some(land) = ask
+        .ents
+        .iter()
+        .position(|action| matches!(action, Action::PlayLand(_)))
+    {
+        choice.insert(land);
+        return choice;
+    }
+    //Ties go to the spell offered first
+    let spell = castable(game, me, ask).into_iter().min_by_key(|&i| {
+        let value = match &ask.ents[i] {
+            Action::Cast(option) => mana_value(&option.costs),
+            _ => 0,
+        };
+        (std::cmp::Reverse(value), i)
+    });
+    match spell {
+        Some(spell) => cast(game, me, ask, spell),
+        None => choice,
+    }
+}
+
+//Whether an effect is meant for the opponent's things rather than the caster's
+fn harmful(effect: &ClauseEffect) -> bool {
+    match effect {
+        ClauseEffect::Destroy | ClauseEffect::Exile | ClauseEffect::Tap => true,
+        ClauseEffect::SetTargetController(clause) => harmful(&clause.effect),
+        ClauseEffect::Compound(clauses) => clauses.iter().any(|clause| harmful(&clause.effect)),
+        ClauseEffect::MultClause(effect, _) => harmful(effect),
+        ClauseEffect::UntilEndTurn(effect) => match effect {
+            ContEffect::ModifyPT(pt) => pt.power + pt.toughness < 0,
+            ContEffect::CantAttackOrBlock | ContEffect::CantActivateNonManaAbil => true,
+            ContEffect::HasAbility(_) | ContEffect::AddSubtype(_) => false,
+        },
+        ClauseEffect::AddMana(_)
+        | ClauseEffect::GainLife(_)
+        | ClauseEffect::DrawCard
+        | ClauseEffect::CreateToken(_)
+        | ClauseEffect::PutCounter(..) => false,
+    }
+}
+fn targets(clause: &Clause) -> bool {
+    matches!(
+        clause.affected,
+        Affected::Target(_) | Affected::UpToXTarget(..)
+    )
+}
+//Targets are chosen for whatever was put on the stack last
+pub(crate) fn choose_targets(
+    game: &GameState,
+    me: PlayerId,
+    ask: &AskSelectN<TargetId>,
+) -> HashSetObj<usize> {
+    let targeting = game
+        .game
+        .stack
+        .last()
+        .and_then(|id| game.cards.get(id))
+        .and_then(|card| card.effect.iter().find(|clause| targets(clause)));
+    let against_opponents = targeting.map_or(true, |clause| harmful(&clause.effect));
+    let score = |target: &TargetId| -> i64 {
+        let (controller, value) = match target {
+            TargetId::Player(player) => (*player, 1),
+            TargetId::Card(card) => match game.cards.get(card) {
+                Some(card) => (card.get_controller(), card_value(card) + 1),
+                None => return 0,
+            },
+        };
+        if (controller == me) == against_opponents {
+            -value
+        } else {
+            value
+        }
+    };
+    let mut ranked: Vec<(usize, i64)> = ask
+        .ents
+        .iter()
+        .enumerate()
+        .map(|(i, target)| (i, score(target)))
+        .collect();
+    ranked.sort_by_key(|(i, score)| (std::cmp::Reverse(*score), *i));
+    let wanted = ranked.iter().filter(|(_, score)| *score > 0).count();
+    let count = wanted.clamp(ask.min.max(0) as usize, ask.max.max(0) as usize);
+    ranked.into_iter().take(count).map(|(i, _)| i).collect()
+}
+
+//Attacks with every creature that none of the opponents' untapped creatures
+//could block, kill and survive
+pub(crate) fn choose_attacks(
+    game: &GameState,
+    me: PlayerId,
+    ask: &AskPair<TargetId>,
+) -> HashMap<CardId, HashSetObj<TargetId>> {
+    let blockers = possible_blockers(game, me);
+    let mut attackers: Vec<&CardId> = ask.pairs.keys().collect();
+    attackers.sort();
+    let mut attacks = HashMap::new();
+    for attacker in attackers {
+        let Some(pt) = game.cards.get(attacker).and_then(power_toughness) else {
+            continue;
+        };
+        let safe = blockers
+            .iter()
+            .all(|blocker| blocker.power < pt.toughness || blocker.toughness <= pt.power);
+        if pt.power <= 0 || !safe {
+            continue;
+        }
+        //Go after whoever is closest to losing
+        let target = ask.pairs[attacker]
+            .items
+            .iter()
+            .min_by_key(|target| match target {
+                TargetId::Player(player) => {
+                    (game.players.get(player).map_or(0, |pl| pl.life), *player)
+                }
+                TargetId::Card(_) => (i64::MAX, me),
+            });
+        if let Some(target) = target {
+            attacks.insert(*attacker, [*target].into_iter().collect());
+        }
+    }
+    attacks
+}
+//Blocks when the blocker survives or trades for something worth more, then
+//throws chump blockers in front of the biggest attackers if the attack is lethal
+pub(crate) fn choose_blocks(
+    game: &GameState,
+    me: PlayerId,
+    ask: &AskPair<CardId>,
+) -> HashMap<CardId, HashSetObj<CardId>> {
+    let stats = |id: &CardId| game.cards.get(id).and_then(power_toughness);
+    let value = |id: &CardId| game.cards.get(id).map_or(0, card_value);
+    let mut blockers: Vec<&CardId> = ask.pairs.keys().collect();
+    blockers.sort_by_key(|blocker| (value(blocker), **blocker));
+    let mut attackers: Vec<CardId> = ask
+        .pairs
+        .values()
+        .flat_map(|item| item.items.iter().copied())
+        .collect::<HashSet<CardId>>()
+        .into_iter()
+        .collect();
+    attackers.sort_by_key(|attacker| {
+        let power = stats(attacker).map_or(0, |pt| pt.power);
+        (std::cmp::Reverse(power), *attacker)
+    });
+    let mut blocks: HashMap<CardId, HashSetObj<CardId>> = HashMap::new();
+    let mut blocked: HashSet<CardId> = HashSet::new();
+    for &blocker in &blockers {
+        let Some(blocker_pt) = stats(blocker) else {
+            continue;
+        };
+        let good = attackers.iter().find(|attacker| {
+            let Some(attacker_pt) = stats(attacker) else {
+                return false;
+            };
+            let survives = attacker_pt.power < blocker_pt.toughness;
+            let kills = blocker_pt.power >= attacker_pt.toughness;
+            !blocked.contains(attacker)
+                && ask.pairs[blocker].items.contains(attacker)
+                && (survives || (kills && value(attacker) >= value(blocker)))
+        });
+        if let Some(&attacker) = good {
+            blocked.insert(attacker);
+            blocks.insert(*blocker, [attacker].into_iter().collect());
+        }
+    }
+    let life = game.players.get(&me).map_or(0, |player| player.life);
+    let mut incoming: i64 = attackers
+        .iter()
+        .filter(|attacker| !blocked.contains(attacker))
+        .filter_map(|attacker| stats(attacker))
+        .map(|pt| pt.power)
+        .sum();
+    for attacker in &attackers {
+        if incoming < life {
+            break;
+        }
+        if blocked.contains(attacker) {
+            continue;
+        }
+        let chump = blockers.iter().find(|blocker| {
+            !blocks.contains_key(**blocker) && ask.pairs[**blocker].items.contains(attacker)
+        });
+        if let Some(&&chump) = chump {
+            blocked.insert(*attacker);
+            blocks.insert(chump, [*attacker].into_iter().collect());
+            incoming -= stats(attacker).map_or(0, |pt| pt.power);
+        }
+    }
+    blocks
+}
+//Discards spells it can't cast soon, or spare lands once it has plenty out
+pub(crate) fn choose_discards(
+    game: &GameState,
+    me: PlayerId,
+    ask: &AskSelectN<CardId>,
+) -> HashSetObj<usize> {
+    let lands_out = sorted_cards(game)
+        .iter()
+        .filter(|(id, card)| {
+            game.game.battlefield.contains(id)
+                && card.types.is_land()
+                && card.get_controller() == me
+        })
+        .count();
+    let plenty_of_lands = lands_out >= 6;
+    let mut ranked: Vec<(usize, (bool, usize))> = ask
+        .ents
+        .iter()
+        .enumerate()
+        .map(|(i, card)| {
+            let rank = match game.cards.get(card) {
+                Some(card) => (
+                    card.types.is_land() == plenty_of_lands,
+                    mana_value(&card.costs),
+                ),
+                None => (true, usize::MAX),
+            };
+            (i, rank)
+        })
+        .collect();
+    ranked.sort_by_key(|(i, rank)| (std::cmp::Reverse(*rank), *i));
+    ranked
+        .into_iter()
+        .take(ask.min.max(0) as usize)
+        .map(|(i, _)| i)
+        .collect()
+}
@@ -1,4 +1,5 @@
-use crate::{game::Game, player::PlayerView};
+use crate::{game::Game, log::LogMessage, player::PlayerView, state_diff::StateDiff};
+use carddb::decklist::DeckError;
 use common::{
     actions::Action,
     card_entities::CardEnt,
@@ -8,9 +9,11 @@ use common::{
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, hash::Hash};
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Serialize, Deserialize, JsonSchema)]
 pub struct GameState {
-    pub player: PlayerId,
+    pub player: Option<PlayerId>, //None when sent to a spectator
+    #[serde(default)]
+    pub version: u64, //Set per connection when the state is sent
     pub cards: HashMap<CardId, CardEnt>,
     pub players: HashMap<PlayerId, PlayerView>,
     #[serde(flatten)]
@@ -20,7 +23,33 @@ pub struct GameState {
 #[derive(Serialize, Deserialize, JsonSchema)]
 pub enum ClientMessage {
     GameState(GameState),
-    AskUser(Ask),
+    StateDiff(StateDiff),
+    Log(Vec<LogMessage>), //New log entries, only the ones the client is allowed to see
+    AskUser {
+        id: RequestId,
+        ask: Ask,
+    },
+    //The answer to the ask with this id was rejected, the ask is still open
+    InvalidResponse {
+        id: RequestId,
+        reason: String,
+    },
+    DeckRejected(DeckError),
+    //Sent when the game starts, connecting again with the token takes over the seat
+    Session { token: String },
+}
+pub type RequestId = u64;
+//Every answer to an Ask echoes the id the Ask was sent with
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct AskResponse<T> {
+    pub id: RequestId,
+    pub response: T,
+}
+//Sent by clients outside of answering an Ask
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub enum ClientRequest {
+    //Asks for the whole state again after noticing a skipped version
+    Resync,
 }
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct AskSelectN<T> {
@@ -46,4 +75,5 @@ pub enum Ask {
     DiscardToHandSize(AskSelectN<CardId>),
     Action(AskSelectN<Action>),
     Target(AskSelectN<TargetId>),
+    CommandZone(AskSelectN<CardId>), //Which commanders go to the command zone instead
 }
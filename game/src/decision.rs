@@ -0,0 +1,137 @@
+use crate::client_message::{AskPair, AskSelectN, GameState, RequestId};
+use crate::game::serialize_game::Viewer;
+use crate::game::Game;
+use crate::log::LogMessage;
+use async_trait::async_trait;
+use common::actions::Action;
+use common::entities::{CardId, PlayerId, TargetId};
+use common::hashset_obj::HashSetObj;
+use serde::Serialize;
+use std::collections::HashMap;
+
+//Whoever makes a player's choices, be it a client over a websocket, a test,
+//an AI or a replay. The game asks through the method for each kind of ask
+//and checks the answers it gets, a rejected answer is asked for again
+#[async_trait]
+pub trait DecisionProvider: Send {
+    //Providers that look at the game when they're asked aren't sent states,
+    //which saves building one for every player each time something happens
+    fn wants_states(&self) -> bool {
+        true
+    }
+    async fn state(&mut self, _state: &GameState) {}
+    async fn log(&mut self, _messages: &[LogMessage]) {}
+
+    async fn select_action(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>>;
+    async fn select_targets(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>>;
+    async fn select_attackers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>>;
+    async fn select_blockers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>>;
+    async fn discard_to_hand_size(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>>;
+    async fn command_zone(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>>;
+
+    //The answer to id broke the rules, the same ask comes again with the same id
+    async fn rejected(&mut self, _id: RequestId, _reason: &str) {}
+    async fn accepted(&mut self, _id: RequestId) {}
+}
+
+//What every ask comes with. The game is the real one, so anything
+//that looks at it has to keep to what the player is allowed to see
+#[derive(Clone, Copy)]
+pub struct AskContext<'a> {
+    pub id: RequestId,
+    pub game: &'a Game,
+    pub player: PlayerId,
+}
+impl<'a> AskContext<'a> {
+    pub fn state(&self) -> GameState {
+        self.game.state_for(Viewer::Player(self.player))
+    }
+}
+//The player is gone and won't be answering anything else
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gone;
+pub type Answer<T> = Result<T, Gone>;
+
+//Any answer, as it's recorded for replays
+#[derive(Clone, Debug, Serialize)]
+#[serde(untagged)]
+pub enum Response {
+    Selection(HashSetObj<usize>),
+    Attacks(HashMap<CardId, HashSetObj<TargetId>>),
+    Blocks(HashMap<CardId, HashSetObj<CardId>>),
+}
+
+//Nobody is there, every ask is forfeited
+pub struct Absent;
+#[async_trait]
+impl DecisionProvider for Absent {
+    fn wants_states(&self) -> bool {
+        false
+    }
+    async fn select_action(
+        &mut self,
+        _ctx: AskContext<'_>,
+        _ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        Err(Gone)
+    }
+    async fn select_targets(
+        &mut self,
+        _ctx: AskContext<'_>,
+        _ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        Err(Gone)
+    }
+    async fn select_attackers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        _ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        Err(Gone)
+    }
+    async fn select_blockers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        _ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        Err(Gone)
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        _ctx: AskContext<'_>,
+        _ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        Err(Gone)
+    }
+    async fn command_zone(
+        &mut self,
+        _ctx: AskContext<'_>,
+        _ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        Err(Gone)
+    }
+}
@@ -4,18 +4,20 @@ use crate::game::{Phase, Subphase};
 use common::{
     counters::Counter,
     entities::{CardId, PlayerId, TargetId},
+    log::{GameContext, LogCards, MTGLog},
     spellabil::Clause,
     zones::Zone,
 };
+use mtg_log_macro::MTGLoggable;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, MTGLoggable)]
 pub enum DamageReason {
     Combat,
     SpellAbility(CardId),
 }
 //This will be wrapped when resolving to prevent
 //replacement effects from triggering twice
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, MTGLoggable)]
 pub enum Event {
     Draw {
         player: PlayerId,
@@ -94,7 +96,8 @@ pub enum Event {
         quantity: i64,
     },
 }
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, MTGLoggable)]
+#[log_group]
 pub struct MoveZonesResult {
     pub oldent: CardId,
     pub newent: Option<CardId>,
@@ -102,7 +105,7 @@ pub struct MoveZonesResult {
     pub dest: Zone,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, MTGLoggable)]
 pub enum EventResult {
     Draw(CardId),
     MoveZones(Vec<MoveZonesResult>),
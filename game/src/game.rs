@@ -1,9 +1,11 @@
 use crate::client_message::{Ask, AskSelectN};
 use crate::errors::MTGError;
 use crate::event::{Event, EventResult};
-use crate::log::{LogEntry, Entry};
+use crate::log::{Entry, GameLog, LogEntry, LogMessage, LogStamp, LoggedEntry};
 use crate::player::{Player, PlayerCon};
-use crate::CARDDB;
+use crate::record::{GameRecord, RecordedPlayer};
+use crate::replay::{Replay, ReplayPlayer};
+use crate::spectator::Spectators;
 use anyhow::{bail, Result};
 use async_recursion::async_recursion;
 use carddb::carddb::CardDB;
@@ -14,8 +16,9 @@ use common::card_entities::{CardEnt, EntType};
 use common::cardtypes::Subtype;
 use common::cost::{Cost, PaidCost};
 use common::entities::{CardId, ManaId, PlayerId, TargetId, MIN_CARDID};
+use common::format::Format;
 use common::hashset_obj::HashSetObj;
-use common::log::{MTGLog, GameContext};
+use common::log::{MTGLog, GameContext, LogCards};
 use common::mana::{Color, Mana, ManaCostSymbol};
 use common::spellabil::{
     Affected, Clause, ClauseEffect, Constraint, ContEffect, Continuous, KeywordAbility,
@@ -23,13 +26,18 @@ use common::spellabil::{
 use common::zones::Zone;
 use enum_map::EnumMap;
 use futures::future;
+use mtg_log_macro::MTGLoggable;
+use once_cell::sync::OnceCell;
 use rand::seq::SliceRandom;
 use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::cmp::max;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 pub mod build_game;
 mod compute_actions;
@@ -37,7 +45,9 @@ mod event_generators;
 mod handle_event;
 mod layers_state_actions;
 mod resolve;
-mod serialize_game;
+pub mod serialize_game;
+pub mod snapshot;
+use serialize_game::{HiddenIds, Viewer};
 
 pub type Players = EntMap<PlayerId, Player>;
 pub type Cards = EntMap<CardId, CardEnt>;
@@ -64,6 +74,8 @@ pub struct Game {
     pub land_play_limit: u32,
     pub priority: PlayerId,
     pub active_player: PlayerId,
+    pub turn: u32, //Counts every turn taken, extra turns included
+    pub format: Format,
     pub cont_effects: Vec<Continuous>, //Holds continuous effects
     //that are perpetual or time-driven
     #[serde(skip, default = "get_carddb")]
@@ -72,19 +84,34 @@ pub struct Game {
     #[serde(skip)]
     backup: Option<Box<Game>>,
     #[serde(skip, default = "rng_from_entropy")]
-    rng: rand::rngs::StdRng, //Store the RNG to allow for deterministic replay
-    //if I choose to implement it
+    rng: ChaCha12Rng, //Seeded from seed, so a replay shuffles the same way
     #[serde(skip)]
-    game_log: Arc<Mutex<Vec<LogEntry>>>,
+    seed: u64,
+    #[serde(skip)]
+    seats: Vec<(PlayerId, ReplayPlayer)>, //Each player's deck, for replays
+    #[serde(skip)]
+    game_log: Arc<Mutex<GameLog>>,
+    #[serde(skip)]
+    pub spectators: Spectators,
+    #[serde(skip)]
+    hidden_ids: Arc<Mutex<HashMap<Viewer, HiddenIds>>>, //Kept per viewer between states
+    #[serde(skip)]
+    pub autosave: Option<PathBuf>, //Where to keep a snapshot of the game as it runs
     pub panic_on_restore: bool,
 }
 
+//Deserialized games use the database games were built with,
+//so loading a snapshot doesn't read the card database again
+static BUILT_WITH: OnceCell<&'static CardDB> = OnceCell::new();
 fn get_carddb() -> &'static CardDB {
-    CARDDB.get_or_init(|| CardDB::new())
+    match BUILT_WITH.get() {
+        Some(db) => db,
+        None => crate::carddb(),
+    }
 }
 
-fn rng_from_entropy() -> rand::rngs::StdRng {
-    rand::rngs::StdRng::from_entropy()
+fn rng_from_entropy() -> ChaCha12Rng {
+    ChaCha12Rng::from_entropy()
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
@@ -100,33 +127,96 @@ pub enum GameOutcome {
 
 impl Game {
     pub async fn run(&mut self) -> GameOutcome {
+        self.deal().await;
+        self.resume().await
+    }
+    //Like run, but stops once last_turn starts if nobody has won by then
+    pub async fn run_until(&mut self, last_turn: u32) -> GameOutcome {
+        self.deal().await;
+        self.send_state().await;
+        while self.outcome == GameOutcome::Ongoing && self.turn < last_turn {
+            self.step().await;
+        }
+        self.outcome
+    }
+    async fn deal(&mut self) {
         for player in self.turn_order.clone() {
             self.shuffle(player);
             for _i in 0..7 {
                 self.draw(player).await;
             }
         }
+    }
+    //Plays the game on from wherever it is, which is the start
+    //of a step for games loaded from a snapshot
+    pub async fn resume(&mut self) -> GameOutcome {
         self.send_state().await;
         while self.outcome == GameOutcome::Ongoing {
-            if let Some(subphase) = self.subphases.pop_front() {
-                self.handle_event(Event::Subphase { subphase }).await;
-            } else if let Some(phase) = self.phases.pop_front() {
-                self.handle_event(Event::Phase { phase }).await;
-            } else if let Some(player) = self.extra_turns.pop_front() {
-                self.handle_event(Event::Turn {
-                    player,
-                    extra: true,
-                })
-                .await;
-            } else {
-                self.turn_order.rotate_left(1);
-                self.handle_event(Event::Turn {
-                    player: self.turn_order[0],
-                    extra: false,
-                })
-                .await;
+            self.step().await;
+        }
+        self.outcome
+    }
+    //Runs whatever step, phase or turn is up next
+    async fn step(&mut self) {
+        if let Some(path) = &self.autosave {
+            if let Err(err) = self.autosave_snapshot(path).await {
+                log::warn!("Failed to save a snapshot to {}: {}", path.display(), err);
+            }
+        }
+        if let Some(subphase) = self.subphases.pop_front() {
+            self.handle_event(Event::Subphase { subphase }).await;
+        } else if let Some(phase) = self.phases.pop_front() {
+            self.handle_event(Event::Phase { phase }).await;
+        } else if let Some(player) = self.extra_turns.pop_front() {
+            self.handle_event(Event::Turn {
+                player,
+                extra: true,
+            })
+            .await;
+        } else {
+            self.turn_order.rotate_left(1);
+            self.handle_event(Event::Turn {
+                player: self.turn_order[0],
+                extra: false,
+            })
+            .await;
+        }
+    }
+    //A copy of the game to try things out on. It's cut off from the players,
+    //spectators, log and snapshots of the original and shuffles with its own seed
+    pub fn simulation(&self, seed: u64, connect: impl Fn(PlayerId) -> PlayerCon) -> Game {
+        let mut game = self.clone();
+        for player in game.turn_order.clone() {
+            if let Some(pl) = game.players.get_mut(player) {
+                pl.player_con = connect(player);
             }
         }
+        game.backup = None;
+        game.rng = ChaCha12Rng::seed_from_u64(seed);
+        game.seed = seed;
+        game.game_log = Default::default();
+        game.spectators = Default::default();
+        game.hidden_ids = Default::default();
+        game.autosave = None;
+        game.panic_on_restore = false;
+        game
+    }
+    //Plays a copy made while someone was asked for priority on from there,
+    //until the game ends or the given number of turns have started
+    pub async fn play_out(&mut self, turns: u32) -> GameOutcome {
+        let last_turn = self.turn + turns;
+        let mut players = self.turn_order_from_player(self.priority);
+        loop {
+            self.player_cycle_priority(players).await;
+            if self.outcome != GameOutcome::Ongoing || self.stack.is_empty() {
+                break;
+            }
+            self.resolve(self.stack[self.stack.len() - 1]).await;
+            players = self.turn_order_from_player(self.active_player);
+        }
+        while self.outcome == GameOutcome::Ongoing && self.turn < last_turn {
+            self.step().await;
+        }
         self.outcome
     }
     pub fn get_active_player<'a>(&'a self) -> &'a Player {
@@ -155,9 +245,58 @@ impl Game {
         self.backup()
     }
 
+    //Everything needed to play this game again, responses are
+    //whatever the players have answered so far
+    pub fn replay(&self) -> Result<Replay> {
+        let players = self
+            .seats
+            .iter()
+            .map(|(player, seat)| ReplayPlayer {
+                responses: self
+                    .players
+                    .get(*player)
+                    .map(|player| player.player_con.responses())
+                    .unwrap_or_default(),
+                ..seat.clone()
+            })
+            .collect();
+        Ok(Replay {
+            seed: self.seed,
+            format: self.format,
+            players,
+            final_state: self.final_state()?,
+        })
+    }
+    //The game as kept for analysis, the server measures how long it took
+    pub fn record(&self, duration: Duration) -> GameRecord {
+        let players = self
+            .seats
+            .iter()
+            .map(|(player, seat)| RecordedPlayer {
+                player: *player,
+                name: seat.name.clone(),
+                deck: seat.deck.clone(),
+                commander: seat.commander.clone(),
+            })
+            .collect();
+        GameRecord {
+            seed: self.seed,
+            format: self.format,
+            players,
+            outcome: self.outcome,
+            turns: self.turn,
+            duration_secs: duration.as_secs(),
+        }
+    }
     pub fn shuffle(&mut self, player: PlayerId) {
         if let Some(pl) = self.players.get_mut(player) {
             pl.library.shuffle(&mut self.rng);
+            //Hidden ids outlast a reorder, so they're dealt again
+            //or a viewer could follow a card through the shuffle
+            let mut hidden_ids = self.hidden_ids.lock().unwrap();
+            for ids in hidden_ids.values_mut() {
+                ids.forget(&pl.library);
+            }
         }
     }
     pub async fn add_mana(&mut self, player: PlayerId, mana: ManaCostSymbol) -> Vec<ManaId> {
@@ -270,6 +409,9 @@ impl Game {
     }
     pub async fn cycle_priority(&mut self) {
         loop {
+            if self.outcome != GameOutcome::Ongoing {
+                return;
+            }
             self.player_cycle_priority(self.turn_order_from_player(self.active_player))
                 .await;
             if self.stack.len() == 0 {
@@ -285,6 +427,10 @@ impl Game {
         let mut pass_count = 0;
         while pass_count < players.len() {
             self.layers_state_actions().await;
+            players.retain(|player| self.turn_order.contains(player));
+            if self.outcome != GameOutcome::Ongoing || players.is_empty() {
+                return;
+            }
             self.priority = players[0];
             self.send_state().await;
             let act_taken = self.grant_priority(&players).await;
@@ -316,7 +462,7 @@ impl Game {
                     max: 1,
                 };
                 choice = pl
-                    .ask_user_selectn(&Ask::Action(select.clone()), &select)
+                    .ask_user_selectn(self, player, &Ask::Action(select.clone()), &select)
                     .await;
             }
             if choice.len() == 0 {
@@ -355,6 +501,10 @@ impl Game {
                             keyword: None,
                             player,
                         };
+                        if casting_option.zone == Zone::Command
+                        && let Some(pl) = self.players.get_mut(player) {
+                            pl.commander_casts += 1;
+                        }
                         if let Ok(_) = self.handle_cast(stack_opt).await {
                         } else {
                             self.restore();
@@ -573,6 +723,31 @@ impl Game {
         }
         Ok(())
     }
+    //Everything the card's effects and aura target, once they've been chosen
+    fn chosen_targets(&self, card: CardId) -> Vec<TargetId> {
+        fn clause_targets(clause: &Clause, targets: &mut Vec<TargetId>) {
+            match &clause.affected {
+                Affected::Target(Some(target)) => targets.push(*target),
+                Affected::ManuallySet(chosen) | Affected::UpToXTarget(_, chosen) => {
+                    targets.extend(chosen.iter().copied())
+                }
+                _ => {}
+            }
+            if let ClauseEffect::Compound(clauses) = &clause.effect {
+                for clause in clauses {
+                    clause_targets(clause, targets);
+                }
+            }
+        }
+        let mut targets = Vec::new();
+        if let Some(card) = self.cards.get(card) {
+            for clause in &card.effect {
+                clause_targets(clause, &mut targets);
+            }
+            targets.extend(card.enchanting_or_equipping);
+        }
+        targets
+    }
     async fn select_enchant_target(
         &self,
         player: PlayerId,
@@ -591,7 +766,9 @@ impl Game {
                     min: 1,
                     max: 1,
                 };
-                let choice = pl.ask_user_selectn(&Ask::Target(ask.clone()), &ask).await;
+                let choice = pl
+                    .ask_user_selectn(self, player, &Ask::Target(ask.clone()), &ask)
+                    .await;
                 let target = valid[choice.into_iter().next().unwrap()];
                 return Ok(Some(target));
             } else {
@@ -624,7 +801,9 @@ impl Game {
                         min: 1,
                         max: 1,
                     };
-                    let choice = pl.ask_user_selectn(&Ask::Target(ask.clone()), &ask).await;
+                    let choice = pl
+                    .ask_user_selectn(self, player, &Ask::Target(ask.clone()), &ask)
+                    .await;
                     let target = valid[choice.into_iter().next().unwrap()];
                     clause.affected = Affected::Target(Some(target));
                     return Ok(clause);
@@ -641,7 +820,9 @@ impl Game {
                         min: 0,
                         max: *n,
                     };
-                    let choice = pl.ask_user_selectn(&Ask::Target(ask.clone()), &ask).await;
+                    let choice = pl
+                    .ask_user_selectn(self, player, &Ask::Target(ask.clone()), &ask)
+                    .await;
                     let choices = choice.into_iter().map(|i| valid[i]).collect();
                     clause.affected = Affected::UpToXTarget(*n, choices);
                     return Ok(clause);
@@ -686,7 +867,6 @@ impl Game {
     //The spell has already been moved to the stack for this operation
     async fn handle_cast(&mut self, castopt: StackActionOption) -> Result<(), MTGError> {
         println!("Handling cast {:?}", castopt);
-        self.log(Entry::Cast(castopt.clone()));
         if !castopt.filter.check() {
             self.log(Entry::CastFailedFromRestriction(castopt.stack_ent));
             return Err(MTGError::CantCast);
@@ -694,6 +874,8 @@ impl Game {
         self.send_state().await;
         self.select_targets(castopt.player, castopt.stack_ent)
             .await?;
+        let targets = self.chosen_targets(castopt.stack_ent);
+        self.log(Entry::Cast(castopt.clone(), targets));
         self.send_state().await;
         let cost_paid = self.request_cost_payment(&castopt).await?;
         println!("cost paid {:?}", cost_paid);
@@ -812,11 +994,14 @@ impl Game {
         }
         false
     }
+    fn is_ability(&self, id: CardId) -> bool {
+        self.cards.is(id, |card| {
+            card.ent_type == EntType::ActivatedAbility || card.ent_type == EntType::TriggeredAbility
+        })
+    }
     fn is_mana_ability(&self, id: CardId) -> bool {
         if let Some(card) = self.cards.get(id) {
-            if !(card.ent_type == EntType::ActivatedAbility
-                || card.ent_type == EntType::TriggeredAbility)
-            {
+            if !self.is_ability(id) {
                 return false;
             }
             self.effect_is_mana_abil(&card.costs, &card.effect)
@@ -885,6 +1070,63 @@ impl Game {
             .collect()
     }
 
+    //Removes a player who lost from the game along with everything they own,
+    //and ends the game once at most one player is left. Returns the events
+    //exiling what the player still controls that someone else owns
+    pub fn lose(&mut self, player: PlayerId) -> Vec<Event> {
+        let Some(position) = self.turn_order.iter().position(|&x| x == player) else {
+            return Vec::new();
+        };
+        self.log(Entry::Lose(player));
+        self.turn_order.remove(position);
+        if position == 0 {
+            //It was their turn, so end it and make sure the
+            //next turn goes to the player after them
+            self.phases.clear();
+            self.subphases.clear();
+            self.turn_order.rotate_right(1);
+        }
+        let owned: Vec<CardId> = self
+            .all_cards()
+            .into_iter()
+            .filter(|&card| self.cards.is(card, |card| card.owner == player))
+            .collect();
+        for card in &owned {
+            self.battlefield.remove(card);
+            self.exile.remove(card);
+            self.command.remove(card);
+        }
+        self.stack.retain(|card| !owned.contains(card));
+        self.outcome = match self.turn_order.len() {
+            0 => GameOutcome::Tie,
+            1 => GameOutcome::Winner(self.turn_order[0]),
+            _ => GameOutcome::Ongoing,
+        };
+        //Gaining control sets the controller outright, so there's no effect to end.
+        //Abilities they control cease to exist and everything else they control is exiled
+        let controlled =
+            |card: &CardId| self.cards.is(*card, |card| card.get_controller() == player);
+        let abilities: Vec<CardId> = (self.stack.iter())
+            .filter(|&card| controlled(card) && self.is_ability(*card))
+            .copied()
+            .collect();
+        self.stack.retain(|card| !abilities.contains(card));
+        let mut exiled = Vec::new();
+        for (cards, origin) in [
+            (self.battlefield.iter().copied().collect::<Vec<_>>(), Zone::Battlefield),
+            (self.stack.clone(), Zone::Stack),
+        ] {
+            let ents: Vec<CardId> = cards.into_iter().filter(controlled).collect();
+            if !ents.is_empty() {
+                exiled.push(Event::MoveZones {
+                    ents,
+                    origin: Some(origin),
+                    dest: Zone::Exile,
+                });
+            }
+        }
+        exiled
+    }
     pub fn remaining_lethal(&self, ent: CardId) -> Option<i64> {
         self.cards.get(ent).and_then(|card| {
             card.pt
@@ -954,9 +1196,44 @@ impl Game {
         let context=GameContext{
             cards: &self.cards,
         };
-        self.get_log().push(entry.mtg_log(&context));
+        let entry = entry.mtg_log(&context);
+        let text = entry.render(|player| match self.players.get(player) {
+            Some(player) => player.name.clone(),
+            None => "A player".to_owned(),
+        });
+        let known_to = self.log_known_to(&entry);
+        let stamp = LogStamp {
+            turn: self.turn,
+            active_player: self.active_player,
+            phase: self.phase,
+            subphase: self.subphase,
+        };
+        let mut log = self.get_log();
+        let index = log.entries.len();
+        log.entries.push(LoggedEntry {
+            message: LogMessage { index, stamp, text, entry },
+            known_to,
+        });
+    }
+    //Who knows every card an entry names.
+    //A card that changed zones is known to anyone who knows it on either side
+    fn log_known_to(&self, entry: &LogEntry) -> Option<HashSet<PlayerId>> {
+        let cards = entry.cards();
+        if cards.is_empty() {
+            return None;
+        }
+        let mut known_to: HashSet<PlayerId> = self.turn_order.iter().copied().collect();
+        for sides in cards {
+            known_to.retain(|player| {
+                sides.iter().any(|&card| match self.cards.get(card) {
+                    Some(card) => card.known_to.contains(player),
+                    None => false,
+                })
+            });
+        }
+        Some(known_to)
     }
-    pub fn get_log(&self) -> std::sync::MutexGuard<'_, Vec<LogEntry>> {
+    pub fn get_log(&self) -> std::sync::MutexGuard<'_, GameLog> {
         self.game_log.as_ref().lock().unwrap()
     }
 }
@@ -973,7 +1250,7 @@ pub enum ActionPriorityType {
     ManaAbilOrSpecialAction,
     Action,
 }
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, JsonSchema, MTGLoggable)]
 pub enum Phase {
     Begin,
     FirstMain,
@@ -981,7 +1258,7 @@ pub enum Phase {
     SecondMain,
     Ending,
 }
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, JsonSchema)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, JsonSchema, MTGLoggable)]
 pub enum Subphase {
     Untap,
     Upkeep,
@@ -1,8 +1,16 @@
 use crate::game::*;
+use crate::replay::ReplayPlayer;
+use carddb::deck_validation::validate_deck;
+pub const STARTING_LIFE: i64 = 20;
+pub const COMMANDER_STARTING_LIFE: i64 = 40;
 pub struct GameBuilder {
     players: Players,
     cards: Cards,
+    command: HashSetObj<CardId>,
     turn_order: VecDeque<PlayerId>,
+    format: Format,
+    seed: Option<u64>,
+    seats: Vec<(PlayerId, ReplayPlayer)>,
 }
 
 impl GameBuilder {
@@ -12,9 +20,21 @@ impl GameBuilder {
         GameBuilder {
             players: Players::new(),
             cards,
+            command: HashSetObj::new(),
             turn_order: VecDeque::new(),
+            format: Format::default(),
+            seed: None,
+            seats: Vec::new(),
         }
     }
+    pub fn set_format(&mut self, format: Format) {
+        self.format = format;
+    }
+    //Games with the same seed, decks and responses play out the same way.
+    //Left unset a random seed is picked, and recorded for replays
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
     //If this function fails the game is corrupted
     pub fn add_player(
         &mut self,
@@ -27,40 +47,105 @@ impl GameBuilder {
         let player = Player {
             name: name.to_owned(),
             hand: HashSetObj::new(),
-            life: 20,
+            life: STARTING_LIFE,
             mana_pool: HashSetObj::new(),
             graveyard: Vec::new(),
             library: Vec::new(),
             counters: Vec::new(),
             max_handsize: 7,
+            commander_casts: 0,
+            commander_damage: HashMap::new(),
             player_con: player_con,
         };
         let (player_id, player) = self.players.insert(player);
+        let mut failed = Vec::new();
         for cardname in card_names {
-            let card: CardEnt = db.spawn_card(cardname, player_id);
-            let (card_id, _card) = self.cards.insert(card);
-            cards.push(card_id);
+            match db.try_spawn_card(cardname, player_id) {
+                Ok(card) => {
+                    let (card_id, _card) = self.cards.insert(card);
+                    cards.push(card_id);
+                }
+                Err(err) => failed.push(err.to_string()),
+            }
+        }
+        if !failed.is_empty() {
+            bail!("Couldn't spawn cards for {}:\n{}", name, failed.join("\n"))
         }
         //Now that the deck has been constructed, set the players deck
         player.library = cards;
         self.turn_order.push_back(player_id);
+        let seat = ReplayPlayer {
+            name: name.to_owned(),
+            deck: card_names.iter().map(|&name| name.to_owned()).collect(),
+            ..Default::default()
+        };
+        self.seats.push((player_id, seat));
         Ok(player_id)
     }
-    pub fn build(self, db: &'static CardDB) -> Result<Game> {
+    //Puts a player's commander into the command zone
+    pub fn set_commander(
+        &mut self,
+        player: PlayerId,
+        db: &CardDB,
+        card_name: &'static str,
+    ) -> Result<CardId> {
+        if self.players.get(player).is_none() {
+            bail!("Commander set for a player who isn't in the game")
+        }
+        if self.commander(player).is_some() {
+            bail!("Player already has a commander")
+        }
+        let mut card = match db.try_spawn_card(card_name, player) {
+            Ok(card) => card,
+            Err(err) => bail!("Couldn't spawn commander:\n{}", err),
+        };
+        card.commander = true;
+        let (card_id, _card) = self.cards.insert(card);
+        self.command.insert(card_id);
+        if let Some((_, seat)) = self.seats.iter_mut().find(|(id, _)| *id == player) {
+            seat.commander = Some(card_name.to_owned());
+        }
+        Ok(card_id)
+    }
+    fn commander(&self, player: PlayerId) -> Option<&CardEnt> {
+        self.command
+            .iter()
+            .filter_map(|&card| self.cards.get(card))
+            .find(|card| card.owner == player)
+    }
+    pub fn build(mut self, db: &'static CardDB) -> Result<Game> {
         if self.turn_order.len() < 2 {
             bail!("Game needs at least two players in initialization")
         };
+        self.validate_decks(db)?;
+        //The command zone is public
+        for &card in &self.command {
+            if let Some(card) = self.cards.get_mut(card) {
+                card.known_to.extend(self.turn_order.iter());
+            }
+        }
+        if self.format == Format::Commander {
+            for player in self.turn_order.clone() {
+                if let Some(player) = self.players.get_mut(player) {
+                    player.life = COMMANDER_STARTING_LIFE;
+                }
+            }
+        }
+        let _ = BUILT_WITH.set(db);
         let start = self.turn_order[0];
+        let seed = self.seed.unwrap_or_else(rand::random);
         Ok(Game {
             players: self.players,
             cards: self.cards,
             mana: EntMap::new(),
             battlefield: HashSetObj::new(),
             exile: HashSetObj::new(),
-            command: HashSetObj::new(),
+            command: self.command,
+            format: self.format,
             stack: Vec::new(),
             turn_order: self.turn_order,
             active_player: start,
+            turn: 0,
             db,
             land_play_limit: 1,
             lands_played_this_turn: 0,
@@ -72,10 +157,39 @@ impl GameBuilder {
             priority: start,
             outcome: GameOutcome::Ongoing,
             backup: None,
-            rng: rand::rngs::StdRng::from_entropy(),
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            seed,
+            seats: self.seats,
             cont_effects: Vec::new(),
-            game_log: Arc::new(Mutex::new(Vec::new())),
+            game_log: Default::default(),
+            spectators: Spectators::default(),
+            hidden_ids: Default::default(),
+            autosave: None,
             panic_on_restore: false,
         })
     }
+    fn validate_decks(&self, db: &CardDB) -> Result<()> {
+        for &player_id in &self.turn_order {
+            let player = self.players.get(player_id).expect("player was added");
+            let deck: Vec<&str> = player
+                .library
+                .iter()
+                .filter_map(|&card| self.cards.get(card))
+                .map(|card| card.name.as_str())
+                .collect();
+            let commander = self
+                .commander(player_id)
+                .map(|commander| commander.name.as_str());
+            if let Err(violations) = validate_deck(db, self.format, &deck, commander) {
+                let violations: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+                bail!(
+                    "{}'s deck isn't legal in {:?}: {}",
+                    player.name,
+                    self.format,
+                    violations.join("; ")
+                )
+            }
+        }
+        Ok(())
+    }
 }
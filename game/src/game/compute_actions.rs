@@ -2,6 +2,8 @@ use common::zones::Zone;
 
 use crate::game::*;
 
+pub const COMMANDER_TAX: i64 = 2; //Extra generic mana for each previous cast
+
 fn mana_from_clause(clause: &Clause) -> Vec<ManaCostSymbol> {
     if let ClauseEffect::AddMana(mana) = &clause.effect {
         return mana.clone();
@@ -115,6 +117,27 @@ impl Game {
                 }
             }
         }
+        for &card_id in self.command.iter() {
+            if let Some(card) = self.cards.get(card_id)
+            && card.commander
+            && card.owner == player
+            && !card.costs.is_empty()
+            && (card.types.is_instant() || self.sorcery_speed(player)) {
+                let mut costs = card.costs.clone();
+                for _ in 0..COMMANDER_TAX * pl.commander_casts {
+                    costs.push(Cost::Mana(ManaCostSymbol::Generic));
+                }
+                let maybe_pay = self.maybe_can_pay(&costs, player, card_id);
+                actions.push(Action::Cast(CastingOption {
+                    source_card: card_id,
+                    costs,
+                    filter: ActionFilter::None,
+                    zone: Zone::Command,
+                    player,
+                    possible_to_take: maybe_pay,
+                }));
+            }
+        }
         actions
     }
 
@@ -1,7 +1,10 @@
 mod combat;
 mod phase_event;
 
-use crate::{event::MoveZonesResult, game::*};
+use crate::{
+    event::{DamageReason, MoveZonesResult},
+    game::*,
+};
 use async_recursion::async_recursion;
 use common::{
     ability::{AbilityTriggerType, Replacement, TriggeredAbility},
@@ -28,6 +31,9 @@ impl Game {
                     for ent in &self.battlefield {
                         trigger_ents.add(*ent);
                     }
+                    for result in &results {
+                        self.log(Entry::EventResult(result.clone()));
+                    }
                     for result in &results {
                         self.fire_triggers(&trigger_ents, result).await;
                     }
@@ -36,6 +42,7 @@ impl Game {
             };
             //Handle prevention effects
             if !self.allow_event(&event) {
+                self.log(Entry::Prevented(event));
                 continue;
             }
             match self.replacements(&event).await {
@@ -49,6 +56,7 @@ impl Game {
             //By the time the loop reaches here, the game is ready to
             //Execute the event. No more prevention/replacement effects
             //At this point
+            self.log(Entry::Event(event.clone()));
             match event {
                 Event::PutCounter { affected, counter, quantity }=>{
                     match affected{
@@ -88,9 +96,9 @@ impl Game {
                     amount,
                     target,
                     source,
-                    reason: _,
+                    reason,
                 } => {
-                    self.handle_damage(amount, target, source).await;
+                    self.handle_damage(amount, target, source, reason).await;
                 }
                 Event::Block { blocker: _ } => {}
                 Event::BlockedBy { attacker, blocker } => {
@@ -125,6 +133,7 @@ impl Game {
                 }
                 Event::Turn { player, extra: _ } => {
                     self.active_player = player;
+                    self.turn += 1;
                     println!("starting turn");
                     self.phases.extend(
                         [
@@ -202,10 +211,22 @@ impl Game {
                     //Similar to spell casting
                 }
                 Event::Lose { player } => {
-                    //TODO add in the logic to have the game terminate such as setting winners
-                    todo!();
+                    for exile in self.lose(player) {
+                        Game::add_event(&mut events, exile);
+                    }
                 }
                 Event::MoveZones { ents, origin, dest } => {
+                    let (ents, to_command) = self.commander_replacement(ents, dest).await;
+                    if !to_command.is_empty() {
+                        Game::add_event(
+                            &mut events,
+                            Event::MoveZones {
+                                ents: to_command,
+                                origin,
+                                dest: Zone::Command,
+                            },
+                        );
+                    }
                     self.movezones(&mut results, &mut events, ents, origin, dest)
                         .await;
                 },
@@ -222,7 +243,6 @@ impl Game {
                     let (id,card)=self.cards.insert(new_card);
                     self.stack.push(id);
                     let controller=card.get_controller();
-                    self.log(Entry::TriggeredAbil(id));
                     self.send_state().await;
                     let _=self.select_targets(controller, id).await;
                     //TODO check if there is a valid target assignment,
@@ -283,6 +303,7 @@ impl Game {
     }
     async fn replacements(&mut self, event: &Event) -> Option<Vec<Event>> {
         if let Some((events, clauses, cardid)) = self.replacements_h(event).await {
+            self.log(Entry::Replaced(cardid, event.clone(), events.clone()));
             for clause in clauses {
                 self.resolve_clause(clause, cardid).await;
             }
@@ -452,6 +473,7 @@ impl Game {
                     }else{
                         let mut newcard=card.printed.as_ref().expect("set printed card").as_ref().clone();
                         newcard.owner=card.owner;
+                        newcard.commander=card.commander;
                         newcard.printed=Some(Box::new(newcard.clone()));
                         newcard
                     };
@@ -498,8 +520,53 @@ impl Game {
         results.push(EventResult::MoveZones(move_results))
     }
 
+    //Commanders headed to the graveyard or exile can go
+    //to the command zone instead if their owner chooses.
+    //Returns the cards that still move normally and the ones that go to the command zone
+    async fn commander_replacement(
+        &self,
+        ents: Vec<CardId>,
+        dest: Zone,
+    ) -> (Vec<CardId>, Vec<CardId>) {
+        if dest != Zone::Graveyard && dest != Zone::Exile {
+            return (ents, Vec::new());
+        }
+        let mut by_owner: HashMap<PlayerId, Vec<CardId>> = HashMap::new();
+        for &ent in &ents {
+            if let Some(card) = self.cards.get(ent)
+            && card.commander
+            && card.ent_type == EntType::RealCard {
+                by_owner.entry(card.owner).or_default().push(ent);
+            }
+        }
+        let mut to_command = Vec::new();
+        for (owner, commanders) in by_owner {
+            if let Some(pl) = self.players.get(owner) {
+                let ask = AskSelectN {
+                    ents: commanders.clone(),
+                    min: 0,
+                    max: commanders.len() as i64,
+                };
+                let choice = pl
+                    .ask_user_selectn(self, owner, &Ask::CommandZone(ask.clone()), &ask)
+                    .await;
+                to_command.extend(choice.into_iter().map(|i| commanders[i]));
+            }
+        }
+        let ents = ents
+            .into_iter()
+            .filter(|ent| !to_command.contains(ent))
+            .collect();
+        (ents, to_command)
+    }
     //Add deathtouch and combat triggers
-    async fn handle_damage(&mut self, amount: i64, target: TargetId, source: CardId) {
+    async fn handle_damage(
+        &mut self,
+        amount: i64,
+        target: TargetId,
+        source: CardId,
+        reason: DamageReason,
+    ) {
         if amount <= 0 {
             return;
         }
@@ -510,8 +577,16 @@ impl Game {
                 }
             }
             TargetId::Player(playerid) => {
+                let commander = self
+                    .cards
+                    .get(source)
+                    .filter(|card| card.commander && reason == DamageReason::Combat)
+                    .map(|card| card.owner);
                 if let Some(player) = self.players.get_mut(playerid) {
                     player.life -= amount;
+                    if let Some(commander) = commander {
+                        *player.commander_damage.entry(commander).or_insert(0) += amount;
+                    }
                 }
             }
         }
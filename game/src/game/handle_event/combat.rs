@@ -1,7 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
 use crate::{
-    client_message::{Ask, AskPair, AskPairItem},
+    client_message::{AskPair, AskPairItem},
     event::{DamageReason, Event, EventResult},
     game::{Game, Subphase},
 };
@@ -48,7 +48,7 @@ impl Game {
             if let Some(player) = self.players.get(self.active_player) {
                 let pairing = AskPair { pairs };
                 attacks = player
-                    .ask_user_pair(&Ask::Attackers(pairing.clone()), &pairing)
+                    .ask_attackers(self, self.active_player, &pairing)
                     .await;
             } else {
                 return;
@@ -134,9 +134,7 @@ impl Game {
                     .collect();
                 let blocks = if let Some(player) = self.players.get(opponent) {
                     let pairing = AskPair { pairs };
-                    player
-                        .ask_user_pair(&Ask::Blockers(pairing.clone()), &pairing)
-                        .await
+                    player.ask_blockers(self, opponent, &pairing).await
                 } else {
                     return;
                 };
@@ -95,7 +95,7 @@ impl Game {
     }
 
     async fn cleanup_phase(&mut self) {
-        if let Some(player) = self.players.get_mut(self.active_player) {
+        if let Some(player) = self.players.get(self.active_player) {
             if player.hand.len() > player.max_handsize {
                 let diff = player.hand.len().saturating_sub(player.max_handsize);
                 let diff = diff.try_into().expect("handisize fits into i64");
@@ -106,7 +106,12 @@ impl Game {
                     max: diff,
                 };
                 let to_discard = player
-                    .ask_user_selectn(&Ask::DiscardToHandSize(ask.clone()), &ask)
+                    .ask_user_selectn(
+                        self,
+                        self.active_player,
+                        &Ask::DiscardToHandSize(ask.clone()),
+                        &ask,
+                    )
                     .await;
                 let to_discard = to_discard.into_iter().map(|i| hand[i]).collect();
                 self.discard(self.active_player, to_discard).await;
@@ -2,6 +2,8 @@ use common::{counters::Counter, spellabil::ContEffect};
 
 use crate::{game::*, log::Entry};
 
+pub const COMMANDER_DAMAGE_TO_LOSE: i64 = 21;
+
 impl Game {
     //Computes layers, state based actions and places abilities on the stack
     pub async fn layers_state_actions(&mut self) {
@@ -9,6 +11,28 @@ impl Game {
         self.state_based_actions().await;
     }
     async fn state_based_actions(&mut self) {
+        let mut losers = Vec::new();
+        for &player_id in &self.turn_order {
+            if let Some(player) = self.players.get(player_id) {
+                if player.life <= 0 {
+                    losers.push(player_id);
+                    self.log(Entry::LoseFromLife(player_id));
+                } else if player
+                    .commander_damage
+                    .values()
+                    .any(|&damage| damage >= COMMANDER_DAMAGE_TO_LOSE)
+                {
+                    losers.push(player_id);
+                    self.log(Entry::LoseFromCommanderDamage(player_id));
+                } else if player.player_con.forfeited() {
+                    losers.push(player_id);
+                    self.log(Entry::LoseFromDisconnect(player_id));
+                }
+            }
+        }
+        for player in losers {
+            self.handle_event(Event::Lose { player }).await;
+        }
         let mut to_die = Vec::new();
         let mut to_destroy = Vec::new();
         for &cardid in &self.battlefield.clone() {
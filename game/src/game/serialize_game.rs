@@ -1,60 +1,205 @@
 use crate::{
     client_message::{ClientMessage, GameState},
     game::*,
+    log::{LogMessage, LoggedEntry},
+    spectator::SpectatorView,
 };
 use rand::prelude::*;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
 use std::num::NonZeroU64;
+use std::time::Duration;
+//Who a state is being built for, which decides what cards they get to see
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Viewer {
+    Player(PlayerId),
+    Spectator(SpectatorView),
+}
 impl Game {
+    fn knows(&self, viewer: Viewer, card: &CardEnt) -> bool {
+        match viewer {
+            Viewer::Player(player) => card.known_to.contains(&player),
+            //Public information is whatever every player in the game knows
+            Viewer::Spectator(SpectatorView::Public) => self
+                .turn_order
+                .iter()
+                .all(|player| card.known_to.contains(player)),
+            Viewer::Spectator(SpectatorView::Omniscient { .. }) => true,
+        }
+    }
     pub async fn send_state(&mut self) {
         let mut state_futures = Vec::new();
         for player in self.turn_order.clone() {
             state_futures.push(self.send_state_player(player));
         }
         let _results = future::join_all(state_futures).await;
+        self.send_state_spectators();
+        self.send_log().await;
+    }
+    fn can_see(&self, viewer: Viewer, entry: &LoggedEntry) -> bool {
+        let Some(known_to) = &entry.known_to else {
+            return true;
+        };
+        match viewer {
+            Viewer::Player(player) => known_to.contains(&player),
+            Viewer::Spectator(SpectatorView::Public) => self
+                .turn_order
+                .iter()
+                .all(|player| known_to.contains(player)),
+            Viewer::Spectator(SpectatorView::Omniscient { .. }) => true,
+        }
+    }
+    //The whole log, as far as the viewer is allowed to see it
+    pub fn log_for(&self, viewer: Viewer) -> Vec<LogMessage> {
+        let log = self.get_log();
+        log.entries
+            .iter()
+            .filter(|entry| self.can_see(viewer, entry))
+            .map(|entry| entry.message.clone())
+            .collect()
+    }
+    //Sends everyone the entries logged since the last time
+    async fn send_log(&self) {
+        let unsent = self.get_log().unsent();
+        if unsent.is_empty() {
+            return;
+        }
+        let visible = |viewer| -> Vec<LogMessage> {
+            unsent
+                .iter()
+                .filter(|entry| self.can_see(viewer, entry))
+                .map(|entry| entry.message.clone())
+                .collect()
+        };
+        for player in self.turn_order.clone() {
+            let messages = visible(Viewer::Player(player));
+            if let (Some(pl), false) = (self.players.get(player), messages.is_empty()) {
+                pl.player_con.log(&messages).await;
+            }
+        }
+        for view in self.spectators.views() {
+            let messages = visible(Viewer::Spectator(view));
+            if !messages.is_empty() {
+                match serde_json::to_value(ClientMessage::Log(messages)) {
+                    Ok(message) => self.spectators.send_message(view, message),
+                    Err(err) => println!("Failed to serialize log: {}", err),
+                }
+            }
+        }
+    }
+    fn send_state_spectators(&self) {
+        for view in self.spectators.views() {
+            match serde_json::to_value(self.state_for(Viewer::Spectator(view))) {
+                Ok(state) => self.spectators.send(view, &state),
+                Err(err) => println!("Failed to serialize spectator state: {}", err),
+            }
+        }
     }
     async fn send_state_player(&self, player: PlayerId) -> Result<()> {
+        if let Some(pl) = self.players.get(player) {
+            if pl.player_con.wants_states() {
+                pl.player_con.state(&self.state_for(Viewer::Player(player))).await;
+            }
+        }
+        Ok(())
+    }
+    pub fn state_for(&self, viewer: Viewer) -> GameState {
         let mut card_views = HashMap::new();
-        let mut hidden_ids = Vec::new();
-        let mut dummy_cards = Vec::new();
+        let mut hidden = Vec::new();
         for (card_id, card_ref) in self.cards.view() {
-            if card_ref.known_to.contains(&player) {
-                card_views.insert(card_id, card_ref.clone());
+            if self.knows(viewer, card_ref) {
+                let mut card = card_ref.clone();
+                //Only needed to recalculate layers, clients are sent the current card
+                card.printed = None;
+                card_views.insert(card_id, card);
             } else {
-                hidden_ids.push(card_id);
-                let mut dummy_card = CardEnt::default();
-                dummy_card.owner = card_ref.owner;
-                dummy_cards.push(dummy_card);
+                hidden.push(card_id);
             }
         }
-        let mut hidden_map: HashMap<CardId, CardId> = HashMap::new();
-        {
-            let mut rng = thread_rng();
-            hidden_ids.shuffle(&mut rng);
-            let mut next_id = self.cards.peek_count();
-            for i in 0..hidden_ids.len() {
-                next_id = NonZeroU64::new((u64::from(next_id)) + 1).unwrap();
-                let card_id = next_id.into();
-                card_views.insert(card_id, dummy_cards[i].clone());
-                hidden_map.insert(hidden_ids[i], card_id);
+        let mut hidden_ids = self.hidden_ids.lock().unwrap();
+        let hidden_map = hidden_ids
+            .entry(viewer)
+            .or_insert_with(|| HiddenIds::new(self.viewer_seed(viewer)))
+            .update(hidden);
+        for (&card_id, &hidden_id) in hidden_map.iter() {
+            let mut dummy_card = CardEnt::default();
+            if let Some(card) = self.cards.get(card_id) {
+                dummy_card.owner = card.owner;
             }
+            card_views.insert(hidden_id, dummy_card);
         }
         let mut player_views = HashMap::new();
         for (player_id, player_ref) in self.players.view() {
-            let view = player_ref.view(&self.cards, player, &hidden_map);
+            let view = player_ref.view(&self.cards, hidden_map);
             player_views.insert(player_id, view);
         }
-        if let Some(pl) = self.players.get(player) {
-            let message: ClientMessage = ClientMessage::GameState(GameState {
-                player,
-                cards: card_views,
-                players: player_views,
-                game: self.clone(),
-            });
-            pl.send_data(message).await?;
+        GameState {
+            player: match viewer {
+                Viewer::Player(player) => Some(player),
+                Viewer::Spectator(_) => None,
+            },
+            version: 0,
+            cards: card_views,
+            players: player_views,
+            game: self.clone(),
         }
-        Ok(())
+    }
+    //Hidden ids are shuffled with their own rng for each viewer. Drawing them from
+    //the game's rng would let spectators joining change how the game plays out
+    fn viewer_seed(&self, viewer: Viewer) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (self.seed, viewer).hash(&mut hasher);
+        hasher.finish()
+    }
+    //Every card as it stands, what a replay is checked against
+    pub fn final_state(&self) -> Result<Value> {
+        let viewer = Viewer::Spectator(SpectatorView::Omniscient {
+            delay: Duration::ZERO,
+        });
+        Ok(serde_json::to_value(self.state_for(viewer))?)
     }
 }
 //This struct maps the hidden CardId's to the ones exposed to the player.
 //If the player knows the information the public ID is used, otherwise
-//it generates one.
+//it generates one. A generated id is kept for as long as the card stays
+//hidden, so ids don't change between diffs
+pub struct HiddenIds {
+    ids: HashMap<CardId, CardId>,
+    next: u64,
+    rng: StdRng,
+}
+//Far above any real id, but small enough to be exact as a javascript number
+const HIDDEN_ID_BASE: u64 = 1 << 40;
+impl HiddenIds {
+    fn new(seed: u64) -> Self {
+        HiddenIds {
+            ids: HashMap::new(),
+            next: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+    fn update(&mut self, hidden: Vec<CardId>) -> &HashMap<CardId, CardId> {
+        let still_hidden: HashSet<CardId> = hidden.iter().copied().collect();
+        self.ids.retain(|card_id, _| still_hidden.contains(card_id));
+        let mut newly_hidden: Vec<CardId> = hidden
+            .into_iter()
+            .filter(|card_id| !self.ids.contains_key(card_id))
+            .collect();
+        //Shuffled so the new ids don't give away which card is which
+        newly_hidden.shuffle(&mut self.rng);
+        for card_id in newly_hidden {
+            self.next += 1;
+            let hidden_id = NonZeroU64::new(HIDDEN_ID_BASE + self.next).unwrap().into();
+            self.ids.insert(card_id, hidden_id);
+        }
+        &self.ids
+    }
+    //These cards are dealt new ids the next time they're seen
+    pub fn forget(&mut self, cards: &[CardId]) {
+        for card_id in cards {
+            self.ids.remove(card_id);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use crate::game::*;
+use crate::player::ConnectionRecord;
+use crate::replay::ReplayPlayer;
+use std::num::NonZeroU64;
+use std::path::Path;
+
+//Ids handed out next, entity maps don't serialize them
+#[derive(Serialize, Deserialize)]
+struct NextIds {
+    players: NonZeroU64,
+    cards: NonZeroU64,
+    mana: NonZeroU64,
+}
+//Everything about a game except the connections, which are made again when it's
+//loaded. Taken between steps, so nothing is half resolved or waiting on an answer
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    game: Game,
+    players: Players,
+    cards: Cards,
+    next_ids: NextIds,
+    rng: ChaCha12Rng,
+    seed: u64,
+    seats: Vec<(PlayerId, ReplayPlayer)>,
+    connections: HashMap<PlayerId, ConnectionRecord>,
+    game_log: GameLog,
+}
+fn restore_count<K, V>(map: &mut EntMap<K, V>, next: NonZeroU64)
+where
+    K: Copy + std::hash::Hash + Eq + From<NonZeroU64> + JsonSchema,
+    V: JsonSchema,
+{
+    map.skip_count(next.get() - map.peek_count().get());
+}
+//Written beside the old snapshot first, so a crash mid write doesn't lose it
+fn write(snapshot: &Snapshot, path: &Path) -> Result<()> {
+    let partial = path.with_extension("partial");
+    std::fs::write(&partial, serde_json::to_vec(snapshot)?)?;
+    std::fs::rename(partial, path)?;
+    Ok(())
+}
+impl Game {
+    pub fn save_snapshot(&self, path: &Path) -> Result<()> {
+        write(&self.snapshot(), path)
+    }
+    //Like save_snapshot, but serializes and writes off the worker running the game
+    pub async fn autosave_snapshot(&self, path: &Path) -> Result<()> {
+        let snapshot = self.snapshot();
+        let path = path.to_owned();
+        tokio::task::spawn_blocking(move || write(&snapshot, &path)).await?
+    }
+    //The fields the game doesn't serialize are moved out of its copy rather than copied again
+    fn snapshot(&self) -> Snapshot {
+        let mut game = self.clone();
+        game.backup = None;
+        let players = std::mem::take(&mut game.players);
+        let cards = std::mem::take(&mut game.cards);
+        let seats = std::mem::take(&mut game.seats);
+        let connections = self
+            .players
+            .view()
+            .into_iter()
+            .map(|(id, player)| (id, player.player_con.record()))
+            .collect();
+        Snapshot {
+            next_ids: NextIds {
+                players: self.players.peek_count(),
+                cards: self.cards.peek_count(),
+                mana: self.mana.peek_count(),
+            },
+            players,
+            cards,
+            rng: self.rng.clone(),
+            seed: self.seed,
+            seats,
+            connections,
+            game_log: self.get_log().clone(),
+            game,
+        }
+    }
+    //connect gives each player their new connection
+    pub fn load_snapshot(
+        path: &Path,
+        db: &'static CardDB,
+        mut connect: impl FnMut(PlayerId) -> PlayerCon,
+    ) -> Result<Game> {
+        let _ = BUILT_WITH.set(db);
+        let snapshot: Snapshot = serde_json::from_slice(&std::fs::read(path)?)?;
+        let mut game = snapshot.game;
+        game.db = db;
+        game.players = snapshot.players;
+        game.cards = snapshot.cards;
+        restore_count(&mut game.players, snapshot.next_ids.players);
+        restore_count(&mut game.cards, snapshot.next_ids.cards);
+        restore_count(&mut game.mana, snapshot.next_ids.mana);
+        game.rng = snapshot.rng;
+        game.seed = snapshot.seed;
+        game.seats = snapshot.seats;
+        game.game_log = Arc::new(Mutex::new(snapshot.game_log));
+        let mut connections = snapshot.connections;
+        let ids: Vec<PlayerId> = game.players.view().into_iter().map(|(id, _)| id).collect();
+        for id in ids {
+            let con = connect(id);
+            if let Some(record) = connections.remove(&id) {
+                con.restore(record);
+            }
+            if let Some(player) = game.players.get_mut(id) {
+                player.player_con = con;
+            }
+        }
+        Ok(game)
+    }
+}
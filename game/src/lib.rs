@@ -5,26 +5,59 @@
 #![allow(irrefutable_let_patterns)]
 use carddb::carddb::CardDB;
 use once_cell::sync::OnceCell;
+pub mod ai;
 pub mod client_message;
+pub mod decision;
 pub mod errors;
 pub mod event;
 pub mod game;
 pub mod player;
+pub mod protocol;
+pub mod record;
+pub mod replay;
+pub mod scenario;
+pub mod search;
+pub mod simulate;
+pub mod spectator;
+pub mod state_diff;
 pub mod log;
-#[allow(dead_code)] //Used in unit tests
 static CARDDB: OnceCell<CardDB> = OnceCell::new();
+//The database shared by tests and snapshots loaded before any game was built
+fn carddb() -> &'static CardDB {
+    CARDDB.get_or_init(|| {
+        if cfg!(test) {
+            CardDB::for_tests()
+        } else {
+            CardDB::new()
+        }
+    })
+}
 pub fn add(left: usize, right: usize) -> usize {
     left + right
 }
 
 #[cfg(test)]
 mod tests {
+    mod ai_tests;
+    mod ask_validation_tests;
     mod aven_gagglemaster_tests;
     mod baneslayer_angel_tests;
     mod card_tests;
+    mod commander_tests;
     mod common_test;
     mod counter_tests;
+    mod decision_tests;
     mod lethal_damage;
+    mod log_tests;
     mod mock_tests;
+    mod protocol_tests;
+    mod record_tests;
+    mod replay_tests;
+    mod scenario_tests;
+    mod search_tests;
+    mod simulate_tests;
+    mod snapshot_tests;
+    mod spectator_tests;
+    mod state_diff_tests;
     mod swift_response_test;
 }
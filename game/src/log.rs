@@ -1,6 +1,11 @@
-use common::{entities::CardId, actions::StackActionOption, spellabil::Clause};
+use crate::event::{Event, EventResult, LogDamageReason, LogEvent, LogEventResult};
+use crate::game::{Phase, Subphase};
+use common::{entities::{CardId, LogTargetId, PlayerId, TargetId}, actions::StackActionOption, spellabil::Clause};
 use mtg_log_macro::MTGLoggable;
-use common::log::{MTGLog, GameContext};
+use common::log::{MTGLog, GameContext, CardIdContext, LogCards};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 #[derive(Debug, Clone, MTGLoggable)]
 pub enum Entry{
     DiesFromZeroOrLessToughness(CardId),
@@ -9,8 +14,214 @@ pub enum Entry{
     EnchantmentFallsOff(CardId),
     CastFailedFromRestriction(CardId),
     ManaCostNotPaid(CardId),
-    Cast(StackActionOption),
+    Cast(StackActionOption, Vec<TargetId>),
     Resolve(CardId, Vec<Clause>),
     Text(&'static str),
-    TriggeredAbil(CardId)
-}
\ No newline at end of file
+    TriggeredAbil(CardId),
+    Lose(PlayerId),
+    LoseFromLife(PlayerId),
+    LoseFromCommanderDamage(PlayerId),
+    LoseFromDisconnect(PlayerId),
+    Event(Event),
+    EventResult(EventResult),
+    Prevented(Event),
+    //The card whose replacement effect applied, what it replaced and what happened instead
+    Replaced(CardId, Event, Vec<Event>),
+}
+
+fn card_name(card: &CardIdContext) -> String {
+    match &card.name {
+        Some(name) if !name.is_empty() => name.clone(),
+        _ => "a card".to_owned(),
+    }
+}
+fn cards_named(cards: &[CardIdContext]) -> String {
+    let names: Vec<String> = cards.iter().map(card_name).collect();
+    names.join(", ")
+}
+fn target_name(target: &LogTargetId, player_name: &impl Fn(PlayerId) -> String) -> String {
+    match target {
+        LogTargetId::Player(player) => player_name(*player),
+        LogTargetId::Card(card) => card_name(card),
+    }
+}
+impl LogEvent {
+    pub fn render(&self, player_name: &impl Fn(PlayerId) -> String) -> String {
+        match self {
+            LogEvent::Draw { player } => format!("{} draws a card", player_name(*player)),
+            LogEvent::Damage { amount, target, source, reason } => {
+                let kind = match reason {
+                    LogDamageReason::Combat => "combat damage",
+                    LogDamageReason::SpellAbility(_) => "damage",
+                };
+                format!("{} deals {} {} to {}", card_name(source), amount, kind, target_name(target, player_name))
+            }
+            LogEvent::Destroy { perms } => format!("{} destroyed", cards_named(perms)),
+            LogEvent::Discard { player, cards } => {
+                format!("{} discards {}", player_name(*player), cards_named(cards))
+            }
+            LogEvent::Block { blocker } => format!("{} blocks", card_name(blocker)),
+            LogEvent::Blocked { attacker } => format!("{} is blocked", card_name(attacker)),
+            LogEvent::BlockedBy { attacker, blocker } => {
+                format!("{} is blocked by {}", card_name(attacker), card_name(blocker))
+            }
+            LogEvent::Cast { player, spell } => {
+                format!("{} has cast {}", player_name(*player), card_name(spell))
+            }
+            LogEvent::Activate { controller, ability } => {
+                format!("{} activates {}", player_name(*controller), card_name(ability))
+            }
+            LogEvent::MoveZones { ents, origin, dest } => match origin {
+                Some(origin) => format!("{} moves from {:?} to {:?}", cards_named(ents), origin, dest),
+                None => format!("{} moves to {:?}", cards_named(ents), dest),
+            },
+            LogEvent::Lose { player } => format!("{} loses the game", player_name(*player)),
+            LogEvent::Tap { ent } => format!("{} taps", card_name(ent)),
+            LogEvent::Subphase { subphase } => format!("{:?} step", subphase),
+            LogEvent::PlayLand { player, land } => {
+                format!("{} plays {}", player_name(*player), card_name(land))
+            }
+            LogEvent::Phase { phase } => format!("{:?} phase", phase),
+            LogEvent::Turn { player, extra } => {
+                let extra = if *extra { "extra " } else { "" };
+                format!("{}'s {}turn", player_name(*player), extra)
+            }
+            LogEvent::Untap { ent } => format!("{} untaps", card_name(ent)),
+            LogEvent::GainLife { player, amount } => {
+                format!("{} gains {} life", player_name(*player), amount)
+            }
+            LogEvent::TriggeredAbil { source, .. } => {
+                format!("{}'s ability triggers", card_name(source))
+            }
+            LogEvent::PutCounter { affected, counter, quantity } => {
+                format!("{} {:?} counter(s) put on {}", quantity, counter, target_name(affected, player_name))
+            }
+        }
+    }
+}
+impl LogEventResult {
+    pub fn render(&self, player_name: &impl Fn(PlayerId) -> String) -> String {
+        match self {
+            LogEventResult::Draw(card) => format!("{} was drawn", card_name(card)),
+            LogEventResult::MoveZones(moves) => {
+                let moves: Vec<String> = moves
+                    .iter()
+                    .map(|moved| match &moved.source {
+                        Some(source) => format!("{} went from {:?} to {:?}", card_name(&moved.oldent), source, moved.dest),
+                        None => format!("{} went to {:?}", card_name(&moved.oldent), moved.dest),
+                    })
+                    .collect();
+                moves.join(", ")
+            }
+            LogEventResult::Tap(card) => format!("{} became tapped", card_name(card)),
+            LogEventResult::Untap(card) => format!("{} became untapped", card_name(card)),
+            LogEventResult::Attacks(attacks) => {
+                let attacks: Vec<String> = attacks
+                    .iter()
+                    .map(|(attacker, target)| format!("{} attacks {}", card_name(attacker), target_name(target, player_name)))
+                    .collect();
+                attacks.join(", ")
+            }
+        }
+    }
+}
+impl LogEntry {
+    //What the entry says in words, like "p1 casts Murder targeting Aven Gagglemaster"
+    pub fn render(&self, player_name: impl Fn(PlayerId) -> String) -> String {
+        match self {
+            LogEntry::DiesFromZeroOrLessToughness(card) => {
+                format!("{} dies with 0 or less toughness", card_name(card))
+            }
+            LogEntry::DestroyFromDamage(card) => {
+                format!("{} is destroyed by lethal damage", card_name(card))
+            }
+            LogEntry::DetachedEnchantmentDies(card) => {
+                format!("{} is put into the graveyard, it isn't attached to anything", card_name(card))
+            }
+            LogEntry::EnchantmentFallsOff(card) => format!("{} falls off", card_name(card)),
+            LogEntry::CastFailedFromRestriction(card) => {
+                format!("{} can't be cast right now", card_name(card))
+            }
+            LogEntry::ManaCostNotPaid(card) => {
+                format!("{}'s mana cost couldn't be paid", card_name(card))
+            }
+            LogEntry::Cast(cast, targets) => {
+                let mut text = format!("{} casts {}", player_name(cast.player), card_name(&cast.stack_ent));
+                if !targets.is_empty() {
+                    let targets: Vec<String> = targets
+                        .iter()
+                        .map(|target| target_name(target, &player_name))
+                        .collect();
+                    text += &format!(" targeting {}", targets.join(" and "));
+                }
+                text
+            }
+            LogEntry::Resolve(card, _) => format!("{} resolves", card_name(card)),
+            LogEntry::Text(text) => text.clone(),
+            LogEntry::TriggeredAbil(card) => format!("{}'s ability triggers", card_name(card)),
+            LogEntry::Lose(player) => format!("{} loses the game", player_name(*player)),
+            LogEntry::LoseFromLife(player) => {
+                format!("{} loses the game at 0 or less life", player_name(*player))
+            }
+            LogEntry::LoseFromCommanderDamage(player) => {
+                format!("{} loses the game to commander damage", player_name(*player))
+            }
+            LogEntry::LoseFromDisconnect(player) => {
+                format!("{} loses the game after disconnecting", player_name(*player))
+            }
+            LogEntry::Event(event) => event.render(&player_name),
+            LogEntry::EventResult(result) => result.render(&player_name),
+            LogEntry::Prevented(event) => format!("Prevented: {}", event.render(&player_name)),
+            LogEntry::Replaced(source, event, replacements) => {
+                let replacements: Vec<String> = replacements
+                    .iter()
+                    .map(|replacement| replacement.render(&player_name))
+                    .collect();
+                format!(
+                    "{} replaced \"{}\" with \"{}\"",
+                    card_name(source),
+                    event.render(&player_name),
+                    replacements.join(", ")
+                )
+            }
+        }
+    }
+}
+
+//When in the game an entry was logged
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct LogStamp {
+    pub turn: u32,
+    pub active_player: PlayerId,
+    pub phase: Option<Phase>,
+    pub subphase: Option<Subphase>,
+}
+//A log entry as clients get it
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LogMessage {
+    pub index: usize, //Position in the game's log, clients skip the entries they can't see
+    pub stamp: LogStamp,
+    pub text: String,
+    pub entry: LogEntry,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LoggedEntry {
+    pub message: LogMessage,
+    //The players that knew every card the entry names when it was logged,
+    //None if it doesn't name any. Cards can be hidden again later, so this
+    //isn't worked out when the entry is sent
+    pub known_to: Option<HashSet<PlayerId>>,
+}
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct GameLog {
+    pub entries: Vec<LoggedEntry>,
+    sent: usize,
+}
+impl GameLog {
+    //Entries that haven't been sent to clients yet
+    pub fn unsent(&mut self) -> Vec<LoggedEntry> {
+        let unsent = self.entries[self.sent..].to_vec();
+        self.sent = self.entries.len();
+        unsent
+    }
+}
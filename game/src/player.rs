@@ -1,24 +1,26 @@
-use crate::client_message::{Ask, AskPair, AskSelectN, ClientMessage, GameState};
-use crate::game::Cards;
+use crate::ai::AiClient;
+use crate::client_message::{Ask, AskPair, AskResponse, AskSelectN, GameState, RequestId};
+use crate::decision::{Absent, Answer, AskContext, DecisionProvider, Gone, Response};
+use crate::game::{Cards, Game};
+use crate::log::LogMessage;
+use crate::replay::ReplayClient;
 use anyhow::Result;
+use async_trait::async_trait;
 use common::actions::Action;
 use common::counters::Counter;
 use common::entities::{CardId, ManaId, PlayerId, TargetId};
 use common::hashset_obj::HashSetObj;
-use futures::{SinkExt, StreamExt};
 use schemars::JsonSchema;
-use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
-use std::ops::DerefMut;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use warp::filters::ws::Message;
 
-use warp::ws::WebSocket;
-#[derive(Clone, JsonSchema, Debug)]
+#[derive(Clone, JsonSchema, Debug, Serialize, Deserialize)]
 pub struct Player {
     pub name: String,
     pub life: i64,
@@ -28,11 +30,13 @@ pub struct Player {
     pub graveyard: Vec<CardId>,
     pub counters: Vec<Counter>,
     pub max_handsize: usize,
+    pub commander_casts: i64, //Times the commander has been cast from the command zone
+    pub commander_damage: HashMap<PlayerId, i64>, //Combat damage taken from each player's commander
     #[serde(skip)]
     pub player_con: PlayerCon,
 }
 
-#[derive(Serialize, Deserialize, JsonSchema)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct PlayerView {
     pub name: String,
     pub life: i64,
@@ -42,37 +46,26 @@ pub struct PlayerView {
     pub mana_pool: HashSetObj<ManaId>,
     pub counters: Vec<Counter>,
     pub max_handsize: usize,
+    pub commander_casts: i64,
+    pub commander_damage: HashMap<PlayerId, i64>,
 }
+//Cards the viewer doesn't know are swapped for their hidden ids
 fn view_t<'a>(
     cards: &'a Cards,
     r: impl Iterator<Item = &'a CardId> + 'a,
-    pl: PlayerId,
     hidden_map: &'a HashMap<CardId, CardId>,
 ) -> impl Iterator<Item = CardId> + 'a {
-    r.map(move |&id| {
-        cards
-            .get(id)
-            .map(|ent| {
-                if ent.known_to.contains(&pl) {
-                    Some(id)
-                } else {
-                    hidden_map.get(&id).map(|x| *x) //If the player's hand contains a card not in the game
-                                                    //don't send it to the client because it will confuse the javascript
-                }
-            })
-            .flatten()
+    r.filter_map(move |&id| {
+        //If the player's hand contains a card not in the game
+        //don't send it to the client because it will confuse the javascript
+        cards.get(id)?;
+        Some(hidden_map.get(&id).copied().unwrap_or(id))
     })
-    .filter_map(|x| x)
 }
 impl Player {
-    pub fn view(
-        &self,
-        cards: &Cards,
-        player: PlayerId,
-        hidden_map: &HashMap<CardId, CardId>,
-    ) -> PlayerView {
-        let libview = view_t(cards, self.library.iter(), player, hidden_map).collect::<Vec<_>>();
-        let handview = view_t(cards, self.hand.iter(), player, hidden_map).collect::<Vec<_>>();
+    pub fn view(&self, cards: &Cards, hidden_map: &HashMap<CardId, CardId>) -> PlayerView {
+        let libview = view_t(cards, self.library.iter(), hidden_map).collect::<Vec<_>>();
+        let handview = view_t(cards, self.hand.iter(), hidden_map).collect::<Vec<_>>();
         PlayerView {
             name: self.name.clone(),
             life: self.life,
@@ -82,240 +75,368 @@ impl Player {
             mana_pool: self.mana_pool.clone(),
             max_handsize: self.max_handsize,
             counters: self.counters.clone(),
+            commander_casts: self.commander_casts,
+            commander_damage: self.commander_damage.clone(),
         }
     }
 
-    pub async fn send_data(&self, data: ClientMessage) -> Result<()> {
-        let mut buffer = Vec::new();
-        {
-            let cursor = std::io::Cursor::new(&mut buffer);
-            let mut json_serial = serde_json::Serializer::new(cursor);
-            data.serialize(&mut json_serial)?;
-        }
-        self.player_con.send_data(buffer).await
-    }
-
-    //Select n entities from a vector, returns selected indicies
-    pub async fn ask_user_selectn<T>(&self, query: &Ask, ask: &AskSelectN<T>) -> HashSetObj<usize> {
+    async fn ask<R>(
+        &self,
+        game: &Game,
+        player: PlayerId,
+        query: &Ask,
+        answer: impl Fn(Response) -> Option<R>,
+        validate: impl Fn(&R) -> Result<(), String>,
+    ) -> Option<R> {
+        let id = self.player_con.next_request_id();
+        let ctx = AskContext { id, game, player };
         loop {
-            self.send_data(ClientMessage::AskUser(query.clone()))
-                .await
-                .expect("Failed to send data");
-            let response = self.player_con.receive::<HashSetObj<usize>>().await;
-            let response = if let Ok(resp) = response {
-                resp
-            } else {
-                continue;
-            };
-            if response.len() < ask.min.try_into().unwrap()
-                || response.len() > ask.max.try_into().unwrap()
-                || response.iter().any(|&i| i >= ask.ents.len())
-            {
-                continue;
+            //Nobody is left to answer
+            let response = self.player_con.decide(ctx, query).await.ok()?;
+            self.player_con.record_response(id, &response);
+            let checked = answer(response)
+                .ok_or_else(|| "that's the wrong kind of answer".to_owned())
+                .and_then(|answer| validate(&answer).map(|()| answer));
+            match checked {
+                Ok(answer) => {
+                    self.player_con.accepted(id).await;
+                    return Some(answer);
+                }
+                Err(reason) => self.player_con.rejected(id, reason).await,
             }
-            println!("accepted {:?}", response);
-            return response;
         }
     }
-    //pair attackers with blockers/attacking targets
-    //Returns an adjacency list with either the
-    //planeswalker/player each attacker is attacking,
-    //or the list of creatures each blocker is blocking
-    pub async fn ask_user_pair<T: DeserializeOwned + Hash + Eq + Copy + Clone + Debug>(
+    //Select n entities from a vector, returns selected indicies
+    pub async fn ask_user_selectn<T>(
         &self,
+        game: &Game,
+        player: PlayerId,
         query: &Ask,
-        ask: &AskPair<T>,
-    ) -> HashMap<CardId, HashSetObj<T>> {
-        'outer: loop {
-            self.send_data(ClientMessage::AskUser(query.clone()))
-                .await
-                .expect("Failed to send data");
-            let response = self
-                .player_con
-                .receive::<HashMap<CardId, HashSetObj<T>>>()
-                .await;
-            let response = if let Ok(resp) = response {
-                resp
-            } else {
-                continue 'outer;
-            };
-            for (card, pairing) in response.iter() {
-                if let Some(input) = ask.pairs.get(card) {
-                    let items = input.items.clone();
-                    if pairing.len() < input.min
-                        || pairing.len() > input.max
-                        || !items.is_subset(pairing)
-                    {
-                        continue 'outer;
-                    }
-                } else {
-                    continue 'outer;
-                }
-            }
-            println!("accepted {:?}", response);
-            return response;
+        ask: &AskSelectN<T>,
+    ) -> HashSetObj<usize> {
+        let selection = |response| match response {
+            Response::Selection(selection) => Some(selection),
+            _ => None,
+        };
+        self.ask(game, player, query, selection, |selection| {
+            validate_selectn(selection, ask)
+        })
+        .await
+        //Take the smallest allowed selection for a player who's gone
+        .unwrap_or_else(|| (0..ask.min.try_into().unwrap()).collect())
+    }
+    //Returns the planeswalker or player each attacker is attacking
+    pub async fn ask_attackers(
+        &self,
+        game: &Game,
+        player: PlayerId,
+        ask: &AskPair<TargetId>,
+    ) -> HashMap<CardId, HashSetObj<TargetId>> {
+        let attacks = |response| match response {
+            Response::Attacks(attacks) => Some(attacks),
+            _ => None,
+        };
+        let query = Ask::Attackers(ask.clone());
+        self.ask(game, player, &query, attacks, |attacks| {
+            validate_pair(attacks, ask)
+        })
+        .await
+        .unwrap_or_default()
+    }
+    //Returns the list of creatures each blocker is blocking
+    pub async fn ask_blockers(
+        &self,
+        game: &Game,
+        player: PlayerId,
+        ask: &AskPair<CardId>,
+    ) -> HashMap<CardId, HashSetObj<CardId>> {
+        let blocks = |response| match response {
+            Response::Blocks(blocks) => Some(blocks),
+            _ => None,
+        };
+        let query = Ask::Blockers(ask.clone());
+        self.ask(game, player, &query, blocks, |blocks| {
+            validate_pair(blocks, ask)
+        })
+        .await
+        .unwrap_or_default()
+    }
+}
+pub(crate) fn validate_selectn<T>(
+    response: &HashSetObj<usize>,
+    ask: &AskSelectN<T>,
+) -> Result<(), String> {
+    let min: usize = ask.min.try_into().unwrap();
+    let max: usize = ask.max.try_into().unwrap();
+    if response.len() < min {
+        return Err(format!(
+            "selected {} but at least {} are needed",
+            response.len(),
+            min
+        ));
+    }
+    if response.len() > max {
+        return Err(format!(
+            "selected {} but at most {} are allowed",
+            response.len(),
+            max
+        ));
+    }
+    if let Some(i) = response.iter().find(|&&i| i >= ask.ents.len()) {
+        return Err(format!(
+            "{} isn't a choice, there are only {}",
+            i,
+            ask.ents.len()
+        ));
+    }
+    Ok(())
+}
+pub(crate) fn validate_pair<T: Hash + Eq + Debug>(
+    response: &HashMap<CardId, HashSetObj<T>>,
+    ask: &AskPair<T>,
+) -> Result<(), String> {
+    for (card, pairing) in response.iter() {
+        let Some(input) = ask.pairs.get(card) else {
+            return Err(format!("{:?} can't be paired", card));
+        };
+        if pairing.len() < input.min || pairing.len() > input.max {
+            return Err(format!(
+                "{:?} has {} pairings, it needs between {} and {}",
+                card,
+                pairing.len(),
+                input.min,
+                input.max
+            ));
+        }
+        if !input.items.is_subset(pairing) {
+            return Err(format!(
+                "{:?} can only be paired with {:?}",
+                card,
+                input.items.iter().collect::<Vec<_>>()
+            ));
         }
     }
+    Ok(())
 }
 
-#[allow(dead_code)]
-pub enum Socket {
-    TestSocket(TestClient), //used in testing, and therefore isn't dead code
-    Web(WebSocket),
+//What a connection has been sent and answered, kept in snapshots so
+//request ids carry on and replays still cover the whole game
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ConnectionRecord {
+    pub requests: RequestId,
+    pub responses: Vec<Value>,
 }
 #[derive(Clone)]
 pub struct PlayerCon {
-    socket: Arc<Mutex<Socket>>,
+    provider: Arc<Mutex<Box<dyn DecisionProvider>>>,
+    wants_states: bool,
+    forfeited: Arc<AtomicBool>,
+    requests: Arc<AtomicU64>,
+    responses: Arc<std::sync::Mutex<Vec<Value>>>, //Every answer given, for replays
+}
+//Nobody is connected, the player forfeits at their first ask
+impl Default for PlayerCon {
+    fn default() -> Self {
+        PlayerCon::new(Absent)
+    }
 }
 impl Debug for PlayerCon {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PlayerCon")
-            .field("socket", &"socket")
+            .field("provider", &"provider")
+            .field("forfeited", &self.forfeited())
             .finish()
     }
 }
 impl PlayerCon {
-    pub fn new(socket: WebSocket) -> Self {
+    pub fn new(provider: impl DecisionProvider + 'static) -> Self {
         PlayerCon {
-            socket: Arc::new(Mutex::new(Socket::Web(socket))),
+            wants_states: provider.wants_states(),
+            provider: Arc::new(Mutex::new(Box::new(provider))),
+            forfeited: Arc::new(AtomicBool::new(false)),
+            requests: Arc::new(AtomicU64::new(0)),
+            responses: Default::default(),
         }
     }
     #[allow(dead_code)] //Used in test code and therefore isn't dead
     pub fn new_test(test: TestClient) -> Self {
-        PlayerCon {
-            socket: Arc::new(Mutex::new(Socket::TestSocket(test))),
+        PlayerCon::new(test)
+    }
+    //A computer player, it never disconnects
+    pub fn new_ai() -> Self {
+        PlayerCon::new(AiClient::default())
+    }
+    //Answers with the responses from a replay. Once they run out the
+    //player is treated as gone, like they were when it was recorded
+    pub fn new_replay(responses: Vec<Value>) -> Self {
+        PlayerCon::new(ReplayClient::new(responses))
+    }
+    pub fn next_request_id(&self) -> RequestId {
+        self.requests.fetch_add(1, Ordering::SeqCst) + 1
+    }
+    pub fn forfeited(&self) -> bool {
+        self.forfeited.load(Ordering::SeqCst)
+    }
+    pub fn responses(&self) -> Vec<Value> {
+        self.responses.lock().unwrap().clone()
+    }
+    pub fn record(&self) -> ConnectionRecord {
+        ConnectionRecord {
+            requests: self.requests.load(Ordering::SeqCst),
+            responses: self.responses(),
         }
     }
-    pub async fn receive<T: DeserializeOwned>(&self) -> Result<T> {
-        let mut socket = self.socket.lock().await;
-        loop {
-            let recieved = match socket.deref_mut() {
-                Socket::TestSocket(test) => test.recieve(),
-                Socket::Web(socket) => socket.next().await.expect("Socket is still open"),
-            };
-            let message = if let Ok(msg) = recieved {
-                msg
-            } else {
-                PlayerCon::socket_error().await;
-                continue;
-            };
-            let text = message
-                .to_str()
-                .map_err(|_| anyhow::Error::msg("Didn't recieve a string"))?;
-            println!("parsing:{}", text);
-            return serde_json::from_str(text)
-                .map_err(|_| anyhow::Error::msg("Message failed to parse correctly"));
+    //Picks up where a connection from before a restart left off
+    pub fn restore(&self, record: ConnectionRecord) {
+        self.requests.store(record.requests, Ordering::SeqCst);
+        *self.responses.lock().unwrap() = record.responses;
+    }
+    fn record_response(&self, id: RequestId, response: &Response) {
+        match serde_json::to_value(AskResponse { id, response }) {
+            Ok(response) => self.responses.lock().unwrap().push(response),
+            Err(err) => println!("Failed to record response to {}: {}", id, err),
         }
     }
-    pub async fn send_data(&self, data: Vec<u8>) -> Result<()> {
-        let msg = std::str::from_utf8(&data).expect("json is valid text");
-        let mut socket = self.socket.lock().await;
-        match socket.deref_mut() {
-            Socket::TestSocket(test) => test.send_message(Message::text(msg)),
-            Socket::Web(socket) => socket
-                .send(Message::text(msg))
+    //Building states for a player is skipped when they'd go unused
+    pub fn wants_states(&self) -> bool {
+        self.wants_states && !self.forfeited()
+    }
+    pub async fn state(&self, state: &GameState) {
+        if self.wants_states() {
+            self.provider.lock().await.state(state).await;
+        }
+    }
+    pub async fn log(&self, messages: &[LogMessage]) {
+        if !self.forfeited() {
+            self.provider.lock().await.log(messages).await;
+        }
+    }
+    async fn decide(&self, ctx: AskContext<'_>, query: &Ask) -> Answer<Response> {
+        if self.forfeited() {
+            return Err(Gone);
+        }
+        let mut provider = self.provider.lock().await;
+        let response = match query {
+            Ask::Action(ask) => provider
+                .select_action(ctx, ask)
+                .await
+                .map(Response::Selection),
+            Ask::Target(ask) => provider
+                .select_targets(ctx, ask)
+                .await
+                .map(Response::Selection),
+            Ask::Attackers(ask) => provider
+                .select_attackers(ctx, ask)
+                .await
+                .map(Response::Attacks),
+            Ask::Blockers(ask) => provider
+                .select_blockers(ctx, ask)
                 .await
-                .map_err(|_| anyhow::Error::msg("Connection broke on send")),
+                .map(Response::Blocks),
+            Ask::DiscardToHandSize(ask) => provider
+                .discard_to_hand_size(ctx, ask)
+                .await
+                .map(Response::Selection),
+            Ask::CommandZone(ask) => provider
+                .command_zone(ctx, ask)
+                .await
+                .map(Response::Selection),
+        };
+        if response.is_err() {
+            self.forfeited.store(true, Ordering::SeqCst);
         }
+        response
     }
-    async fn socket_error() {
-        panic!("Connection broke on read");
+    async fn rejected(&self, id: RequestId, reason: String) {
+        println!("rejected response to {}: {}", id, reason);
+        self.provider.lock().await.rejected(id, &reason).await;
+    }
+    async fn accepted(&self, id: RequestId) {
+        self.provider.lock().await.accepted(id).await;
     }
 }
 
+//Answers with a mock client, using the latest state the game sent
 pub struct TestClient {
     game: Option<GameState>,
-    prepared_response: ClientResponse,
     mock_client: Box<dyn MockClient>,
 }
 impl TestClient {
     pub fn with_client(client: Box<dyn MockClient>) -> Self {
         Self {
             game: None,
-            prepared_response: ClientResponse::None,
             mock_client: client,
         }
     }
-    pub fn send_message(&mut self, msg: Message) -> Result<()> {
-        let text = msg.to_str().expect("message is text");
-        let contents: Result<ClientMessage, _> = serde_json::from_str(text);
-        let contents = contents.expect("parsed correctly");
-        match contents {
-            ClientMessage::GameState(state) => {
-                self.game = Some(state);
-            }
-            ClientMessage::AskUser(ask) => {
-                self.prepared_response = TestClient::respond(
-                    &mut self.mock_client,
-                    &self.game.as_ref().expect("game is set"),
-                    ask,
-                );
-            }
-        }
-        Ok(())
-    }
-    fn respond(
-        mock_client: &mut Box<dyn MockClient>,
-        game: &GameState,
-        ask: Ask,
-    ) -> ClientResponse {
-        return match &ask {
-            Ask::Action(act) => {
-                if act.min == 0 && act.max == 1 && act.ents.len() == 0 {
-                    ClientResponse::Indicies(HashSetObj::new())
-                } else {
-                    ClientResponse::Indicies(mock_client.select_action(game, act))
-                }
-            }
-            Ask::Target(ask) => {
-                let resp = mock_client.select_targets(game, ask);
-                ClientResponse::Indicies(resp)
-            }
-            Ask::Attackers(attacks) => {
-                let resp = mock_client.select_attacks(game, attacks);
-                ClientResponse::AttaksOrBlocks(resp)
-            }
-            _ => {
-                dbg!(ask);
-                panic!("Can't handle query");
-            }
-        };
-    }
-    pub fn recieve(&mut self) -> Result<Message, warp::Error> {
-        let mut buffer = Vec::new();
-        let cursor = std::io::Cursor::new(&mut buffer);
-        let mut json_serial = serde_json::Serializer::new(cursor);
-        match &self.prepared_response {
-            ClientResponse::None => {
-                panic!("response not set");
-            }
-            ClientResponse::Indicies(indexs) => {
-                indexs
-                    .serialize(&mut json_serial)
-                    .expect("serialized to json correctly");
-            }
-            ClientResponse::AttaksOrBlocks(data) => {
-                data.serialize(&mut json_serial)
-                    .expect("serialized to json correctly");
-            }
-        };
-        let msg = std::str::from_utf8(&buffer).expect("json is valid text");
-        return Ok(Message::text(msg));
+    fn game(&self) -> &GameState {
+        self.game.as_ref().expect("game is set")
     }
 }
 impl Default for TestClient {
     fn default() -> Self {
-        Self {
-            game: None,
-            prepared_response: ClientResponse::None,
-            mock_client: Box::new(PanicClient {}),
-        }
+        TestClient::with_client(Box::new(PanicClient {}))
     }
 }
-pub enum ClientResponse {
-    None,
-    Indicies(HashSetObj<usize>),
-    AttaksOrBlocks(HashMap<CardId, HashSetObj<TargetId>>),
+#[async_trait]
+impl DecisionProvider for TestClient {
+    async fn state(&mut self, state: &GameState) {
+        self.game = Some(state.clone());
+    }
+    async fn select_action(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        if ask.min == 0 && ask.max == 1 && ask.ents.len() == 0 {
+            return Ok(HashSetObj::new());
+        }
+        let game = self.game.as_ref().expect("game is set");
+        Ok(self.mock_client.select_action(game, ask))
+    }
+    async fn select_targets(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        let game = self.game.as_ref().expect("game is set");
+        Ok(self.mock_client.select_targets(game, ask))
+    }
+    async fn select_attackers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        let game = self.game.as_ref().expect("game is set");
+        Ok(self.mock_client.select_attacks(game, ask))
+    }
+    async fn select_blockers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        dbg!(ask, self.game().game.phase);
+        panic!("Can't handle query");
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        dbg!(ask);
+        panic!("Can't handle query");
+    }
+    async fn command_zone(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        let game = self.game.as_ref().expect("game is set");
+        Ok(self.mock_client.select_command_zone(game, ask))
+    }
+    //Mock clients are expected to answer correctly
+    async fn rejected(&mut self, id: RequestId, reason: &str) {
+        panic!("response to {} was rejected: {}", id, reason);
+    }
 }
 
 pub trait MockClient: Send + Sync {
@@ -352,6 +473,14 @@ pub trait MockClient: Send + Sync {
         dbg!(ask);
         panic!("Select attacks not overriden");
     }
+    //By default commanders always go back to the command zone
+    fn select_command_zone(
+        &mut self,
+        _game: &GameState,
+        ask: &AskSelectN<CardId>,
+    ) -> HashSetObj<usize> {
+        (0..ask.ents.len()).collect()
+    }
 }
 pub struct PanicClient {}
 impl MockClient for PanicClient {}
@@ -0,0 +1,174 @@
+use crate::client_message::{AskResponse, ClientMessage, ClientRequest};
+use carddb::decklist::DeckSubmission;
+use common::entities::{CardId, TargetId};
+use common::hashset_obj::HashSetObj;
+use schemars::gen::SchemaSettings;
+use schemars::schema::RootSchema;
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+mod typescript;
+
+//Bump whenever a message changes shape, and raise the minimum
+//once the server stops understanding older clients
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+//Versions are offered as websocket subprotocols, e.g. "mtg.v1" for
+//JSON or "mtg.v1.msgpack" for MessagePack
+const SUBPROTOCOL_PREFIX: &str = "mtg.v";
+
+//Everything a client can send. Nothing on the wire says which of these a
+//message is, answers to an Ask have the response type the Ask called for
+#[derive(JsonSchema)]
+#[serde(untagged)]
+#[allow(dead_code)] //Only exists for its schema
+pub enum ClientToServer {
+    Deck(DeckSubmission),
+    Request(ClientRequest),
+    //Action, target and command zone asks
+    Selection(AskResponse<HashSetObj<usize>>),
+    Attacks(AskResponse<HashMap<CardId, HashSetObj<TargetId>>>),
+    Blocks(AskResponse<HashMap<CardId, HashSetObj<CardId>>>),
+}
+
+//How messages are encoded on a connection. MessagePack frames hold the
+//same document the JSON ones would, so map keys are strings in both
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    #[default]
+    Json,
+    MessagePack,
+}
+//A websocket frame, left to the server to put on whichever socket it's using
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Frame {
+    Text(String),
+    Binary(Vec<u8>),
+}
+impl Frame {
+    pub fn is_binary(&self) -> bool {
+        matches!(self, Frame::Binary(_))
+    }
+}
+impl Encoding {
+    fn suffix(self) -> &'static str {
+        match self {
+            Encoding::Json => "",
+            Encoding::MessagePack => ".msgpack",
+        }
+    }
+    pub fn encode<T: Serialize>(self, message: &T) -> Result<Frame> {
+        Ok(match self {
+            Encoding::Json => Frame::Text(serde_json::to_string(message)?),
+            Encoding::MessagePack => {
+                let document = serde_json::to_value(message)?;
+                Frame::Binary(rmp_serde::to_vec_named(&document)?)
+            }
+        })
+    }
+}
+//Clients may answer in either encoding, the frame type says which one was used
+pub fn decode<T: DeserializeOwned>(frame: &Frame) -> Result<T> {
+    let document: Value = match frame {
+        Frame::Binary(bytes) => rmp_serde::from_slice(bytes)?,
+        Frame::Text(text) => serde_json::from_str(text)?,
+    };
+    Ok(serde_json::from_value(document)?)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Negotiated {
+    pub version: u32,
+    pub encoding: Encoding,
+}
+pub fn subprotocol(version: u32, encoding: Encoding) -> String {
+    format!("{}{}{}", SUBPROTOCOL_PREFIX, version, encoding.suffix())
+}
+fn parse_subprotocol(protocol: &str) -> Option<Negotiated> {
+    let version = protocol.trim().strip_prefix(SUBPROTOCOL_PREFIX)?;
+    let (version, encoding) = match version.strip_suffix(Encoding::MessagePack.suffix()) {
+        Some(version) => (version, Encoding::MessagePack),
+        None => (version, Encoding::Json),
+    };
+    let version = version.parse::<u32>().ok()?;
+    (MIN_PROTOCOL_VERSION..=PROTOCOL_VERSION)
+        .contains(&version)
+        .then_some(Negotiated { version, encoding })
+}
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsupportedProtocol {
+    pub offered: String,
+}
+impl fmt::Display for UnsupportedProtocol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "none of \"{}\" are supported, this server speaks {} to {}",
+            self.offered,
+            subprotocol(MIN_PROTOCOL_VERSION, Encoding::Json),
+            subprotocol(PROTOCOL_VERSION, Encoding::Json)
+        )
+    }
+}
+//Picks the newest version both sides speak from a Sec-WebSocket-Protocol header,
+//using the encoding the client listed first for it. Clients that don't offer
+//any subprotocol get the current version over JSON, and no subprotocol
+//header should be sent back to them
+pub fn negotiate(offered: Option<&str>) -> Result<Option<Negotiated>, UnsupportedProtocol> {
+    let Some(offered) = offered else {
+        return Ok(None);
+    };
+    let mut best: Option<Negotiated> = None;
+    for negotiated in offered.split(',').filter_map(parse_subprotocol) {
+        if best.map_or(true, |best| negotiated.version > best.version) {
+            best = Some(negotiated);
+        }
+    }
+    best.map(Some).ok_or_else(|| UnsupportedProtocol {
+        offered: offered.to_owned(),
+    })
+}
+
+fn versioned(mut schema: RootSchema) -> RootSchema {
+    schema
+        .schema
+        .extensions
+        .insert("protocolVersion".to_owned(), json!(PROTOCOL_VERSION));
+    schema
+}
+//Server to client messages
+pub fn server_schema() -> RootSchema {
+    versioned(
+        SchemaSettings::draft07()
+            .into_generator()
+            .into_root_schema_for::<ClientMessage>(),
+    )
+}
+//Client to server messages
+pub fn client_schema() -> RootSchema {
+    versioned(
+        SchemaSettings::draft07()
+            .into_generator()
+            .into_root_schema_for::<ClientToServer>(),
+    )
+}
+//TypeScript definitions for both directions
+pub fn typescript() -> String {
+    let mut gen = SchemaSettings::draft07().into_generator();
+    gen.subschema_for::<ClientMessage>();
+    gen.subschema_for::<ClientToServer>();
+    let definitions: Vec<(String, Value)> = gen
+        .definitions()
+        .iter()
+        .map(|(name, schema)| {
+            let schema = serde_json::to_value(schema).expect("schemas serialize");
+            (name.clone(), schema)
+        })
+        .collect();
+    typescript::definitions(PROTOCOL_VERSION, &definitions)
+}
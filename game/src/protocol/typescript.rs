@@ -0,0 +1,118 @@
+use serde_json::{Map, Value};
+
+//Turns the subset of JSON schema that schemars generates into TypeScript types
+pub fn definitions(version: u32, definitions: &[(String, Value)]) -> String {
+    let mut out = String::new();
+    out.push_str("/* tslint:disable */\n");
+    out.push_str("// Generated by `cargo run --bin gen-schema`, don't edit by hand\n\n");
+    out.push_str(&format!("export const PROTOCOL_VERSION = {};\n", version));
+    for (name, schema) in definitions {
+        if let Some(description) = schema.get("description").and_then(Value::as_str) {
+            out.push_str(&format!("\n/** {} */", description));
+        }
+        out.push_str(&format!(
+            "\nexport type {} = {};\n",
+            type_name(name),
+            to_type(schema)
+        ));
+    }
+    out
+}
+fn type_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+fn union(types: Vec<String>) -> String {
+    match types.len() {
+        0 => "never".to_owned(),
+        1 => types.into_iter().next().unwrap(),
+        _ => format!("({})", types.join(" | ")),
+    }
+}
+fn to_type(schema: &Value) -> String {
+    let schema = match schema {
+        Value::Bool(true) => return "unknown".to_owned(),
+        Value::Bool(false) => return "never".to_owned(),
+        Value::Object(schema) => schema,
+        _ => return "unknown".to_owned(),
+    };
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return type_name(reference.trim_start_matches("#/definitions/"));
+    }
+    if let Some(value) = schema.get("const") {
+        return value.to_string();
+    }
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return union(values.iter().map(Value::to_string).collect());
+    }
+    let mut parts = Vec::new();
+    for key in ["oneOf", "anyOf"] {
+        if let Some(options) = schema.get(key).and_then(Value::as_array) {
+            parts.push(union(options.iter().map(to_type).collect()));
+        }
+    }
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        parts.extend(all.iter().map(to_type));
+    }
+    if let Some(instance) = schema.get("type") {
+        let instances: Vec<&str> = match instance {
+            Value::String(single) => vec![single],
+            Value::Array(many) => many.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        let types = instances
+            .into_iter()
+            .map(|instance| instance_type(instance, schema))
+            .collect();
+        parts.push(union(types));
+    }
+    match parts.len() {
+        0 => "unknown".to_owned(),
+        1 => parts.pop().unwrap(),
+        _ => format!("({})", parts.join(" & ")),
+    }
+}
+fn instance_type(instance: &str, schema: &Map<String, Value>) -> String {
+    match instance {
+        "null" => "null".to_owned(),
+        "boolean" => "boolean".to_owned(),
+        "integer" | "number" => "number".to_owned(),
+        "string" => "string".to_owned(),
+        "array" => match schema.get("items") {
+            Some(Value::Array(tuple)) => {
+                let items: Vec<String> = tuple.iter().map(to_type).collect();
+                format!("[{}]", items.join(", "))
+            }
+            Some(items) => format!("{}[]", to_type(items)),
+            None => "unknown[]".to_owned(),
+        },
+        "object" => object_type(schema),
+        _ => "unknown".to_owned(),
+    }
+}
+fn object_type(schema: &Map<String, Value>) -> String {
+    let required: Vec<&str> = schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default();
+    let mut fields = Vec::new();
+    if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+        for (name, property) in properties {
+            let optional = if required.contains(&name.as_str()) {
+                ""
+            } else {
+                "?"
+            };
+            fields.push(format!("{}{}: {}", name, optional, to_type(property)));
+        }
+    }
+    match schema.get("additionalProperties") {
+        Some(Value::Bool(false)) => {}
+        Some(values) => fields.push(format!("[key: string]: {}", to_type(values))),
+        None if fields.is_empty() => fields.push("[key: string]: unknown".to_owned()),
+        None => {}
+    }
+    format!("{{ {} }}", fields.join("; "))
+}
@@ -0,0 +1,131 @@
+use crate::game::serialize_game::Viewer;
+use crate::game::{Game, GameOutcome};
+use crate::spectator::SpectatorView;
+use anyhow::Result;
+use common::entities::PlayerId;
+use common::format::Format;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const RECORD_SUFFIX: &str = ".game.json";
+const LOG_SUFFIX: &str = ".log.jsonl";
+
+//A finished game as kept for analysis. Each one is written next to
+//a JSON Lines file holding the game's whole log, one entry per line
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub format: Format,
+    pub players: Vec<RecordedPlayer>, //In turn order
+    pub outcome: GameOutcome,
+    pub turns: u32,
+    pub duration_secs: u64,
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RecordedPlayer {
+    pub player: PlayerId,
+    pub name: String,
+    pub deck: Vec<String>,
+    pub commander: Option<String>,
+}
+impl GameRecord {
+    pub fn won(&self, player: &RecordedPlayer) -> bool {
+        self.outcome == GameOutcome::Winner(player.player)
+    }
+}
+
+//Where finished games are recorded, a directory that fills up
+//with a record and a log file for each game
+#[derive(Clone, Debug)]
+pub struct RecordSink {
+    dir: PathBuf,
+}
+impl RecordSink {
+    pub fn new(dir: PathBuf) -> Self {
+        RecordSink { dir }
+    }
+    //Returns the path of the record, the log is written beside it
+    pub fn write(&self, key: &str, game: &Game, duration: Duration) -> Result<PathBuf> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut log = std::fs::File::create(self.dir.join(format!("{}{}", key, LOG_SUFFIX)))?;
+        let everything = Viewer::Spectator(SpectatorView::Omniscient {
+            delay: Duration::ZERO,
+        });
+        for message in game.log_for(everything) {
+            writeln!(log, "{}", serde_json::to_string(&message)?)?;
+        }
+        let path = self.dir.join(format!("{}{}", key, RECORD_SUFFIX));
+        std::fs::write(&path, serde_json::to_string(&game.record(duration))?)?;
+        Ok(path)
+    }
+    //Every record in the directory, skipping files that can't be read
+    pub fn load_all(&self) -> Result<Vec<GameRecord>> {
+        let mut records = Vec::new();
+        for entry in std::fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if !path.to_string_lossy().ends_with(RECORD_SUFFIX) {
+                continue;
+            }
+            match load(&path) {
+                Ok(record) => records.push(record),
+                Err(err) => println!("Skipping {}: {}", path.display(), err),
+            }
+        }
+        Ok(records)
+    }
+}
+fn load(path: &Path) -> Result<GameRecord> {
+    Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CardWinRate {
+    pub card: String,
+    pub games: u32, //Decks that played the card, each deck counts once however many copies it had
+    pub wins: u32,
+}
+impl CardWinRate {
+    pub fn rate(&self) -> f64 {
+        self.wins as f64 / self.games as f64
+    }
+}
+//Games that never finished are left out, ties count as a loss for everyone.
+//Sorted by win rate, then by how often the card was played
+pub fn win_rates(records: &[GameRecord]) -> Vec<CardWinRate> {
+    let mut rates: HashMap<&str, CardWinRate> = HashMap::new();
+    for record in records {
+        if record.outcome == GameOutcome::Ongoing {
+            continue;
+        }
+        for player in &record.players {
+            let cards: BTreeSet<&str> = player
+                .deck
+                .iter()
+                .chain(player.commander.iter())
+                .map(String::as_str)
+                .collect();
+            for card in cards {
+                let rate = rates.entry(card).or_insert_with(|| CardWinRate {
+                    card: card.to_owned(),
+                    games: 0,
+                    wins: 0,
+                });
+                rate.games += 1;
+                if record.won(player) {
+                    rate.wins += 1;
+                }
+            }
+        }
+    }
+    let mut rates: Vec<CardWinRate> = rates.into_values().collect();
+    rates.sort_by(|a, b| {
+        b.rate()
+            .total_cmp(&a.rate())
+            .then(b.games.cmp(&a.games))
+            .then(a.card.cmp(&b.card))
+    });
+    rates
+}
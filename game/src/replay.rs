@@ -0,0 +1,156 @@
+use crate::client_message::{AskPair, AskResponse, AskSelectN, RequestId};
+use crate::decision::{Answer, AskContext, DecisionProvider, Gone};
+use crate::game::build_game::GameBuilder;
+use crate::game::GameOutcome;
+use crate::player::PlayerCon;
+use anyhow::{bail, Result};
+use async_trait::async_trait;
+use carddb::carddb::CardDB;
+use common::actions::Action;
+use common::entities::{CardId, TargetId};
+use common::format::Format;
+use common::hashset_obj::HashSetObj;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+//A finished game, enough to play it again move for move
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub format: Format,
+    pub players: Vec<ReplayPlayer>, //In turn order
+    pub final_state: Value,
+}
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReplayPlayer {
+    pub name: String,
+    pub deck: Vec<String>,
+    pub commander: Option<String>,
+    pub responses: Vec<Value>, //Every answer the player gave, in order
+}
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+    pub fn save(&self, path: &Path) -> Result<()> {
+        Ok(std::fs::write(path, serde_json::to_string(self)?)?)
+    }
+}
+fn card_name(db: &'static CardDB, name: &str) -> Result<&'static str> {
+    match db.card_name(name) {
+        Some(name) => Ok(name),
+        None => bail!("{} isn't in the card database", name),
+    }
+}
+//Plays the game again without anyone connected, failing
+//if it doesn't end in the state that was recorded
+pub async fn replay(db: &'static CardDB, replay: &Replay) -> Result<GameOutcome> {
+    let mut gamebuild = GameBuilder::new();
+    gamebuild.set_format(replay.format);
+    gamebuild.set_seed(replay.seed);
+    for seat in &replay.players {
+        let deck = seat
+            .deck
+            .iter()
+            .map(|name| card_name(db, name))
+            .collect::<Result<Vec<_>>>()?;
+        let con = PlayerCon::new_replay(seat.responses.clone());
+        let player = gamebuild.add_player(&seat.name, db, &deck, con)?;
+        if let Some(commander) = &seat.commander {
+            gamebuild.set_commander(player, db, card_name(db, commander)?)?;
+        }
+    }
+    let mut game = gamebuild.build(db)?;
+    let outcome = game.run().await;
+    let final_state = game.final_state()?;
+    if final_state != replay.final_state {
+        let differs: Vec<&String> = final_state
+            .as_object()
+            .into_iter()
+            .flatten()
+            .filter(|(key, value)| replay.final_state.get(key.as_str()) != Some(*value))
+            .map(|(key, _)| key)
+            .collect();
+        bail!("replay diverged from the recording, {:?} differ", differs)
+    }
+    Ok(outcome)
+}
+
+//Answers with recorded responses, in the order they were given
+pub struct ReplayClient {
+    responses: VecDeque<Value>,
+}
+impl ReplayClient {
+    pub fn new(responses: Vec<Value>) -> Self {
+        ReplayClient {
+            responses: responses.into(),
+        }
+    }
+    //Answers to earlier asks and ones that don't parse were rejected when
+    //they were recorded, so they're passed over the same way
+    fn next<T: DeserializeOwned>(&mut self, id: RequestId) -> Answer<T> {
+        while let Some(response) = self.responses.pop_front() {
+            let Ok(response) = serde_json::from_value::<AskResponse<Value>>(response) else {
+                continue;
+            };
+            if response.id != id {
+                continue;
+            }
+            if let Ok(answer) = serde_json::from_value(response.response) {
+                return Ok(answer);
+            }
+        }
+        Err(Gone)
+    }
+}
+#[async_trait]
+impl DecisionProvider for ReplayClient {
+    fn wants_states(&self) -> bool {
+        false
+    }
+    async fn select_action(
+        &mut self,
+        ctx: AskContext<'_>,
+        _ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.next(ctx.id)
+    }
+    async fn select_targets(
+        &mut self,
+        ctx: AskContext<'_>,
+        _ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.next(ctx.id)
+    }
+    async fn select_attackers(
+        &mut self,
+        ctx: AskContext<'_>,
+        _ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        self.next(ctx.id)
+    }
+    async fn select_blockers(
+        &mut self,
+        ctx: AskContext<'_>,
+        _ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        self.next(ctx.id)
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        ctx: AskContext<'_>,
+        _ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.next(ctx.id)
+    }
+    async fn command_zone(
+        &mut self,
+        ctx: AskContext<'_>,
+        _ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.next(ctx.id)
+    }
+}
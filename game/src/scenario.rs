@@ -0,0 +1,747 @@
+use crate::client_message::{AskPair, AskSelectN, RequestId};
+use crate::decision::{Answer, AskContext, DecisionProvider};
+use crate::game::build_game::GameBuilder;
+use crate::game::{Game, GameOutcome, Phase, Subphase};
+use crate::player::PlayerCon;
+use anyhow::Result;
+use async_trait::async_trait;
+use carddb::carddb::CardDB;
+use common::actions::Action;
+use common::card_entities::CardEnt;
+use common::counters::Counter;
+use common::entities::{CardId, PlayerId, TargetId};
+use common::hashset_obj::HashSetObj;
+use common::mana::ManaCostSymbol;
+use common::zones::Zone;
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::hash::Hash;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+//A rules test written down as data: the board each player starts with,
+//what each player answers when they're asked, and what the game should look
+//like afterwards. Scenarios are read from RON, see game/scenarios for examples
+#[derive(Clone, Debug, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default = "first_main")]
+    pub phase: Phase,
+    #[serde(default)]
+    pub subphase: Option<Subphase>, //The phase's first step if it has steps and none is given
+    #[serde(default)]
+    pub active: Option<String>, //The first player unless it's given
+    #[serde(default = "first_turn")]
+    pub turn: u32,
+    pub players: Vec<PlayerSetup>,
+    #[serde(default)]
+    pub run: Run,
+    #[serde(default)]
+    pub expect: Vec<Expectation>,
+}
+fn first_main() -> Phase {
+    Phase::FirstMain
+}
+fn first_turn() -> u32 {
+    1
+}
+#[derive(Clone, Debug, Deserialize)]
+pub struct PlayerSetup {
+    pub name: String,
+    #[serde(default = "starting_life")]
+    pub life: i64,
+    #[serde(default, deserialize_with = "card_setups")]
+    pub hand: Vec<CardSetup>,
+    #[serde(default, deserialize_with = "card_setups")]
+    pub library: Vec<CardSetup>, //Listed from the top down
+    #[serde(default, deserialize_with = "card_setups")]
+    pub graveyard: Vec<CardSetup>, //Listed from the bottom up
+    #[serde(default, deserialize_with = "card_setups")]
+    pub battlefield: Vec<CardSetup>,
+    #[serde(default, deserialize_with = "card_setups")]
+    pub exile: Vec<CardSetup>,
+    #[serde(default, deserialize_with = "card_setups")]
+    pub command: Vec<CardSetup>, //The player's commander
+    #[serde(default)]
+    pub counters: Vec<Counter>,
+    #[serde(default)]
+    pub mana: Vec<ManaCostSymbol>, //Already in the player's mana pool
+    #[serde(default)]
+    pub script: Vec<Step>,
+}
+fn starting_life() -> i64 {
+    crate::game::build_game::STARTING_LIFE
+}
+//Written as just the card's name, or with how the card is
+//like (name: "Staunch Shieldmate", tapped: true)
+#[derive(Clone, Debug, Deserialize)]
+pub struct CardSetup {
+    pub name: String,
+    #[serde(default)]
+    pub tapped: bool,
+    #[serde(default)]
+    pub counters: Vec<Counter>,
+    #[serde(default)]
+    pub damage: i64,
+}
+//Not an untagged enum, those lose the type hints RON needs to read enums like Counter
+fn card_setups<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<CardSetup>, D::Error> {
+    struct CardVisitor;
+    impl<'de> Visitor<'de> for CardVisitor {
+        type Value = CardSetup;
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "a card name or a card")
+        }
+        fn visit_str<E: de::Error>(self, name: &str) -> Result<CardSetup, E> {
+            Ok(CardSetup {
+                name: name.to_owned(),
+                tapped: false,
+                counters: Vec::new(),
+                damage: 0,
+            })
+        }
+        fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<CardSetup, A::Error> {
+            CardSetup::deserialize(de::value::MapAccessDeserializer::new(map))
+        }
+    }
+    struct Card(CardSetup);
+    impl<'de> Deserialize<'de> for Card {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(CardVisitor).map(Card)
+        }
+    }
+    let cards = Vec::<Card>::deserialize(deserializer)?;
+    Ok(cards.into_iter().map(|Card(card)| card).collect())
+}
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum Run {
+    #[default]
+    Priority, //Players get priority until they all pass with the stack empty
+    Turns(u32), //Then the game goes on until this many more turns have started
+}
+//A card or player, by name
+#[derive(Clone, Debug, Deserialize)]
+pub enum Target {
+    Card(String),
+    Player(String),
+}
+//One answer in a player's script. Asks the next step doesn't answer are
+//declined if they can be, so priority only needs passing on purpose when a
+//later step shouldn't be taken yet. Pass takes the smallest answer to anything
+#[derive(Clone, Debug, Deserialize)]
+pub enum Step {
+    Pass,
+    Cast(String),
+    PlayLand(String),
+    Activate(String),
+    Targets(Vec<Target>),
+    Attack(Vec<(String, Target)>),
+    Block(Vec<(String, String)>), //Blocker then attacker
+    Discard(Vec<String>),
+    CommandZone(Vec<String>),
+}
+#[derive(Clone, Debug, Deserialize)]
+pub enum Expectation {
+    Zone(String, Zone, Vec<String>), //Every card the player has there, in any order
+    Life(String, i64),
+    Tapped(String, Vec<String>),      //The player's tapped permanents
+    Counters(String, Counter, usize), //Across every permanent with the name
+    PowerToughness(String, i64, i64), //Of every permanent with the name
+    Log(String),                      //Some log message contains this
+    Outcome(ExpectedOutcome),
+    Phase(Phase, Option<Subphase>),
+}
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub enum ExpectedOutcome {
+    Ongoing,
+    Tie,
+    Winner(String),
+}
+
+impl Scenario {
+    pub fn from_ron(text: &str) -> Result<Self> {
+        Ok(ron::from_str(text)?)
+    }
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        Scenario::from_ron(&text).map_err(|err| anyhow::anyhow!("{}: {}", path.display(), err))
+    }
+    //Fails if the scenario can't be set up, otherwise returns the game
+    //as it was left along with everything that didn't go as expected
+    pub async fn play(&self, db: &'static CardDB) -> Result<(Game, Vec<String>)> {
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let mut scripts = Vec::new();
+        let mut gamebuild = GameBuilder::new();
+        gamebuild.set_seed(0);
+        let mut seats = Vec::new();
+        for setup in &self.players {
+            let mut deck = Vec::new();
+            for card in setup.cards() {
+                match db.card_name(&card.name) {
+                    Some(name) => deck.push(name),
+                    None => anyhow::bail!("{} isn't a card", &card.name),
+                }
+            }
+            let script = Arc::new(Mutex::new(setup.script.iter().cloned().collect()));
+            let client = ScriptedClient {
+                name: setup.name.clone(),
+                script: script.clone(),
+                failures: failures.clone(),
+                derailed: false,
+            };
+            let player = gamebuild.add_player(&setup.name, db, &deck, PlayerCon::new(client))?;
+            seats.push(player);
+            scripts.push((setup.name.clone(), script));
+        }
+        let mut game = gamebuild.build(db)?;
+        for (setup, &player) in self.players.iter().zip(&seats) {
+            self.place(&mut game, setup, player).await;
+        }
+        let active = match &self.active {
+            Some(name) => player_named(&game, name)
+                .ok_or_else(|| anyhow::anyhow!("{} isn't playing", name))?,
+            None => seats[0],
+        };
+        while game.turn_order[0] != active {
+            game.turn_order.rotate_left(1);
+        }
+        game.active_player = active;
+        game.priority = active;
+        game.turn = self.turn;
+        game.phase = Some(self.phase);
+        game.phases = later_phases(self.phase);
+        let mut subphases = subphases(self.phase);
+        if let Some(subphase) = self.subphase {
+            let Some(i) = subphases.iter().position(|&s| s == subphase) else {
+                anyhow::bail!("{:?} isn't a step of {:?}", subphase, self.phase);
+            };
+            subphases.drain(..i);
+        }
+        game.subphase = subphases.pop_front();
+        game.subphases = subphases;
+        game.layers_state_actions().await;
+        match self.run {
+            Run::Priority => game.cycle_priority().await,
+            Run::Turns(turns) => {
+                game.play_out(turns).await;
+            }
+        }
+
+        let mut failures = failures.lock().unwrap().clone();
+        for (name, script) in scripts {
+            let left = script.lock().unwrap().len();
+            if left > 0 {
+                failures.push(format!("{} still had {} steps to take", name, left));
+            }
+        }
+        for expectation in &self.expect {
+            if let Err(failure) = expectation.check(&game) {
+                failures.push(failure);
+            }
+        }
+        Ok((game, failures))
+    }
+    pub async fn check(&self, db: &'static CardDB) -> Result<()> {
+        let (_game, failures) = self.play(db).await?;
+        if !failures.is_empty() {
+            anyhow::bail!("{} failed:\n  {}", self.name, failures.join("\n  "));
+        }
+        Ok(())
+    }
+    //Cards are spawned into the library in the order cards lists them,
+    //then each one is put where it belongs without any event happening
+    async fn place(&self, game: &mut Game, setup: &PlayerSetup, player: PlayerId) {
+        let Some(pl) = game.players.get_mut(player) else {
+            return;
+        };
+        pl.life = setup.life;
+        pl.counters = setup.counters.clone();
+        let ids = std::mem::take(&mut pl.library);
+        let mut ids = ids.into_iter();
+        let mut zones = Vec::new();
+        for (zone, cards) in setup.zones() {
+            for card in cards {
+                if let Some(id) = ids.next() {
+                    zones.push((id, zone, card));
+                }
+            }
+        }
+        for (id, zone, card) in zones {
+            let Some(pl) = game.players.get_mut(player) else {
+                return;
+            };
+            match zone {
+                Zone::Hand => _ = pl.hand.insert(id),
+                Zone::Library => pl.library.insert(0, id),
+                Zone::Graveyard => pl.graveyard.push(id),
+                Zone::Battlefield => _ = game.battlefield.insert(id),
+                Zone::Exile => _ = game.exile.insert(id),
+                Zone::Command => _ = game.command.insert(id),
+                Zone::Stack => {}
+            }
+            let everyone: Vec<PlayerId> = game.turn_order.iter().copied().collect();
+            if let Some(ent) = game.cards.get_mut(id) {
+                if zone != Zone::Hand && zone != Zone::Library {
+                    ent.known_to.extend(everyone);
+                } else if zone == Zone::Hand {
+                    ent.known_to.insert(player);
+                }
+                ent.tapped = card.tapped;
+                ent.counters = card.counters.clone();
+                ent.damaged = card.damage;
+                ent.commander = zone == Zone::Command;
+            }
+        }
+        for &mana in &setup.mana {
+            game.add_mana(player, mana).await;
+        }
+    }
+}
+impl PlayerSetup {
+    fn zones(&self) -> Vec<(Zone, &Vec<CardSetup>)> {
+        vec![
+            (Zone::Hand, &self.hand),
+            (Zone::Graveyard, &self.graveyard),
+            (Zone::Battlefield, &self.battlefield),
+            (Zone::Exile, &self.exile),
+            (Zone::Command, &self.command),
+            (Zone::Library, &self.library),
+        ]
+    }
+    fn cards(&self) -> impl Iterator<Item = &CardSetup> {
+        self.zones().into_iter().flat_map(|(_, cards)| cards.iter())
+    }
+}
+fn later_phases(phase: Phase) -> VecDeque<Phase> {
+    let phases = [
+        Phase::Begin,
+        Phase::FirstMain,
+        Phase::Combat,
+        Phase::SecondMain,
+        Phase::Ending,
+    ];
+    let i = phases.iter().position(|&p| p == phase).unwrap_or(0);
+    phases[i + 1..].iter().copied().collect()
+}
+fn subphases(phase: Phase) -> VecDeque<Subphase> {
+    let subphases: &[Subphase] = match phase {
+        Phase::Begin => &[Subphase::Untap, Subphase::Upkeep, Subphase::Draw],
+        Phase::Combat => &[
+            Subphase::BeginCombat,
+            Subphase::Attackers,
+            Subphase::Blockers,
+            Subphase::FirstStrikeDamage,
+            Subphase::Damage,
+            Subphase::EndCombat,
+        ],
+        Phase::Ending => &[Subphase::EndStep, Subphase::Cleanup],
+        Phase::FirstMain | Phase::SecondMain => &[],
+    };
+    subphases.iter().copied().collect()
+}
+fn player_named(game: &Game, name: &str) -> Option<PlayerId> {
+    game.turn_order
+        .iter()
+        .copied()
+        .find(|&id| game.players.get(id).map_or(false, |pl| pl.name == name))
+}
+fn card_name(game: &Game, card: CardId) -> Option<&str> {
+    game.cards.get(card).map(|card| card.name.as_str())
+}
+fn names(game: &Game, cards: impl Iterator<Item = CardId>) -> Vec<String> {
+    let mut names: Vec<String> = cards
+        .filter_map(|card| card_name(game, card).map(|name| name.to_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+impl Expectation {
+    fn check(&self, game: &Game) -> Result<(), String> {
+        let player = |name: &str| {
+            player_named(game, name)
+                .and_then(|id| game.players.get(id).map(|pl| (id, pl)))
+                .ok_or_else(|| format!("{} isn't playing", name))
+        };
+        match self {
+            Expectation::Zone(name, zone, expected) => {
+                let (id, pl) = player(name)?;
+                let controlled = |cards: &HashSetObj<CardId>| -> Vec<CardId> {
+                    cards
+                        .iter()
+                        .copied()
+                        .filter(|&card| game.get_controller(card) == Some(id))
+                        .collect()
+                };
+                let cards = match zone {
+                    Zone::Hand => pl.hand.iter().copied().collect(),
+                    Zone::Library => pl.library.clone(),
+                    Zone::Graveyard => pl.graveyard.clone(),
+                    Zone::Battlefield => controlled(&game.battlefield),
+                    Zone::Exile => controlled(&game.exile),
+                    Zone::Command => controlled(&game.command),
+                    Zone::Stack => game
+                        .stack
+                        .iter()
+                        .copied()
+                        .filter(|&card| game.get_controller(card) == Some(id))
+                        .collect(),
+                };
+                let found = names(game, cards.into_iter());
+                let mut expected = expected.clone();
+                expected.sort();
+                if found != expected {
+                    return Err(format!(
+                        "{}'s {:?} has {:?}, not {:?}",
+                        name, zone, found, expected
+                    ));
+                }
+            }
+            Expectation::Life(name, life) => {
+                let (_, pl) = player(name)?;
+                if pl.life != *life {
+                    return Err(format!("{} is at {} life, not {}", name, pl.life, life));
+                }
+            }
+            Expectation::Tapped(name, expected) => {
+                let (id, _) = player(name)?;
+                let tapped = game
+                    .players_permanents(id)
+                    .filter(|&card| game.cards.get(card).map_or(false, |card| card.tapped));
+                let found = names(game, tapped);
+                let mut expected = expected.clone();
+                expected.sort();
+                if found != expected {
+                    return Err(format!(
+                        "{} has {:?} tapped, not {:?}",
+                        name, found, expected
+                    ));
+                }
+            }
+            Expectation::Counters(card, counter, count) => {
+                let found: usize = permanents_named(game, card)?
+                    .iter()
+                    .map(|ent| ent.counters.iter().filter(|c| *c == counter).count())
+                    .sum();
+                if found != *count {
+                    return Err(format!(
+                        "{} has {} {:?} counters, not {}",
+                        card, found, counter, count
+                    ));
+                }
+            }
+            Expectation::PowerToughness(card, power, toughness) => {
+                for ent in permanents_named(game, card)? {
+                    let pt = ent.pt.as_ref().map(|pt| (pt.power, pt.toughness));
+                    if pt != Some((*power, *toughness)) {
+                        return Err(format!("{} is {:?}, not {}/{}", card, pt, power, toughness));
+                    }
+                }
+            }
+            Expectation::Log(text) => {
+                let log = game.get_log();
+                if !log
+                    .entries
+                    .iter()
+                    .any(|entry| entry.message.text.contains(text.as_str()))
+                {
+                    return Err(format!("nothing logged mentions {:?}", text));
+                }
+            }
+            Expectation::Outcome(expected) => {
+                let found = match game.outcome {
+                    GameOutcome::Ongoing => ExpectedOutcome::Ongoing,
+                    GameOutcome::Tie => ExpectedOutcome::Tie,
+                    GameOutcome::Winner(winner) => ExpectedOutcome::Winner(
+                        game.players
+                            .get(winner)
+                            .map_or(String::new(), |pl| pl.name.clone()),
+                    ),
+                };
+                if found != *expected {
+                    return Err(format!("the game is {:?}, not {:?}", found, expected));
+                }
+            }
+            Expectation::Phase(phase, subphase) => {
+                if game.phase != Some(*phase) || game.subphase != *subphase {
+                    return Err(format!(
+                        "the game is in {:?} {:?}, not {:?} {:?}",
+                        game.phase, game.subphase, phase, subphase
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+fn permanents_named<'a>(game: &'a Game, name: &str) -> Result<Vec<&'a CardEnt>, String> {
+    let found: Vec<_> = game
+        .battlefield
+        .iter()
+        .filter_map(|&card| game.cards.get(card))
+        .filter(|card| card.name == name)
+        .collect();
+    if found.is_empty() {
+        return Err(format!("{} isn't on the battlefield", name));
+    }
+    Ok(found)
+}
+
+//Answers a player's asks from their script. Whatever goes wrong is written
+//down and answered as little as the ask allows, so the game carries on
+struct ScriptedClient {
+    name: String,
+    script: Arc<Mutex<VecDeque<Step>>>,
+    failures: Arc<Mutex<Vec<String>>>,
+    derailed: bool, //An answer was rejected, the rest of the script is off
+}
+impl ScriptedClient {
+    fn fail(&self, failure: String) {
+        self.failures
+            .lock()
+            .unwrap()
+            .push(format!("{}: {}", self.name, failure));
+    }
+    //Takes the next step if answer can make something of it. Some(None)
+    //means the step was taken but couldn't be answered
+    fn next<R>(
+        &mut self,
+        answer: impl FnOnce(&Step) -> Option<Result<R, String>>,
+    ) -> Option<Option<R>> {
+        if self.derailed {
+            return None;
+        }
+        let mut script = self.script.lock().unwrap();
+        let answered = answer(script.front()?)?;
+        script.pop_front();
+        drop(script);
+        match answered {
+            Ok(answer) => Some(Some(answer)),
+            Err(failure) => {
+                self.fail(failure);
+                Some(None)
+            }
+        }
+    }
+    fn pass(&mut self) -> bool {
+        self.next(|step| matches!(step, Step::Pass).then_some(Ok(())))
+            .is_some()
+    }
+    fn selectn<T>(
+        &mut self,
+        kind: &str,
+        ask: &AskSelectN<T>,
+        answer: impl FnOnce(&Step) -> Option<Result<HashSetObj<usize>, String>>,
+    ) -> Answer<HashSetObj<usize>> {
+        let smallest = || (0..ask.min.try_into().unwrap_or(0)).collect();
+        if self.pass() {
+            return Ok(smallest());
+        }
+        match self.next(answer) {
+            Some(Some(selection)) => Ok(selection),
+            Some(None) => Ok(smallest()),
+            None => {
+                if ask.min > 0 && !self.derailed {
+                    self.fail(format!("wasn't expecting to be asked for {}", kind));
+                }
+                Ok(smallest())
+            }
+        }
+    }
+    fn pair<T: Copy + Hash + Eq>(
+        &mut self,
+        kind: &str,
+        ask: &AskPair<T>,
+        answer: impl FnOnce(&Step) -> Option<Result<HashMap<CardId, HashSetObj<T>>, String>>,
+    ) -> Answer<HashMap<CardId, HashSetObj<T>>> {
+        let smallest = || {
+            ask.pairs
+                .iter()
+                .map(|(&card, item)| (card, item.items.iter().copied().take(item.min).collect()))
+                .collect()
+        };
+        if self.pass() {
+            return Ok(smallest());
+        }
+        match self.next(answer) {
+            Some(Some(pairing)) => Ok(pairing),
+            Some(None) => Ok(smallest()),
+            None => {
+                if ask.pairs.values().any(|item| item.min > 0) && !self.derailed {
+                    self.fail(format!("wasn't expecting to be asked for {}", kind));
+                }
+                Ok(smallest())
+            }
+        }
+    }
+}
+//Picks a different choice for each name, in the order they're given
+fn pick<T>(
+    choices: &[T],
+    wanted: &[Target],
+    matches: impl Fn(&T, &Target) -> bool,
+) -> Result<HashSetObj<usize>, String> {
+    let mut picked = HashSetObj::new();
+    for target in wanted {
+        let found = (0..choices.len())
+            .find(|&i| !picked.contains(&i) && matches(&choices[i], target))
+            .ok_or_else(|| format!("couldn't choose {:?}", target))?;
+        picked.insert(found);
+    }
+    Ok(picked)
+}
+fn is_target(game: &Game, id: TargetId, target: &Target) -> bool {
+    match (id, target) {
+        (TargetId::Card(card), Target::Card(name)) => card_name(game, card) == Some(name),
+        (TargetId::Player(player), Target::Player(name)) => game
+            .players
+            .get(player)
+            .map_or(false, |pl| &pl.name == name),
+        _ => false,
+    }
+}
+fn cards_named(
+    game: &Game,
+    ents: &[CardId],
+    names: &[String],
+) -> Result<HashSetObj<usize>, String> {
+    let wanted: Vec<Target> = names.iter().cloned().map(Target::Card).collect();
+    pick(ents, &wanted, |&card, target| {
+        is_target(game, TargetId::Card(card), target)
+    })
+}
+//Pairs each named card with one of the choices it's allowed
+fn pairing<T: Copy + Hash + Eq>(
+    game: &Game,
+    ask: &AskPair<T>,
+    wanted: &[(String, Target)],
+    matches: impl Fn(T, &Target) -> bool,
+) -> Result<HashMap<CardId, HashSetObj<T>>, String> {
+    let mut cards: Vec<&CardId> = ask.pairs.keys().collect();
+    cards.sort();
+    let mut pairing: HashMap<CardId, HashSetObj<T>> = HashMap::new();
+    for (name, target) in wanted {
+        let found = cards.iter().find_map(|&&card| {
+            if pairing.contains_key(&card) || card_name(game, card) != Some(name) {
+                return None;
+            }
+            let item = ask.pairs[&card]
+                .items
+                .iter()
+                .find(|&&item| matches(item, target))?;
+            Some((card, *item))
+        });
+        let Some((card, item)) = found else {
+            return Err(format!("{} couldn't be paired with {:?}", name, target));
+        };
+        pairing.insert(card, [item].into_iter().collect());
+    }
+    Ok(pairing)
+}
+#[async_trait]
+impl DecisionProvider for ScriptedClient {
+    fn wants_states(&self) -> bool {
+        false
+    }
+    async fn select_action(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        let game = ctx.game;
+        let find = |wanted: &str, name: &String, matches: fn(&Action) -> Option<CardId>| {
+            let found = ask.ents.iter().position(|action| {
+                matches(action).map_or(false, |card| card_name(game, card) == Some(name))
+            });
+            match found {
+                Some(i) => Ok([i].into_iter().collect()),
+                None => Err(format!("couldn't {} {}", wanted, name)),
+            }
+        };
+        self.selectn("an action", ask, |step| match step {
+            Step::Cast(name) => Some(find("cast", name, |action| match action {
+                Action::Cast(option) => Some(option.source_card),
+                _ => None,
+            })),
+            Step::PlayLand(name) => Some(find("play", name, |action| match action {
+                Action::PlayLand(card) => Some(*card),
+                _ => None,
+            })),
+            Step::Activate(name) => Some(find("activate", name, |action| match action {
+                Action::ActivateAbility { source, .. } => Some(*source),
+                _ => None,
+            })),
+            _ => None,
+        })
+    }
+    async fn select_targets(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.selectn("targets", ask, |step| match step {
+            Step::Targets(targets) => Some(pick(&ask.ents, targets, |&id, target| {
+                is_target(ctx.game, id, target)
+            })),
+            _ => None,
+        })
+    }
+    async fn select_attackers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        self.pair("attackers", ask, |step| match step {
+            Step::Attack(attacks) => Some(pairing(ctx.game, ask, attacks, |id, target| {
+                is_target(ctx.game, id, target)
+            })),
+            _ => None,
+        })
+    }
+    async fn select_blockers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        self.pair("blockers", ask, |step| match step {
+            Step::Block(blocks) => {
+                let blocks: Vec<(String, Target)> = blocks
+                    .iter()
+                    .map(|(blocker, attacker)| (blocker.clone(), Target::Card(attacker.clone())))
+                    .collect();
+                Some(pairing(ctx.game, ask, &blocks, |card, target| {
+                    is_target(ctx.game, TargetId::Card(card), target)
+                }))
+            }
+            _ => None,
+        })
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.selectn("discards", ask, |step| match step {
+            Step::Discard(cards) => Some(cards_named(ctx.game, &ask.ents, cards)),
+            _ => None,
+        })
+    }
+    async fn command_zone(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.selectn("the command zone", ask, |step| match step {
+            Step::CommandZone(cards) => Some(cards_named(ctx.game, &ask.ents, cards)),
+            _ => None,
+        })
+    }
+    async fn rejected(&mut self, _id: RequestId, reason: &str) {
+        if !self.derailed {
+            self.fail(format!("an answer was rejected: {}", reason));
+        }
+        self.derailed = true;
+    }
+}
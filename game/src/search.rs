@@ -0,0 +1,412 @@
+use crate::ai::{cast, castable, AiClient};
+use crate::client_message::{AskPair, AskSelectN, GameState};
+use crate::decision::{Answer, AskContext, DecisionProvider};
+use crate::game::serialize_game::Viewer;
+use crate::game::{Game, GameOutcome, Phase};
+use crate::player::PlayerCon;
+use async_trait::async_trait;
+use common::actions::Action;
+use common::entities::{CardId, PlayerId, TargetId};
+use common::hashset_obj::HashSetObj;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+//How far past the decision each playout goes before the position is scored
+pub const PLAYOUT_TURNS: u32 = 2;
+
+//A computer player that searches. Whenever it could play a land or cast a
+//spell it tries each option, along with passing, on copies of the game with
+//the cards it can't see dealt out at random. Each copy is played on a few
+//turns with the heuristic AI making every choice, and the option that did
+//best is taken once the time budget runs out. Anything else is left to the
+//heuristic AI
+pub struct SearchClient {
+    budget: Duration,
+    turns: u32,
+    rng: ChaCha12Rng,
+    plan: Option<Plan>, //A spell it's still tapping mana for
+}
+impl SearchClient {
+    pub fn new(budget: Duration, seed: u64) -> Self {
+        SearchClient {
+            budget,
+            turns: PLAYOUT_TURNS,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            plan: None,
+        }
+    }
+    pub fn with_turns(mut self, turns: u32) -> Self {
+        self.turns = turns;
+        self
+    }
+    pub(crate) async fn search(
+        &mut self,
+        game: &Game,
+        me: PlayerId,
+        ask: &AskSelectN<Action>,
+    ) -> Option<HashSetObj<usize>> {
+        let state = game.state_for(Viewer::Player(me));
+        if let Some(plan) = self.plan.take()
+        && let Some((choice, done)) = step(&state, me, ask, plan) {
+            if !done {
+                self.plan = Some(plan);
+            }
+            return Some(choice);
+        }
+        let plans = plans(&state, me, ask);
+        if plans.len() < 2 {
+            return None;
+        }
+        //The playouts are CPU bound, so they're run on a blocking thread
+        //rather than holding up the worker other games are running on
+        let copy = game.clone();
+        let (budget, turns, mut rng) = (self.budget, self.turns, self.rng.clone());
+        let runtime = tokio::runtime::Handle::current();
+        let searched = tokio::task::spawn_blocking(move || {
+            let best = runtime.block_on(best_plan(&copy, me, &plans, budget, turns, &mut rng));
+            (best, rng)
+        })
+        .await;
+        let (best, rng) = match searched {
+            Ok(searched) => searched,
+            Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+            Err(_) => return None,
+        };
+        self.rng = rng;
+        let best = best?;
+        let (choice, done) = step(&state, me, ask, best)?;
+        if !done {
+            self.plan = Some(best);
+        }
+        Some(choice)
+    }
+}
+//Playouts are handed out like a bandit at the root of a search tree,
+//the options that score well get tried more often
+async fn best_plan(
+    game: &Game,
+    me: PlayerId,
+    plans: &[Plan],
+    budget: Duration,
+    turns: u32,
+    rng: &mut ChaCha12Rng,
+) -> Option<Plan> {
+    let deadline = Instant::now() + budget;
+    let mut stats = vec![Stats::default(); plans.len()];
+    let mut playouts = 0;
+    while playouts < plans.len() || Instant::now() < deadline {
+        let i = next_to_try(&stats, playouts);
+        let score = playout(game, me, plans[i], turns, rng).await;
+        stats[i].total += score;
+        stats[i].visits += 1;
+        playouts += 1;
+    }
+    let best = (0..plans.len()).max_by(|&a, &b| {
+        (stats[a].visits.cmp(&stats[b].visits)).then(stats[a].mean().total_cmp(&stats[b].mean()))
+    })?;
+    Some(plans[best])
+}
+async fn playout(game: &Game, me: PlayerId, plan: Plan, turns: u32, rng: &mut ChaCha12Rng) -> f64 {
+    let mut simulation = game.simulation(rng.gen(), |player| {
+        PlayerCon::new(PlayoutClient {
+            plan: (player == me).then_some(plan),
+        })
+    });
+    determinize(&mut simulation, me, rng);
+    simulation.play_out(turns).await;
+    score(&simulation, me)
+}
+//It looks at the game when it's asked, states would go unused
+#[async_trait]
+impl DecisionProvider for SearchClient {
+    fn wants_states(&self) -> bool {
+        false
+    }
+    async fn select_action(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        match self.search(ctx.game, ctx.player, ask).await {
+            Some(choice) => Ok(choice),
+            None => heuristic(ctx).select_action(ctx, ask).await,
+        }
+    }
+    async fn select_targets(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        heuristic(ctx).select_targets(ctx, ask).await
+    }
+    async fn select_attackers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        heuristic(ctx).select_attackers(ctx, ask).await
+    }
+    async fn select_blockers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        heuristic(ctx).select_blockers(ctx, ask).await
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        heuristic(ctx).discard_to_hand_size(ctx, ask).await
+    }
+    async fn command_zone(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        heuristic(ctx).command_zone(ctx, ask).await
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Stats {
+    total: f64,
+    visits: u32,
+}
+impl Stats {
+    fn mean(&self) -> f64 {
+        self.total / self.visits.max(1) as f64
+    }
+}
+//Every option is tried once, after that UCB1 balances the best
+//looking options against the ones that haven't been tried much
+fn next_to_try(stats: &[Stats], playouts: usize) -> usize {
+    if let Some(untried) = stats.iter().position(|stats| stats.visits == 0) {
+        return untried;
+    }
+    let explore = |stats: &Stats| (2.0 * (playouts as f64).ln() / stats.visits as f64).sqrt();
+    (0..stats.len())
+        .max_by(|&a, &b| {
+            let a = stats[a].mean() + explore(&stats[a]);
+            let b = stats[b].mean() + explore(&stats[b]);
+            a.total_cmp(&b)
+        })
+        .unwrap_or(0)
+}
+
+//What the searching player does with its priority in a playout, after
+//which it plays like the heuristic AI
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Plan {
+    Pass,
+    PlayLand(CardId),
+    Cast(CardId),
+}
+//Cards with the same name play the same, so only one of them is tried
+pub(crate) fn plans(game: &GameState, me: PlayerId, ask: &AskSelectN<Action>) -> Vec<Plan> {
+    let name = |card: &CardId| game.cards.get(card).map(|card| card.name.clone());
+    let mut seen = HashSet::new();
+    let mut plans = vec![Plan::Pass];
+    let castable = castable(game, me, ask);
+    for (i, action) in ask.ents.iter().enumerate() {
+        let (plan, card) = match action {
+            Action::PlayLand(card) => (Plan::PlayLand(*card), card),
+            Action::Cast(option) if castable.contains(&i) => {
+                (Plan::Cast(option.source_card), &option.source_card)
+            }
+            _ => continue,
+        };
+        if seen.insert((matches!(plan, Plan::Cast(_)), name(card))) {
+            plans.push(plan);
+        }
+    }
+    plans
+}
+//The answer that carries the plan out and whether that's the end of it,
+//casting a spell can take a few answers to tap mana for it first
+fn step(
+    game: &GameState,
+    me: PlayerId,
+    ask: &AskSelectN<Action>,
+    plan: Plan,
+) -> Option<(HashSetObj<usize>, bool)> {
+    match plan {
+        Plan::Pass => Some((HashSetObj::new(), true)),
+        Plan::PlayLand(card) => {
+            let land = ask
+                .ents
+                .iter()
+                .position(|action| matches!(action, Action::PlayLand(land) if *land == card))?;
+            Some(([land].into_iter().collect(), true))
+        }
+        Plan::Cast(card) => {
+            let spell = ask.ents.iter().position(
+                |action| matches!(action, Action::Cast(option) if option.source_card == card),
+            )?;
+            let choice = cast(game, me, ask, spell);
+            let done = choice.contains(&spell);
+            Some((choice, done))
+        }
+    }
+}
+
+//Plays a simulated game. The searching player's copy starts off by following
+//its plan, everyone else plays like the heuristic AI from the start
+struct PlayoutClient {
+    plan: Option<Plan>,
+}
+#[async_trait]
+impl DecisionProvider for PlayoutClient {
+    fn wants_states(&self) -> bool {
+        false
+    }
+    async fn select_action(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        let game = ctx.game;
+        if let Some(plan) = self.plan.take() {
+            if let Some((choice, done)) = step(&ctx.state(), ctx.player, ask, plan) {
+                if !done {
+                    self.plan = Some(plan);
+                }
+                return Ok(choice);
+            }
+        }
+        //The heuristic AI only acts in its own main phases, so there's
+        //no need to build it a state to pass priority everywhere else
+        let main_phase = matches!(game.phase, Some(Phase::FirstMain | Phase::SecondMain));
+        if game.active_player != ctx.player || !game.stack.is_empty() || !main_phase {
+            return Ok(HashSetObj::new());
+        }
+        heuristic(ctx).select_action(ctx, ask).await
+    }
+    async fn select_targets(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        heuristic(ctx).select_targets(ctx, ask).await
+    }
+    async fn select_attackers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        heuristic(ctx).select_attackers(ctx, ask).await
+    }
+    async fn select_blockers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        heuristic(ctx).select_blockers(ctx, ask).await
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        heuristic(ctx).discard_to_hand_size(ctx, ask).await
+    }
+    async fn command_zone(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        heuristic(ctx).command_zone(ctx, ask).await
+    }
+}
+//Anything the search leaves alone is answered like the heuristic AI would
+fn heuristic(ctx: AskContext<'_>) -> AiClient {
+    AiClient::seeing(ctx.state())
+}
+
+//Deals the cards the player can't see out again at random. Each player's
+//unseen cards are shuffled between their hand and library, so the search
+//can't make use of anything hidden from it. Whoever could see a hand or
+//library slot before can still see whatever card was dealt into it
+pub(crate) fn determinize(game: &mut Game, me: PlayerId, rng: &mut impl Rng) {
+    for player in game.turn_order.clone() {
+        let unseen = |card: &CardId| {
+            game.cards
+                .get(*card)
+                .map_or(false, |card| !card.known_to.contains(&me))
+        };
+        let Some(pl) = game.players.get(player) else {
+            continue;
+        };
+        let hand: Vec<CardId> = pl.hand.iter().copied().filter(unseen).collect();
+        let library: Vec<(usize, CardId)> = pl
+            .library
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|(_, card)| unseen(card))
+            .collect();
+        let slots: Vec<CardId> = hand
+            .iter()
+            .copied()
+            .chain(library.iter().map(|(_, card)| *card))
+            .collect();
+        let known_to: Vec<_> = slots
+            .iter()
+            .map(|card| game.cards.get(*card).map(|card| card.known_to.clone()))
+            .collect();
+        let mut dealt = slots.clone();
+        dealt.shuffle(rng);
+        for (card, known_to) in dealt.iter().zip(known_to) {
+            if let (Some(card), Some(known_to)) = (game.cards.get_mut(*card), known_to) {
+                card.known_to = known_to;
+            }
+        }
+        let Some(pl) = game.players.get_mut(player) else {
+            continue;
+        };
+        for card in &hand {
+            pl.hand.remove(card);
+        }
+        for card in &dealt[..hand.len()] {
+            pl.hand.insert(*card);
+        }
+        for ((i, _), card) in library.iter().zip(&dealt[hand.len()..]) {
+            pl.library[*i] = *card;
+        }
+    }
+}
+
+//1 for a win and 0 for a loss. Games still going are scored by
+//how the player's life and creatures compare to the opponents'
+fn score(game: &Game, me: PlayerId) -> f64 {
+    match game.outcome {
+        GameOutcome::Winner(winner) if winner == me => 1.0,
+        GameOutcome::Winner(_) => 0.0,
+        GameOutcome::Tie => 0.5,
+        GameOutcome::Ongoing => {
+            let strength = |player: PlayerId| -> i64 {
+                let life = game.players.get(player).map_or(0, |pl| pl.life.max(0));
+                let creatures: i64 = game
+                    .battlefield
+                    .iter()
+                    .filter_map(|card| game.cards.get(*card))
+                    .filter(|card| card.get_controller() == player)
+                    .filter_map(|card| card.pt.as_ref())
+                    .map(|pt| (pt.power + pt.toughness).max(0))
+                    .sum();
+                life + creatures
+            };
+            let mine = strength(me);
+            let theirs: i64 = game.opponents(me).into_iter().map(strength).sum();
+            if mine + theirs == 0 {
+                0.5
+            } else {
+                mine as f64 / (mine + theirs) as f64
+            }
+        }
+    }
+}
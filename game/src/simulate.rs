@@ -0,0 +1,344 @@
+use crate::client_message::{AskPair, AskSelectN, RequestId};
+use crate::decision::{Answer, AskContext, DecisionProvider};
+use crate::game::build_game::GameBuilder;
+use crate::game::GameOutcome;
+use crate::log::LogEntry;
+use crate::player::PlayerCon;
+use crate::record::{win_rates, GameRecord};
+use crate::search::SearchClient;
+use anyhow::Result;
+use async_trait::async_trait;
+use carddb::carddb::CardDB;
+use carddb::decklist::ResolvedDeck;
+use common::actions::Action;
+use common::entities::{CardId, TargetId};
+use common::format::Format;
+use common::hashset_obj::HashSetObj;
+use common::panic::panic_message;
+use futures::StreamExt;
+use rand::seq::index::sample;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_MAX_TURNS: u32 = 60;
+const SEARCH_BUDGET: Duration = Duration::from_millis(50);
+
+//How a simulated player makes its choices
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Policy {
+    Random,
+    Heuristic,
+    Search, //Searches on a time budget, so its games can't be reproduced exactly
+}
+impl Policy {
+    pub fn connect(self, seed: u64) -> PlayerCon {
+        match self {
+            Policy::Random => PlayerCon::new(RandomClient::new(seed)),
+            Policy::Heuristic => PlayerCon::new_ai(),
+            Policy::Search => PlayerCon::new(SearchClient::new(SEARCH_BUDGET, seed)),
+        }
+    }
+}
+impl FromStr for Policy {
+    type Err = anyhow::Error;
+    fn from_str(policy: &str) -> Result<Self> {
+        match policy {
+            "random" => Ok(Policy::Random),
+            "heuristic" | "ai" => Ok(Policy::Heuristic),
+            "search" => Ok(Policy::Search),
+            _ => anyhow::bail!("{} isn't a policy, try random, heuristic or search", policy),
+        }
+    }
+}
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Policy::Random => "random",
+            Policy::Heuristic => "heuristic",
+            Policy::Search => "search",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+//Picks among the legal answers at random, each allowed number of choices
+//being as likely as any other. Random answers are only wrong when the ask
+//can't be answered, so a rejection panics and the simulation reports it
+pub struct RandomClient {
+    rng: ChaCha12Rng,
+}
+impl RandomClient {
+    pub fn new(seed: u64) -> Self {
+        RandomClient {
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+    fn pick(&mut self, choices: usize, min: usize, max: usize) -> HashSetObj<usize> {
+        let max = max.min(choices);
+        if min > max {
+            return (0..min).collect();
+        }
+        let amount = self.rng.gen_range(min..=max);
+        sample(&mut self.rng, choices, amount).into_iter().collect()
+    }
+    fn selectn<T>(&mut self, ask: &AskSelectN<T>) -> Answer<HashSetObj<usize>> {
+        let min = ask.min.try_into().unwrap_or(0);
+        let max = ask.max.try_into().unwrap_or(usize::MAX);
+        Ok(self.pick(ask.ents.len(), min, max))
+    }
+    fn pair<T: Copy + Hash + Eq>(
+        &mut self,
+        ask: &AskPair<T>,
+    ) -> Answer<HashMap<CardId, HashSetObj<T>>> {
+        //Sorted so the same seed always pairs the same way
+        let mut cards: Vec<&CardId> = ask.pairs.keys().collect();
+        cards.sort();
+        let mut pairing = HashMap::new();
+        for card in cards {
+            let item = &ask.pairs[card];
+            let items: Vec<T> = item.items.iter().copied().collect();
+            let picked = self.pick(items.len(), item.min, item.max);
+            pairing.insert(*card, picked.iter().map(|&i| items[i]).collect());
+        }
+        Ok(pairing)
+    }
+}
+#[async_trait]
+impl DecisionProvider for RandomClient {
+    fn wants_states(&self) -> bool {
+        false
+    }
+    async fn select_action(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.selectn(ask)
+    }
+    async fn select_targets(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.selectn(ask)
+    }
+    async fn select_attackers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        self.pair(ask)
+    }
+    async fn select_blockers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        self.pair(ask)
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.selectn(ask)
+    }
+    async fn command_zone(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.selectn(ask)
+    }
+    //Otherwise the same answer would be given again forever
+    async fn rejected(&mut self, id: RequestId, reason: &str) {
+        panic!("random answer to {} was rejected: {}", id, reason);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Contestant {
+    pub name: String, //Has to be different for each contestant
+    pub deck: ResolvedDeck,
+    pub policy: Policy,
+}
+//Plays the contestants against each other games times with no one connected.
+//Game i is played with seed seed + i, wrapping past u64::MAX, and the seed also
+//decides who goes first, so any one game can be played again by simulating just its seed
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    pub contestants: Vec<Contestant>,
+    pub format: Format,
+    pub games: u32,
+    pub seed: u64,
+    pub max_turns: u32, //Games still going at this turn are given up on
+    pub parallel: usize,
+}
+impl Simulation {
+    pub fn new(contestants: Vec<Contestant>, games: u32) -> Self {
+        Simulation {
+            contestants,
+            format: Format::Casual,
+            games,
+            seed: 0,
+            max_turns: DEFAULT_MAX_TURNS,
+            parallel: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }
+    }
+    //Needs a multithreaded runtime for the games to actually run side by side
+    pub async fn run(self, db: &'static CardDB) -> SimulationReport {
+        let simulation = Arc::new(self);
+        let seeds = (0..simulation.games).map(|i| simulation.seed.wrapping_add(i as u64));
+        let played: Vec<(u64, Result<Played>)> = futures::stream::iter(seeds)
+            .map(|seed| {
+                let simulation = simulation.clone();
+                async move {
+                    let game = tokio::task::spawn(async move { simulation.play(db, seed).await });
+                    let played = match game.await {
+                        Ok(played) => played,
+                        Err(err) if err.is_panic() => {
+                            let message = panic_message(err.into_panic())
+                                .unwrap_or_else(|| "the game panicked".to_owned());
+                            Err(anyhow::Error::msg(message))
+                        }
+                        Err(err) => Err(err.into()),
+                    };
+                    (seed, played)
+                }
+            })
+            .buffer_unordered(simulation.parallel.max(1))
+            .collect()
+            .await;
+        SimulationReport::new(&simulation.contestants, played)
+    }
+    async fn play(&self, db: &'static CardDB, seed: u64) -> Result<Played> {
+        let mut gamebuild = GameBuilder::new();
+        gamebuild.set_format(self.format);
+        gamebuild.set_seed(seed);
+        let first = (seed % self.contestants.len() as u64) as usize;
+        for i in 0..self.contestants.len() {
+            let contestant = &self.contestants[(first + i) % self.contestants.len()];
+            let con = contestant.policy.connect(seed ^ ((i as u64) << 32));
+            let cards = &contestant.deck.cards;
+            let player = gamebuild.add_player(&contestant.name, db, cards, con)?;
+            if let Some(commander) = contestant.deck.commander {
+                gamebuild.set_commander(player, db, commander)?;
+            }
+        }
+        let mut game = gamebuild.build(db)?;
+        let started = Instant::now();
+        game.run_until(self.max_turns).await;
+        let mut casts: Vec<String> = Vec::new();
+        for entry in &game.get_log().entries {
+            if let LogEntry::Cast(cast, _) = &entry.message.entry
+            && let Some(name) = &cast.stack_ent.name {
+                casts.push(name.clone());
+            }
+        }
+        Ok(Played {
+            record: game.record(started.elapsed()),
+            casts,
+        })
+    }
+}
+struct Played {
+    record: GameRecord,
+    casts: Vec<String>, //The name of every card cast, once per cast
+}
+
+#[derive(Clone, Debug)]
+pub struct Crash {
+    pub seed: u64,
+    pub message: String,
+}
+#[derive(Clone, Debug, PartialEq)]
+pub struct CardStats {
+    pub card: String,
+    pub games: u32, //Decks that played the card
+    pub wins: u32,
+    pub casts: u32,
+}
+#[derive(Clone, Debug)]
+pub struct SimulationReport {
+    pub games: u32,
+    pub wins: Vec<(String, u32)>, //For each contestant, in the order they were given
+    pub ties: u32,
+    pub unfinished: u32,       //Given up on at the turn limit
+    pub turns: Vec<u32>,       //How long each game that was played went on for
+    pub cards: Vec<CardStats>, //Sorted by win rate, like win_rates
+    pub crashes: Vec<Crash>,
+}
+impl SimulationReport {
+    fn new(contestants: &[Contestant], played: Vec<(u64, Result<Played>)>) -> Self {
+        let mut report = SimulationReport {
+            games: played.len() as u32,
+            wins: contestants.iter().map(|c| (c.name.clone(), 0)).collect(),
+            ties: 0,
+            unfinished: 0,
+            turns: Vec::new(),
+            cards: Vec::new(),
+            crashes: Vec::new(),
+        };
+        let mut records = Vec::new();
+        let mut casts: HashMap<String, u32> = HashMap::new();
+        for (seed, played) in played {
+            let played = match played {
+                Ok(played) => played,
+                Err(err) => {
+                    report.crashes.push(Crash {
+                        seed,
+                        message: err.to_string(),
+                    });
+                    continue;
+                }
+            };
+            let record = played.record;
+            match record.outcome {
+                GameOutcome::Winner(winner) => {
+                    let name = record.players.iter().find(|p| p.player == winner);
+                    if let Some(name) = name.map(|p| &p.name)
+                    && let Some((_, wins)) = report.wins.iter_mut().find(|(n, _)| n == name) {
+                        *wins += 1;
+                    }
+                }
+                GameOutcome::Tie => report.ties += 1,
+                GameOutcome::Ongoing => report.unfinished += 1,
+            }
+            report.turns.push(record.turns);
+            for card in played.casts {
+                *casts.entry(card).or_default() += 1;
+            }
+            records.push(record);
+        }
+        report.crashes.sort_by_key(|crash| crash.seed);
+        report.cards = win_rates(&records)
+            .into_iter()
+            .map(|rate| CardStats {
+                casts: casts.get(&rate.card).copied().unwrap_or(0),
+                card: rate.card,
+                games: rate.games,
+                wins: rate.wins,
+            })
+            .collect();
+        report
+    }
+    pub fn average_turns(&self) -> f64 {
+        let total: u32 = self.turns.iter().sum();
+        total as f64 / self.turns.len().max(1) as f64
+    }
+    //Wins over the games that were played to the end or the turn limit
+    pub fn win_rate(&self, contestant: &str) -> f64 {
+        let wins = self
+            .wins
+            .iter()
+            .find(|(name, _)| name == contestant)
+            .map_or(0, |(_, wins)| *wins);
+        wins as f64 / self.turns.len().max(1) as f64
+    }
+}
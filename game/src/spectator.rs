@@ -0,0 +1,89 @@
+use crate::state_diff::StateStream;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::time::Instant;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpectatorView {
+    //Only what every player can see, hands and libraries are hidden
+    Public,
+    //Every card, for judges and streamers. The delay keeps it from being used to
+    //help a player, states are held back that long before being sent
+    Omniscient { delay: Duration },
+}
+//Spectators only ever get sent states and the log, nothing they send is read,
+//so they can't affect the game
+#[derive(Clone)]
+pub struct Spectator {
+    pub view: SpectatorView,
+    states: StateStream,
+    sender: UnboundedSender<(Instant, Value)>,
+}
+impl Spectator {
+    //The receiving end gets each state once its delay is up, it's up to
+    //the server to send them on to wherever the spectator is watching
+    pub fn channel(view: SpectatorView) -> (Self, UnboundedReceiver<Value>) {
+        let (sender, mut queued) = unbounded_channel::<(Instant, Value)>();
+        let (delayed, states) = unbounded_channel();
+        let delay = match view {
+            SpectatorView::Public => Duration::ZERO,
+            SpectatorView::Omniscient { delay } => delay,
+        };
+        tokio::task::spawn(async move {
+            while let Some((sent, state)) = queued.recv().await {
+                tokio::time::sleep_until(sent + delay).await;
+                if delayed.send(state).is_err() {
+                    break;
+                }
+            }
+        });
+        let spectator = Spectator {
+            view,
+            states: StateStream::default(),
+            sender,
+        };
+        (spectator, states)
+    }
+    //Returns false once the spectator has gone away
+    fn send(&mut self, state: Value) -> bool {
+        let message = self.states.update(state);
+        self.send_message(message)
+    }
+    fn send_message(&self, message: Value) -> bool {
+        self.sender.send((Instant::now(), message)).is_ok()
+    }
+}
+
+//Shared with the server so spectators can join a game that is already running
+#[derive(Clone, Default)]
+pub struct Spectators(Arc<Mutex<Vec<Spectator>>>);
+impl Spectators {
+    pub fn add(&self, spectator: Spectator) {
+        self.0.lock().unwrap().push(spectator);
+    }
+    pub fn views(&self) -> Vec<SpectatorView> {
+        let mut views: Vec<SpectatorView> = Vec::new();
+        for spectator in self.0.lock().unwrap().iter() {
+            if !views.contains(&spectator.view) {
+                views.push(spectator.view);
+            }
+        }
+        views
+    }
+    //Each spectator is sent a diff against the last state they got
+    pub fn send(&self, view: SpectatorView, state: &Value) {
+        self.0
+            .lock()
+            .unwrap()
+            .retain_mut(|spectator| spectator.view != view || spectator.send(state.clone()));
+    }
+    //Messages other than states are sent as they are, after the same delay
+    pub fn send_message(&self, view: SpectatorView, message: Value) {
+        self.0
+            .lock()
+            .unwrap()
+            .retain(|spectator| spectator.view != view || spectator.send_message(message.clone()));
+    }
+}
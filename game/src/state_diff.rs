@@ -0,0 +1,135 @@
+use crate::player::PlayerView;
+use anyhow::{bail, Result};
+use common::card_entities::CardEnt;
+use common::entities::{CardId, PlayerId};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+//Fields of a GameState that aren't diffed as part of the game
+const CARDS: &str = "cards";
+const PLAYERS: &str = "players";
+const VERSION: &str = "version";
+
+//The changes between two versions of a GameState. Cards and players are
+//sent whole when anything about them changed, the game's own fields
+//(zones, phases, priority...) are sent when their value changed
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct StateDiff {
+    pub version: u64, //Applies on top of version - 1
+    pub cards: HashMap<CardId, CardEnt>,
+    pub removed_cards: Vec<CardId>,
+    pub players: HashMap<PlayerId, PlayerView>,
+    pub game: Map<String, Value>,
+}
+fn changed(old: Option<&Value>, new: &Map<String, Value>) -> Map<String, Value> {
+    let old = old.and_then(Value::as_object);
+    new.iter()
+        .filter(|(key, value)| old.and_then(|old| old.get(*key)) != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+//Both states are serialized GameStates, the diff is returned
+//serialized the same way as a StateDiff would be
+pub fn diff_states(old: &Value, new: &Value) -> Value {
+    let empty = Map::new();
+    let new_map = new.as_object().unwrap_or(&empty);
+    let new_cards = new_map
+        .get(CARDS)
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let removed_cards: Vec<Value> = old
+        .get(CARDS)
+        .and_then(Value::as_object)
+        .unwrap_or(&empty)
+        .keys()
+        .filter(|id| !new_cards.contains_key(*id))
+        .filter_map(|id| id.parse::<u64>().ok())
+        .map(Value::from)
+        .collect();
+    let players = new_map
+        .get(PLAYERS)
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let game: Map<String, Value> = new_map
+        .iter()
+        .filter(|(key, _)| ![CARDS, PLAYERS, VERSION].contains(&key.as_str()))
+        .filter(|(key, value)| old.get(key.as_str()) != Some(*value))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    json!({
+        "version": new.get(VERSION).cloned().unwrap_or(Value::Null),
+        "cards": changed(old.get(CARDS), new_cards),
+        "removed_cards": removed_cards,
+        "players": changed(old.get(PLAYERS), players),
+        "game": game,
+    })
+}
+//Ids are map keys once serialized
+fn key<T: Serialize>(id: &T) -> Result<String> {
+    Ok(serde_json::to_value(id)?.to_string())
+}
+impl StateDiff {
+    //Patches a serialized GameState, failing if a version was skipped
+    pub fn apply(&self, state: &mut Value) -> Result<()> {
+        let version = state.get(VERSION).and_then(Value::as_u64).unwrap_or(0);
+        if self.version != version + 1 {
+            bail!("missed state versions {} to {}", version + 1, self.version);
+        }
+        let Some(state) = state.as_object_mut() else {
+            bail!("states are objects");
+        };
+        let cards = state
+            .entry(CARDS)
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("cards are an object");
+        for id in &self.removed_cards {
+            cards.remove(&key(id)?);
+        }
+        for (id, card) in &self.cards {
+            cards.insert(key(id)?, serde_json::to_value(card)?);
+        }
+        let players = state
+            .entry(PLAYERS)
+            .or_insert_with(|| json!({}))
+            .as_object_mut()
+            .expect("players are an object");
+        for (id, player) in &self.players {
+            players.insert(key(id)?, serde_json::to_value(player)?);
+        }
+        for (key, value) in &self.game {
+            state.insert(key.clone(), value.clone());
+        }
+        state.insert(VERSION.to_owned(), self.version.into());
+        Ok(())
+    }
+}
+
+//The states sent down one connection. The first state and any resync
+//are sent whole, everything else as a diff against the last state sent
+#[derive(Clone, Debug, Default)]
+pub struct StateStream {
+    latest: Option<Value>,
+    version: u64,
+}
+impl StateStream {
+    //Takes a serialized GameState and returns the message to send for it
+    pub fn update(&mut self, mut state: Value) -> Value {
+        self.version += 1;
+        state[VERSION] = self.version.into();
+        let message = match &self.latest {
+            Some(old) => json!({ "StateDiff": diff_states(old, &state) }),
+            None => json!({ "GameState": state.clone() }),
+        };
+        self.latest = Some(state);
+        message
+    }
+    //The whole latest state, for clients that missed a diff or reconnected
+    pub fn snapshot(&self) -> Option<Value> {
+        self.latest
+            .as_ref()
+            .map(|state| json!({ "GameState": state.clone() }))
+    }
+}
@@ -0,0 +1,206 @@
+use crate::{
+    ai::{choose_action, choose_attacks, choose_blocks, choose_targets, AiClient},
+    client_message::{AskPair, AskPairItem, AskSelectN},
+    decision::{AskContext, DecisionProvider, Gone},
+    game::{build_game::GameBuilder, serialize_game::Viewer, Game, GameOutcome, Phase},
+    player::PlayerCon,
+    tests::common_test::{cards_with_name, get_db, hand_battlefield_setup},
+};
+use anyhow::Result;
+use common::{
+    actions::Action,
+    entities::{CardId, PlayerId, TargetId},
+    hashset_obj::HashSetObj,
+    mana::ManaCostSymbol,
+};
+use std::time::Duration;
+
+//Hands the first card with the name to the opponent
+fn give_opponent(game: &mut Game, name: &str) -> CardId {
+    let opponent = game.opponents(game.active_player)[0];
+    let card = cards_with_name(game, name)[0];
+    game.cards
+        .get_mut(card)
+        .unwrap()
+        .set_controller(Some(opponent));
+    card
+}
+fn pair<T: Copy + std::hash::Hash + Eq>(pairs: Vec<(CardId, Vec<T>)>) -> AskPair<T> {
+    AskPair {
+        pairs: pairs
+            .into_iter()
+            .map(|(card, items)| {
+                let item = AskPairItem {
+                    items: items.into_iter().collect(),
+                    min: 0,
+                    max: 1,
+                };
+                (card, item)
+            })
+            .collect(),
+    }
+}
+fn chosen(actions: &[Action], choice: HashSetObj<usize>) -> Option<&Action> {
+    choice.into_iter().next().map(|i| &actions[i])
+}
+#[test_log::test(tokio::test)]
+async fn ai_games_finish() -> Result<()> {
+    let db = get_db();
+    let mut deck = vec!["Plains"; 14];
+    deck.extend(vec!["Staunch Shieldmate"; 6]);
+    let mut gamebuild = GameBuilder::new();
+    gamebuild.set_seed(11);
+    for name in ["p1", "p2"] {
+        gamebuild.add_player(name, db, &deck, PlayerCon::new_ai())?;
+    }
+    let mut game = gamebuild.build(db)?;
+    let outcome = tokio::time::timeout(Duration::from_secs(60), game.run()).await?;
+    assert_ne!(outcome, GameOutcome::Ongoing);
+    let log = game.get_log();
+    assert!(log
+        .entries
+        .iter()
+        .any(|entry| entry.message.text.ends_with("casts Staunch Shieldmate")));
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn ai_plays_lands_then_taps_for_spells() -> Result<()> {
+    let (mut game, _) =
+        hand_battlefield_setup(vec!["Plains", "Staunch Shieldmate"], vec!["Plains"], None).await?;
+    let me = game.active_player;
+    game.phase = Some(Phase::FirstMain);
+    let actions = game.compute_actions(me);
+    let ask = |actions: &Vec<Action>| AskSelectN {
+        ents: actions.clone(),
+        min: 0,
+        max: 1,
+    };
+    let state = game.state_for(Viewer::Player(me));
+    let choice = choose_action(&state, me, &ask(&actions));
+    assert!(matches!(
+        chosen(&actions, choice),
+        Some(Action::PlayLand(_))
+    ));
+    let actions: Vec<Action> = actions
+        .into_iter()
+        .filter(|action| !matches!(action, Action::PlayLand(_)))
+        .collect();
+    let choice = choose_action(&state, me, &ask(&actions));
+    assert!(matches!(
+        chosen(&actions, choice),
+        Some(Action::ActivateAbility { .. })
+    ));
+    game.add_mana(me, ManaCostSymbol::White).await;
+    let state = game.state_for(Viewer::Player(me));
+    let choice = choose_action(&state, me, &ask(&actions));
+    assert!(matches!(chosen(&actions, choice), Some(Action::Cast(_))));
+    //Nothing is cast on the opponent's turn
+    let opponent = game.opponents(me)[0];
+    assert_eq!(choose_action(&state, opponent, &ask(&actions)).len(), 0);
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn ai_attacks_when_no_blocker_eats_the_attacker() -> Result<()> {
+    let (mut game, _) = hand_battlefield_setup(
+        vec![],
+        vec![
+            "Anointed Chorister",
+            "Staunch Shieldmate",
+            "Staunch Shieldmate",
+        ],
+        None,
+    )
+    .await?;
+    let me = game.active_player;
+    let opponent = game.opponents(me)[0];
+    let theirs = give_opponent(&mut game, "Staunch Shieldmate");
+    let chorister = cards_with_name(&game, "Anointed Chorister")[0];
+    let shieldmate = cards_with_name(&game, "Staunch Shieldmate")
+        .into_iter()
+        .find(|&card| card != theirs)
+        .unwrap();
+    let target = vec![TargetId::Player(opponent)];
+    let ask = pair(vec![(chorister, target.clone()), (shieldmate, target)]);
+    let attacks = choose_attacks(&game.state_for(Viewer::Player(me)), me, &ask);
+    let attacks: Vec<(CardId, Vec<TargetId>)> = attacks
+        .into_iter()
+        .map(|(attacker, targets)| (attacker, targets.into_iter().collect()))
+        .collect();
+    assert_eq!(
+        attacks,
+        vec![(shieldmate, vec![TargetId::Player(opponent)])]
+    );
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn ai_chump_blocks_lethal_attacks() -> Result<()> {
+    let (mut game, _) = hand_battlefield_setup(
+        vec![],
+        vec!["Anointed Chorister", "Staunch Shieldmate"],
+        None,
+    )
+    .await?;
+    let me: PlayerId = game.active_player;
+    let attacker = give_opponent(&mut game, "Staunch Shieldmate");
+    game.cards.get_mut(attacker).unwrap().attacking = Some(me.into());
+    let chorister = cards_with_name(&game, "Anointed Chorister")[0];
+    let ask = pair(vec![(chorister, vec![attacker])]);
+    //A 1/1 doesn't block a 1/3 for nothing
+    let blocks = choose_blocks(&game.state_for(Viewer::Player(me)), me, &ask);
+    assert!(blocks.is_empty());
+    game.players.get_mut(me).unwrap().life = 1;
+    let blocks = choose_blocks(&game.state_for(Viewer::Player(me)), me, &ask);
+    let blocked: Vec<CardId> = blocks[&chorister].iter().copied().collect();
+    assert_eq!(blocked, vec![attacker]);
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn ai_targets_the_opponents_creatures_with_removal() -> Result<()> {
+    let (mut game, hand) = hand_battlefield_setup(
+        vec!["Murder"],
+        vec!["Staunch Shieldmate", "Staunch Shieldmate"],
+        None,
+    )
+    .await?;
+    let me = game.active_player;
+    let theirs = give_opponent(&mut game, "Staunch Shieldmate");
+    let mine = cards_with_name(&game, "Staunch Shieldmate")
+        .into_iter()
+        .find(|&card| card != theirs)
+        .unwrap();
+    let mut state = game.state_for(Viewer::Player(me));
+    state.game.stack.push(*hand.iter().next().unwrap());
+    let ask = AskSelectN {
+        ents: vec![TargetId::Card(mine), TargetId::Card(theirs)],
+        min: 1,
+        max: 1,
+    };
+    let choice = choose_targets(&state, me, &ask);
+    assert_eq!(choice.into_iter().collect::<Vec<usize>>(), vec![1]);
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn ai_falls_back_to_the_smallest_answer_then_gives_up() -> Result<()> {
+    let (game, _) = hand_battlefield_setup(vec![], vec![], None).await?;
+    let me = game.active_player;
+    let opponent = game.opponents(me)[0];
+    let mut ai = AiClient::seeing(game.state_for(Viewer::Player(me)));
+    let ctx = AskContext {
+        id: 1,
+        game: &game,
+        player: me,
+    };
+    let ask = AskSelectN {
+        ents: vec![TargetId::Player(opponent), TargetId::Player(me)],
+        min: 1,
+        max: 1,
+    };
+    ai.rejected(1, "forced").await;
+    let smallest = ai.select_targets(ctx, &ask).await.unwrap();
+    assert_eq!(smallest.into_iter().collect::<Vec<_>>(), vec![0]);
+    ai.rejected(1, "forced").await;
+    assert!(matches!(ai.select_targets(ctx, &ask).await, Err(Gone)));
+    ai.accepted(2).await;
+    assert!(ai.select_targets(ctx, &ask).await.is_ok());
+    Ok(())
+}
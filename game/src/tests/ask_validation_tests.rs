@@ -0,0 +1,61 @@
+use crate::{
+    client_message::{AskPair, AskPairItem, AskSelectN},
+    player::{validate_pair, validate_selectn},
+};
+use common::{entities::CardId, hashset_obj::HashSetObj};
+use std::{collections::HashMap, num::NonZeroU64};
+
+fn card(id: u64) -> CardId {
+    CardId::from(NonZeroU64::new(id).unwrap())
+}
+#[test]
+fn selectn_reasons() {
+    let ask = AskSelectN {
+        ents: vec!["a", "b", "c"],
+        min: 1,
+        max: 2,
+    };
+    assert_eq!(validate_selectn(&[0].into_iter().collect(), &ask), Ok(()));
+    assert_eq!(
+        validate_selectn(&HashSetObj::new(), &ask),
+        Err("selected 0 but at least 1 are needed".to_owned())
+    );
+    assert_eq!(
+        validate_selectn(&[0, 1, 2].into_iter().collect(), &ask),
+        Err("selected 3 but at most 2 are allowed".to_owned())
+    );
+    assert_eq!(
+        validate_selectn(&[5].into_iter().collect(), &ask),
+        Err("5 isn't a choice, there are only 3".to_owned())
+    );
+}
+#[test]
+fn pair_reasons() {
+    let blocker = card(300);
+    let mut pairs = HashMap::new();
+    pairs.insert(
+        blocker,
+        AskPairItem {
+            items: [card(301)].into_iter().collect(),
+            min: 0,
+            max: 1,
+        },
+    );
+    let ask = AskPair { pairs };
+    let mut response = HashMap::new();
+    response.insert(blocker, [card(301)].into_iter().collect());
+    assert_eq!(validate_pair(&response, &ask), Ok(()));
+    response.insert(blocker, [card(302)].into_iter().collect());
+    assert!(validate_pair(&response, &ask)
+        .unwrap_err()
+        .contains("can only be paired with"));
+    response.insert(blocker, [card(301), card(302)].into_iter().collect());
+    assert!(validate_pair(&response, &ask)
+        .unwrap_err()
+        .contains("has 2 pairings"));
+    let mut response = HashMap::new();
+    response.insert(card(400), HashSetObj::new());
+    assert!(validate_pair(&response, &ask)
+        .unwrap_err()
+        .contains("can't be paired"));
+}
@@ -0,0 +1,132 @@
+use crate::{
+    event::{DamageReason, Event},
+    game::{build_game::GameBuilder, Game, GameOutcome, Phase},
+    player::{PlayerCon, TestClient},
+};
+use anyhow::Result;
+use carddb::carddb::CardDB;
+use common::{
+    actions::Action,
+    entities::{CardId, TargetId},
+    format::Format,
+    zones::Zone,
+};
+
+fn commander_game() -> Result<Game> {
+    let db: &CardDB = crate::carddb();
+    let deck = vec!["Plains"; 99];
+    let mut gamebuild = GameBuilder::new();
+    gamebuild.set_format(Format::Commander);
+    for name in ["p1", "p2"] {
+        let player =
+            gamebuild.add_player(name, db, &deck, PlayerCon::new_test(TestClient::default()))?;
+        gamebuild.set_commander(player, db, "Isamaru, Hound of Konda")?;
+    }
+    gamebuild.build(db)
+}
+fn commander_of(game: &Game, zone: Zone) -> CardId {
+    game.cards_and_zones()
+        .into_iter()
+        .find(|&(card, card_zone)| {
+            card_zone == zone
+                && game.cards.is(card, |card| {
+                    card.commander && card.owner == game.active_player
+                })
+        })
+        .expect("commander is in the zone")
+        .0
+}
+#[test_log::test(tokio::test)]
+async fn commander_setup() -> Result<()> {
+    let game = commander_game()?;
+    assert_eq!(game.command.len(), 2);
+    for (_, player) in game.players.view() {
+        assert_eq!(player.life, 40);
+    }
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn commander_tax() -> Result<()> {
+    let mut game = commander_game()?;
+    game.phase = Some(Phase::FirstMain);
+    let commander = commander_of(&game, Zone::Command);
+    let cast_cost = |game: &Game| {
+        game.compute_actions(game.active_player)
+            .into_iter()
+            .find_map(|action| match action {
+                Action::Cast(cast) if cast.source_card == commander => {
+                    assert_eq!(cast.zone, Zone::Command);
+                    Some(cast.costs.len())
+                }
+                _ => None,
+            })
+            .expect("commander can be cast")
+    };
+    assert_eq!(cast_cost(&game), 1);
+    let active = game.active_player;
+    game.players.get_mut(active).unwrap().commander_casts = 2;
+    assert_eq!(cast_cost(&game), 5);
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn commander_returns_to_command_zone() -> Result<()> {
+    let mut game = commander_game()?;
+    game.send_state().await;
+    let commander = commander_of(&game, Zone::Command);
+    let _ = game
+        .move_zones(vec![commander], Zone::Command, Zone::Battlefield)
+        .await;
+    let commander = commander_of(&game, Zone::Battlefield);
+    game.destroy(vec![commander]).await;
+    assert_eq!(game.battlefield.len(), 0);
+    let owner = game.players.get(game.active_player).unwrap();
+    assert!(owner.graveyard.is_empty());
+    commander_of(&game, Zone::Command);
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn commander_damage_loses() -> Result<()> {
+    let mut game = commander_game()?;
+    let commander = commander_of(&game, Zone::Command);
+    let _ = game
+        .move_zones(vec![commander], Zone::Command, Zone::Battlefield)
+        .await;
+    let commander = commander_of(&game, Zone::Battlefield);
+    let opponent = game.opponents(game.active_player)[0];
+    let _ = game
+        .handle_event(Event::Damage {
+            amount: 21,
+            target: TargetId::Player(opponent),
+            source: commander,
+            reason: DamageReason::Combat,
+        })
+        .await;
+    game.layers_state_actions().await;
+    assert_eq!(game.players.get(opponent).unwrap().life, 19);
+    //Commander damage is part of what clients see
+    game.send_state().await;
+    assert_eq!(game.outcome, GameOutcome::Winner(game.active_player));
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn losing_exiles_what_the_player_controls() -> Result<()> {
+    let mut game = commander_game()?;
+    let active = game.active_player;
+    let opponent = game.opponents(active)[0];
+    let plains = *game.players.get(active).unwrap().library.last().unwrap();
+    let _ = game
+        .move_zones(vec![plains], Zone::Library, Zone::Battlefield)
+        .await;
+    let plains = *game.battlefield.iter().next().unwrap();
+    game.cards
+        .get_mut(plains)
+        .unwrap()
+        .set_controller(Some(opponent));
+    let _ = game.handle_event(Event::Lose { player: opponent }).await;
+    assert_eq!(game.battlefield.len(), 0);
+    assert_eq!(game.exile.len(), 1);
+    let exiled = *game.exile.iter().next().unwrap();
+    assert!(game.cards.is(exiled, |card| card.owner == active));
+    assert_eq!(game.outcome, GameOutcome::Winner(active));
+    Ok(())
+}
@@ -1,15 +1,35 @@
 use std::collections::HashMap;
 
 use crate::{
+    client_message::{AskPair, AskSelectN, GameState},
     game::{build_game::GameBuilder, Game},
     player::{MockClient, PlayerCon, TestClient},
 };
 use anyhow::Result;
 use carddb::carddb::CardDB;
-use common::{entities::CardId, hashset_obj::HashSetObj, zones::Zone};
+use common::{
+    actions::Action,
+    entities::{CardId, TargetId},
+    hashset_obj::HashSetObj,
+    zones::Zone,
+};
 
-fn get_db() -> &'static CardDB {
-    crate::CARDDB.get_or_init(|| CardDB::new())
+pub fn get_db() -> &'static CardDB {
+    crate::carddb()
+}
+//Takes the first thing it can do until there's nothing left
+pub struct FirstActionClient {}
+impl MockClient for FirstActionClient {
+    fn select_action(&mut self, _game: &GameState, ask: &AskSelectN<Action>) -> HashSetObj<usize> {
+        (0..ask.ents.len().min(1)).collect()
+    }
+    fn select_attacks(
+        &mut self,
+        _game: &GameState,
+        _ask: &AskPair<TargetId>,
+    ) -> HashMap<CardId, HashSetObj<TargetId>> {
+        HashMap::new()
+    }
 }
 pub fn test_state_w_decks(deck: Vec<&'static str>) -> Result<Game> {
     let db: &CardDB = get_db();
@@ -0,0 +1,130 @@
+use crate::{
+    client_message::{Ask, AskPair, AskSelectN, RequestId},
+    decision::{Answer, AskContext, DecisionProvider},
+    player::PlayerCon,
+    tests::common_test::test_state_w_decks,
+};
+use anyhow::Result;
+use async_trait::async_trait;
+use common::{
+    actions::Action,
+    entities::{CardId, TargetId},
+    hashset_obj::HashSetObj,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+//Picks every choice until it's told that's too many, then picks none
+struct Greedy {
+    rejections: Arc<Mutex<Vec<String>>>,
+}
+impl Greedy {
+    fn answer(&mut self, choices: usize) -> Answer<HashSetObj<usize>> {
+        if self.rejections.lock().unwrap().is_empty() {
+            Ok((0..choices).collect())
+        } else {
+            Ok(HashSetObj::new())
+        }
+    }
+}
+#[async_trait]
+impl DecisionProvider for Greedy {
+    fn wants_states(&self) -> bool {
+        false
+    }
+    async fn select_action(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.answer(ask.ents.len())
+    }
+    async fn select_targets(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.answer(ask.ents.len())
+    }
+    async fn select_attackers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        _ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        Ok(HashMap::new())
+    }
+    async fn select_blockers(
+        &mut self,
+        _ctx: AskContext<'_>,
+        _ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        Ok(HashMap::new())
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.answer(ask.ents.len())
+    }
+    async fn command_zone(
+        &mut self,
+        _ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.answer(ask.ents.len())
+    }
+    async fn rejected(&mut self, _id: RequestId, reason: &str) {
+        self.rejections.lock().unwrap().push(reason.to_owned());
+    }
+}
+#[tokio::test]
+async fn rejected_answers_are_asked_again() -> Result<()> {
+    let mut game = test_state_w_decks(vec!["Plains"; 10])?;
+    let me = game.active_player;
+    let rejections = Arc::new(Mutex::new(Vec::new()));
+    let greedy = Greedy {
+        rejections: rejections.clone(),
+    };
+    game.players.get_mut(me).unwrap().player_con = PlayerCon::new(greedy);
+    let cards: Vec<CardId> = game.players.get(me).unwrap().library.clone();
+    let ask = AskSelectN {
+        ents: cards[..3].to_vec(),
+        min: 0,
+        max: 1,
+    };
+    let query = Ask::DiscardToHandSize(ask.clone());
+    let player = game.players.get(me).unwrap();
+    let selection = player.ask_user_selectn(&game, me, &query, &ask).await;
+    assert_eq!(selection.len(), 0);
+    assert_eq!(
+        *rejections.lock().unwrap(),
+        vec!["selected 3 but at most 1 are allowed".to_owned()]
+    );
+    //Both answers are recorded, so a replay is rejected the same way
+    assert_eq!(player.player_con.responses().len(), 2);
+    assert!(!player.player_con.forfeited());
+    Ok(())
+}
+#[tokio::test]
+async fn players_who_are_gone_forfeit() -> Result<()> {
+    let mut game = test_state_w_decks(vec!["Plains"; 10])?;
+    let me = game.active_player;
+    game.players.get_mut(me).unwrap().player_con = PlayerCon::default();
+    let cards: Vec<CardId> = game.players.get(me).unwrap().library.clone();
+    let ask = AskSelectN {
+        ents: cards[..3].to_vec(),
+        min: 2,
+        max: 3,
+    };
+    let query = Ask::DiscardToHandSize(ask.clone());
+    let player = game.players.get(me).unwrap();
+    //The smallest answer allowed is taken for them
+    let selection = player.ask_user_selectn(&game, me, &query, &ask).await;
+    assert_eq!(selection.len(), 2);
+    assert!(player.player_con.forfeited());
+    assert!(!player.player_con.wants_states());
+    Ok(())
+}
@@ -0,0 +1,129 @@
+use crate::{
+    client_message::{AskSelectN, GameState},
+    event::Event,
+    game::{serialize_game::Viewer, Game, Phase},
+    log::{Entry, LogMessage, LogStamp},
+    player::MockClient,
+    spectator::SpectatorView,
+    tests::common_test::hand_battlefield_setup,
+};
+use anyhow::Result;
+use common::{actions::Action, entities::TargetId, hashset_obj::HashSetObj, mana::ManaCostSymbol};
+use std::time::Duration;
+
+struct MurderClient {}
+impl MockClient for MurderClient {
+    fn select_action(&mut self, _game: &GameState, _ask: &AskSelectN<Action>) -> HashSetObj<usize> {
+        [0].into_iter().collect()
+    }
+    fn select_targets(
+        &mut self,
+        _game: &GameState,
+        _ask: &AskSelectN<TargetId>,
+    ) -> HashSetObj<usize> {
+        [0].into_iter().collect()
+    }
+}
+#[test_log::test(tokio::test)]
+async fn casts_are_logged_with_targets() -> Result<()> {
+    let (mut game, _) = hand_battlefield_setup(
+        vec!["Murder"],
+        vec!["Staunch Shieldmate"],
+        Some(Box::new(MurderClient {})),
+    )
+    .await?;
+    game.phase = Some(Phase::FirstMain);
+    for mana in [
+        ManaCostSymbol::Black,
+        ManaCostSymbol::Black,
+        ManaCostSymbol::Black,
+    ] {
+        game.add_mana(game.active_player, mana).await;
+    }
+    game.cycle_priority().await;
+    let opponent = game.opponents(game.active_player)[0];
+    let texts: Vec<String> = game
+        .log_for(Viewer::Player(opponent))
+        .into_iter()
+        .map(|message| message.text)
+        .collect();
+    assert!(texts.contains(&"p1 casts Murder targeting Staunch Shieldmate".to_owned()));
+    assert!(texts.contains(&"Murder resolves".to_owned()));
+    Ok(())
+}
+//Only the entries logged since start
+fn logged_since(game: &Game, viewer: Viewer, start: usize) -> Vec<LogMessage> {
+    game.log_for(viewer)
+        .into_iter()
+        .filter(|message| message.index >= start)
+        .collect()
+}
+#[test_log::test(tokio::test)]
+async fn hidden_cards_stay_out_of_the_log() -> Result<()> {
+    let (game, hand) = hand_battlefield_setup(vec!["Murder"], vec![], None).await?;
+    let murder = *hand.iter().next().unwrap();
+    let start = game.get_log().entries.len();
+    game.log(Entry::TriggeredAbil(murder));
+    game.log(Entry::Text("Everyone sees this"));
+    let owner = logged_since(&game, Viewer::Player(game.active_player), start);
+    assert_eq!(owner.len(), 2);
+    assert_eq!(owner[0].text, "Murder's ability triggers");
+    let opponent = Viewer::Player(game.opponents(game.active_player)[0]);
+    let opponent = logged_since(&game, opponent, start);
+    assert_eq!(opponent.len(), 1);
+    assert_eq!(opponent[0].index, start + 1);
+    let public = Viewer::Spectator(SpectatorView::Public);
+    assert_eq!(logged_since(&game, public, start).len(), 1);
+    let omniscient = Viewer::Spectator(SpectatorView::Omniscient {
+        delay: Duration::ZERO,
+    });
+    assert_eq!(logged_since(&game, omniscient, start).len(), 2);
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn events_are_logged() -> Result<()> {
+    let (game, _) = hand_battlefield_setup(vec!["Murder"], vec!["Plains"], None).await?;
+    let texts = |viewer| -> Vec<String> {
+        game.log_for(viewer)
+            .into_iter()
+            .map(|message| message.text)
+            .collect()
+    };
+    let owner = texts(Viewer::Player(game.active_player));
+    assert!(owner.contains(&"p1 draws a card".to_owned()));
+    assert!(owner.contains(&"Murder went from Library to Hand".to_owned()));
+    assert!(owner.contains(&"Plains went from Library to Battlefield".to_owned()));
+    //The opponent sees the draw and the land, but not what was drawn
+    let opponent = texts(Viewer::Player(game.opponents(game.active_player)[0]));
+    assert!(opponent.contains(&"p1 draws a card".to_owned()));
+    assert!(opponent.contains(&"Plains went from Library to Battlefield".to_owned()));
+    assert!(opponent.iter().all(|text| !text.contains("Murder")));
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn entries_are_stamped_with_the_turn() -> Result<()> {
+    let (mut game, _) = hand_battlefield_setup(vec![], vec![], None).await?;
+    let opponent = game.opponents(game.active_player)[0];
+    let _ = game
+        .handle_event(Event::Turn {
+            player: opponent,
+            extra: false,
+        })
+        .await;
+    game.phase = Some(Phase::FirstMain);
+    game.log(Entry::Text("Main phase"));
+    let log = game.log_for(Viewer::Player(opponent));
+    let last = log.last().unwrap();
+    assert_eq!(last.text, "Main phase");
+    assert_eq!(
+        last.stamp,
+        LogStamp {
+            turn: 1,
+            active_player: opponent,
+            phase: Some(Phase::FirstMain),
+            subphase: None,
+        }
+    );
+    assert!(log.iter().any(|message| message.text == "p2's turn"));
+    Ok(())
+}
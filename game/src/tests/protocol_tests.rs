@@ -0,0 +1,60 @@
+use crate::client_message::ClientRequest;
+use crate::protocol::{
+    decode, negotiate, subprotocol, typescript, Encoding, Negotiated, PROTOCOL_VERSION,
+};
+use common::entities::CardId;
+use common::hashset_obj::HashSetObj;
+use std::collections::HashMap;
+use std::num::NonZeroU64;
+
+#[test]
+fn negotiates_newest_shared_version() {
+    let json = Negotiated {
+        version: PROTOCOL_VERSION,
+        encoding: Encoding::Json,
+    };
+    let msgpack = Negotiated {
+        encoding: Encoding::MessagePack,
+        ..json
+    };
+    assert_eq!(negotiate(None), Ok(None));
+    let offered = format!("chat, {}", subprotocol(PROTOCOL_VERSION, Encoding::Json));
+    assert_eq!(negotiate(Some(&offered)), Ok(Some(json)));
+    let too_new = subprotocol(PROTOCOL_VERSION + 1, Encoding::Json);
+    let both = format!(
+        "{}, {}, {}",
+        too_new,
+        subprotocol(PROTOCOL_VERSION, Encoding::MessagePack),
+        subprotocol(PROTOCOL_VERSION, Encoding::Json)
+    );
+    assert_eq!(negotiate(Some(&both)), Ok(Some(msgpack)));
+    let err = negotiate(Some(&too_new)).unwrap_err();
+    assert_eq!(err.offered, too_new);
+    assert!(negotiate(Some("mtg.vx")).is_err());
+}
+#[test]
+fn typescript_covers_both_directions() {
+    let ts = typescript();
+    assert!(ts.contains(&format!(
+        "export const PROTOCOL_VERSION = {};",
+        PROTOCOL_VERSION
+    )));
+    assert!(ts.contains("export type ClientMessage = "));
+    assert!(ts.contains("export type ClientToServer = "));
+    assert!(ts.contains("export type ClientRequest = \"Resync\";"));
+}
+#[test]
+fn encodings_carry_the_same_document() -> anyhow::Result<()> {
+    let card = CardId::from(NonZeroU64::new(300).unwrap());
+    let mut response: HashMap<CardId, HashSetObj<CardId>> = HashMap::new();
+    response.insert(card, [card].into_iter().collect());
+    for encoding in [Encoding::Json, Encoding::MessagePack] {
+        let message = encoding.encode(&response)?;
+        assert_eq!(message.is_binary(), encoding == Encoding::MessagePack);
+        let decoded: HashMap<CardId, HashSetObj<CardId>> = decode(&message)?;
+        assert!(decoded[&card].contains(&card));
+        let request = encoding.encode(&ClientRequest::Resync)?;
+        assert!(matches!(decode(&request)?, ClientRequest::Resync));
+    }
+    Ok(())
+}
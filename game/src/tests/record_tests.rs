@@ -0,0 +1,97 @@
+use crate::{
+    game::{build_game::GameBuilder, GameOutcome},
+    log::LogMessage,
+    player::{PlayerCon, TestClient},
+    record::{win_rates, CardWinRate, GameRecord, RecordSink, RecordedPlayer},
+    tests::common_test::{get_db, FirstActionClient},
+};
+use anyhow::Result;
+use common::{entities::PlayerId, format::Format};
+use std::num::NonZeroU64;
+use std::time::Duration;
+
+#[test_log::test(tokio::test)]
+async fn finished_games_are_recorded() -> Result<()> {
+    let db = get_db();
+    let mut gamebuild = GameBuilder::new();
+    gamebuild.set_seed(3);
+    for name in ["p1", "p2"] {
+        let client = TestClient::with_client(Box::new(FirstActionClient {}));
+        gamebuild.add_player(name, db, &vec!["Plains"; 8], PlayerCon::new_test(client))?;
+    }
+    let mut game = gamebuild.build(db)?;
+    let outcome = game.run().await;
+    let dir = std::env::temp_dir().join(format!("records-{}", std::process::id()));
+    let sink = RecordSink::new(dir.clone());
+    let path = sink.write("game", &game, Duration::from_secs(90))?;
+    let records = sink.load_all()?;
+    let log = std::fs::read_to_string(dir.join("game.log.jsonl"))?;
+    std::fs::remove_dir_all(&dir)?;
+    assert!(path.ends_with("game.game.json"));
+    assert_eq!(records.len(), 1);
+    let record = &records[0];
+    assert_eq!(record.seed, 3);
+    assert_eq!(record.outcome, outcome);
+    assert_eq!(record.duration_secs, 90);
+    assert!(record.turns > 0);
+    assert_eq!(record.players[1].name, "p2");
+    assert_eq!(record.players[0].deck.len(), 8);
+    let log: Vec<LogMessage> = log
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    assert_eq!(log.len(), game.get_log().entries.len());
+    assert!(log
+        .iter()
+        .any(|message| message.text == "Plains went from Library to Hand"));
+    Ok(())
+}
+fn player(id: u64) -> PlayerId {
+    PlayerId::from(NonZeroU64::new(id).unwrap())
+}
+fn seat(id: u64, deck: &[&str]) -> RecordedPlayer {
+    RecordedPlayer {
+        player: player(id),
+        name: format!("p{}", id),
+        deck: deck.iter().map(|card| card.to_string()).collect(),
+        commander: None,
+    }
+}
+fn played(players: Vec<RecordedPlayer>, outcome: GameOutcome) -> GameRecord {
+    GameRecord {
+        seed: 0,
+        format: Format::Casual,
+        players,
+        outcome,
+        turns: 10,
+        duration_secs: 60,
+    }
+}
+#[test]
+fn card_win_rates() {
+    let winner = GameOutcome::Winner(player(1));
+    let records = vec![
+        played(
+            vec![seat(1, &["Murder", "Murder"]), seat(2, &["Plains"])],
+            winner,
+        ),
+        played(vec![seat(1, &["Plains"]), seat(2, &["Murder"])], winner),
+        played(
+            vec![seat(1, &["Murder"]), seat(2, &["Plains"])],
+            GameOutcome::Tie,
+        ),
+        played(
+            vec![seat(1, &["Murder"]), seat(2, &["Plains"])],
+            GameOutcome::Ongoing,
+        ),
+    ];
+    let rate = |card: &str, games, wins| CardWinRate {
+        card: card.to_owned(),
+        games,
+        wins,
+    };
+    assert_eq!(
+        win_rates(&records),
+        vec![rate("Murder", 3, 1), rate("Plains", 3, 1)]
+    );
+}
@@ -0,0 +1,74 @@
+use crate::{
+    game::{build_game::GameBuilder, GameOutcome},
+    player::{PlayerCon, TestClient},
+    replay::replay,
+    tests::common_test::{get_db, FirstActionClient},
+};
+use anyhow::Result;
+use serde_json::Value;
+
+async fn play(seed: u64) -> Result<crate::replay::Replay> {
+    let db = get_db();
+    let deck = vec!["Plains"; 8];
+    let mut gamebuild = GameBuilder::new();
+    gamebuild.set_seed(seed);
+    for name in ["p1", "p2"] {
+        let client = TestClient::with_client(Box::new(FirstActionClient {}));
+        gamebuild.add_player(name, db, &deck, PlayerCon::new_test(client))?;
+    }
+    let mut game = gamebuild.build(db)?;
+    assert_ne!(game.run().await, GameOutcome::Ongoing);
+    game.replay()
+}
+#[test_log::test(tokio::test)]
+async fn replays_reach_the_same_state() -> Result<()> {
+    let recorded = play(7).await?;
+    assert_eq!(recorded.seed, 7);
+    assert_eq!(recorded.players[0].deck.len(), 8);
+    assert!(recorded
+        .players
+        .iter()
+        .all(|seat| !seat.responses.is_empty()));
+    //Round trips through a file the way the server saves them
+    let recorded: crate::replay::Replay = serde_json::from_str(&serde_json::to_string(&recorded)?)?;
+    replay(get_db(), &recorded).await?;
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn diverging_replays_fail() -> Result<()> {
+    let mut recorded = play(7).await?;
+    recorded.final_state["lands_played_this_turn"] = Value::from(99);
+    assert!(replay(get_db(), &recorded).await.is_err());
+    Ok(())
+}
+#[test]
+fn seeds_decide_shuffles() -> Result<()> {
+    let shuffled = |seed| -> Result<Vec<String>> {
+        let db = get_db();
+        let deck = vec![
+            "Plains",
+            "Murder",
+            "Staunch Shieldmate",
+            "Basri's Acolyte",
+            "Aven Gagglemaster",
+            "Anointed Chorister",
+            "Baneslayer Angel",
+            "Swift Response",
+        ];
+        let mut gamebuild = GameBuilder::new();
+        gamebuild.set_seed(seed);
+        let player =
+            gamebuild.add_player("p1", db, &deck, PlayerCon::new_test(TestClient::default()))?;
+        gamebuild.add_player("p2", db, &deck, PlayerCon::new_test(TestClient::default()))?;
+        let mut game = gamebuild.build(db)?;
+        game.shuffle(player);
+        let library = &game.players.get(player).unwrap().library;
+        Ok(library
+            .iter()
+            .map(|&card| game.cards.get(card).unwrap().name.clone())
+            .collect())
+    };
+    assert_eq!(shuffled(1)?, shuffled(1)?);
+    assert_ne!(shuffled(1)?, shuffled(2)?);
+    Ok(())
+}
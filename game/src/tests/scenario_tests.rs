@@ -0,0 +1,58 @@
+use crate::{scenario::Scenario, tests::common_test::get_db};
+use anyhow::Result;
+use std::path::Path;
+
+#[tokio::test]
+async fn scenarios_play_out_as_expected() -> Result<()> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("scenarios");
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<_, _>>()?;
+    paths.sort();
+    let mut failed = Vec::new();
+    for path in paths {
+        if path.extension().map_or(true, |ext| ext != "ron") {
+            continue;
+        }
+        if let Err(err) = Scenario::load(&path)?.check(get_db()).await {
+            failed.push(format!("{}: {}", path.display(), err));
+        }
+    }
+    assert!(failed.is_empty(), "{}", failed.join("\n"));
+    Ok(())
+}
+#[tokio::test]
+async fn mistakes_in_a_scenario_are_reported() -> Result<()> {
+    let scenario = Scenario::from_ron(
+        r#"Scenario(
+            name: "Casting without mana",
+            players: [
+                (
+                    name: "p1",
+                    hand: ["Staunch Shieldmate"],
+                    script: [Cast("Staunch Shieldmate"), Cast("Plains")],
+                ),
+                (name: "p2"),
+            ],
+            expect: [Zone("p1", Battlefield, ["Staunch Shieldmate"]), Life("p2", 20)],
+        )"#,
+    )?;
+    let (game, failures) = scenario.play(get_db()).await?;
+    assert_eq!(
+        failures,
+        vec![
+            "p1: couldn't cast Plains".to_owned(),
+            r#"p1's Battlefield has [], not ["Staunch Shieldmate"]"#.to_owned(),
+        ]
+    );
+    assert_eq!(game.players.get(game.active_player).unwrap().hand.len(), 1);
+    Ok(())
+}
+#[tokio::test]
+async fn scenarios_need_real_cards() -> Result<()> {
+    let scenario = Scenario::from_ron(
+        r#"Scenario(name: "Typo", players: [(name: "p1", hand: ["Staunch Sheildmate"]), (name: "p2")])"#,
+    )?;
+    assert!(scenario.play(get_db()).await.is_err());
+    Ok(())
+}
@@ -0,0 +1,128 @@
+use crate::{
+    game::{build_game::GameBuilder, GameOutcome},
+    player::PlayerCon,
+    search::{determinize, SearchClient},
+    tests::common_test::{get_db, test_state_w_decks},
+};
+use anyhow::Result;
+use common::entities::CardId;
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use std::collections::HashSet;
+use std::time::Duration;
+
+fn deck() -> Vec<&'static str> {
+    let mut deck = vec!["Plains"; 14];
+    deck.extend(vec!["Staunch Shieldmate"; 6]);
+    deck
+}
+fn searcher(budget: Duration) -> PlayerCon {
+    PlayerCon::new(SearchClient::new(budget, 3))
+}
+#[test_log::test(tokio::test)]
+async fn determinizing_keeps_what_the_player_can_see() -> Result<()> {
+    let mut game = test_state_w_decks(deck())?;
+    let me = game.active_player;
+    let opponent = game.opponents(me)[0];
+    for player in [me, opponent] {
+        for _ in 0..7 {
+            game.draw(player).await;
+        }
+    }
+    //The opponent revealed a card from their hand
+    let revealed = *game
+        .players
+        .get(opponent)
+        .unwrap()
+        .hand
+        .iter()
+        .next()
+        .unwrap();
+    game.cards.get_mut(revealed).unwrap().known_to.insert(me);
+    let unseen = |game: &crate::game::Game| -> HashSet<CardId> {
+        let pl = game.players.get(opponent).unwrap();
+        pl.hand.iter().chain(pl.library.iter()).copied().collect()
+    };
+    let hand = |game: &crate::game::Game, player| -> HashSet<CardId> {
+        game.players
+            .get(player)
+            .unwrap()
+            .hand
+            .iter()
+            .copied()
+            .collect()
+    };
+    let mut rng = ChaCha12Rng::seed_from_u64(5);
+    let mut dealt = game.simulation(1, |_| PlayerCon::default());
+    determinize(&mut dealt, me, &mut rng);
+
+    assert_eq!(hand(&dealt, me), hand(&game, me));
+    assert_eq!(unseen(&dealt), unseen(&game));
+    let their_hand = hand(&dealt, opponent);
+    assert_eq!(their_hand.len(), 7);
+    assert!(their_hand.contains(&revealed));
+    assert_ne!(their_hand, hand(&game, opponent));
+    //The opponent still knows their own hand, whatever was dealt into it
+    for card in their_hand {
+        assert!(dealt.cards.get(card).unwrap().known_to.contains(&opponent));
+    }
+    assert_ne!(
+        dealt.players.get(me).unwrap().library,
+        game.players.get(me).unwrap().library
+    );
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn simulations_leave_the_game_alone() -> Result<()> {
+    let mut game = test_state_w_decks(deck())?;
+    for player in game.turn_order.clone() {
+        for _ in 0..7 {
+            game.draw(player).await;
+        }
+    }
+    let before = serde_json::to_value(&game)?;
+    let logged = game.get_log().entries.len();
+    let mut simulation = game.simulation(1, |_| searcher(Duration::ZERO));
+    simulation.play_out(4).await;
+
+    assert!(simulation.turn >= 4);
+    assert_eq!(serde_json::to_value(&game)?, before);
+    assert_eq!(game.get_log().entries.len(), logged);
+    for player in game.turn_order.clone() {
+        assert_eq!(
+            game.players
+                .get(player)
+                .unwrap()
+                .player_con
+                .responses()
+                .len(),
+            0
+        );
+    }
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn search_ai_games_finish() -> Result<()> {
+    let db = get_db();
+    let mut gamebuild = GameBuilder::new();
+    gamebuild.set_seed(11);
+    gamebuild.add_player("search", db, &deck(), searcher(Duration::from_millis(20)))?;
+    gamebuild.add_player("heuristic", db, &deck(), PlayerCon::new_ai())?;
+    let mut game = gamebuild.build(db)?;
+    let outcome = tokio::time::timeout(Duration::from_secs(120), game.run()).await?;
+    assert_ne!(outcome, GameOutcome::Ongoing);
+    let log = game.get_log();
+    assert!(log
+        .entries
+        .iter()
+        .any(|entry| entry.message.text == "search casts Staunch Shieldmate"));
+    //Its answers are recorded, so games against it can be replayed
+    let (_, searcher) = game
+        .players
+        .view()
+        .into_iter()
+        .find(|(_, player)| player.name == "search")
+        .unwrap();
+    assert!(searcher.player_con.responses().len() > 0);
+    Ok(())
+}
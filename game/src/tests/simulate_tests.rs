@@ -0,0 +1,139 @@
+use crate::{
+    client_message::{Ask, AskPair, AskPairItem, AskSelectN},
+    player::{validate_pair, PlayerCon},
+    simulate::{Contestant, Policy, RandomClient, Simulation},
+    tests::common_test::{get_db, test_state_w_decks},
+};
+use anyhow::Result;
+use carddb::decklist::ResolvedDeck;
+use common::entities::{CardId, TargetId};
+use std::collections::HashMap;
+
+fn contestant(name: &str, policy: Policy, cards: Vec<&'static str>) -> Contestant {
+    Contestant {
+        name: name.to_owned(),
+        deck: ResolvedDeck {
+            commander: None,
+            cards,
+        },
+        policy,
+    }
+}
+fn deck() -> Vec<&'static str> {
+    let mut deck = vec!["Plains"; 14];
+    deck.extend(vec!["Staunch Shieldmate"; 6]);
+    deck
+}
+#[tokio::test]
+async fn random_answers_are_legal() -> Result<()> {
+    let mut game = test_state_w_decks(deck())?;
+    let me = game.active_player;
+    game.players.get_mut(me).unwrap().player_con = PlayerCon::new(RandomClient::new(3));
+    let cards: Vec<CardId> = game.players.get(me).unwrap().library.clone();
+    let player = game.players.get(me).unwrap();
+    for max in 1..4 {
+        let ask = AskSelectN {
+            ents: cards[..5].to_vec(),
+            min: 1,
+            max,
+        };
+        let query = Ask::DiscardToHandSize(ask.clone());
+        let selection = player.ask_user_selectn(&game, me, &query, &ask).await;
+        assert!(selection.len() >= 1 && selection.len() <= max as usize);
+    }
+    let mut pairs = HashMap::new();
+    pairs.insert(
+        cards[0],
+        AskPairItem {
+            items: [TargetId::Player(me)].into_iter().collect(),
+            min: 0,
+            max: 1,
+        },
+    );
+    let ask = AskPair { pairs };
+    let attacks = player.ask_attackers(&game, me, &ask).await;
+    assert_eq!(validate_pair(&attacks, &ask), Ok(()));
+    //Nothing was rejected, every answer was asked for once
+    assert_eq!(player.player_con.responses().len(), 4);
+    Ok(())
+}
+#[tokio::test(flavor = "multi_thread")]
+async fn simulations_report_every_game() -> Result<()> {
+    let mut simulation = Simulation::new(
+        vec![
+            contestant("random", Policy::Random, deck()),
+            contestant("heuristic", Policy::Heuristic, deck()),
+        ],
+        4,
+    );
+    simulation.seed = 20;
+    simulation.max_turns = 30;
+    let report = simulation.run(get_db()).await;
+    assert_eq!(report.games, 4);
+    assert!(report.crashes.is_empty());
+    let wins: u32 = report.wins.iter().map(|(_, wins)| wins).sum();
+    assert_eq!(wins + report.ties + report.unfinished, 4);
+    assert_eq!(report.turns.len(), 4);
+    assert!(report.average_turns() > 0.0);
+    assert_eq!(report.wins[0].0, "random");
+    if wins > 0 {
+        let shieldmate = report
+            .cards
+            .iter()
+            .find(|card| card.card == "Staunch Shieldmate")
+            .unwrap();
+        assert!(shieldmate.casts > 0);
+    }
+    Ok(())
+}
+#[tokio::test(flavor = "multi_thread")]
+async fn failed_games_are_reported_with_their_seed() -> Result<()> {
+    let mut simulation = Simulation::new(
+        vec![
+            contestant("broken", Policy::Random, vec!["Not A Real Card"; 20]),
+            contestant("heuristic", Policy::Heuristic, deck()),
+        ],
+        2,
+    );
+    simulation.seed = 7;
+    let report = simulation.run(get_db()).await;
+    let seeds: Vec<u64> = report.crashes.iter().map(|crash| crash.seed).collect();
+    assert_eq!(seeds, vec![7, 8]);
+    assert!(report.turns.is_empty());
+    Ok(())
+}
+#[tokio::test(flavor = "multi_thread")]
+async fn seeds_wrap_around() -> Result<()> {
+    let mut simulation = Simulation::new(
+        vec![
+            contestant("broken", Policy::Random, vec!["Not A Real Card"; 20]),
+            contestant("heuristic", Policy::Heuristic, deck()),
+        ],
+        2,
+    );
+    simulation.seed = u64::MAX;
+    let report = simulation.run(get_db()).await;
+    let seeds: Vec<u64> = report.crashes.iter().map(|crash| crash.seed).collect();
+    assert_eq!(seeds, vec![0, u64::MAX]);
+    Ok(())
+}
+#[tokio::test]
+async fn rejected_random_answers_crash_the_game() -> Result<()> {
+    let mut game = test_state_w_decks(deck())?;
+    let me = game.active_player;
+    game.players.get_mut(me).unwrap().player_con = PlayerCon::new(RandomClient::new(3));
+    let cards: Vec<CardId> = game.players.get(me).unwrap().library.clone();
+    //More cards than there are to pick from, so no answer is legal
+    let ask = AskSelectN {
+        ents: cards[..2].to_vec(),
+        min: 3,
+        max: 3,
+    };
+    let asked = tokio::task::spawn(async move {
+        let query = Ask::DiscardToHandSize(ask.clone());
+        let player = game.players.get(me).unwrap();
+        player.ask_user_selectn(&game, me, &query, &ask).await;
+    });
+    assert!(asked.await.unwrap_err().is_panic());
+    Ok(())
+}
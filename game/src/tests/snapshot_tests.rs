@@ -0,0 +1,75 @@
+use crate::{
+    game::serialize_game::Viewer,
+    game::Game,
+    log::Entry,
+    player::{PlayerCon, TestClient},
+    tests::common_test::{get_db, hand_battlefield_setup, test_state_w_decks},
+};
+use anyhow::Result;
+use common::zones::Zone;
+use std::path::PathBuf;
+
+fn snapshot_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{}.json", name, std::process::id()))
+}
+#[test_log::test(tokio::test)]
+async fn snapshots_restore_the_game() -> Result<()> {
+    let (mut game, _) =
+        hand_battlefield_setup(vec!["Murder", "Plains"], vec!["Staunch Shieldmate"], None).await?;
+    let path = snapshot_path("snapshots_restore_the_game");
+    game.save_snapshot(&path)?;
+    let mut loaded = Game::load_snapshot(&path, get_db(), |_| {
+        PlayerCon::new_test(TestClient::default())
+    })?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(loaded.final_state()?, game.final_state()?);
+    let shieldmate = *loaded.battlefield.iter().next().unwrap();
+    assert!(loaded.cards.get(shieldmate).unwrap().printed.is_some());
+    //New cards get the ids they would have
+    let moved = game
+        .move_zones(vec![shieldmate], Zone::Battlefield, Zone::Library)
+        .await;
+    let loaded_moved = loaded
+        .move_zones(vec![shieldmate], Zone::Battlefield, Zone::Library)
+        .await;
+    assert_eq!(moved, loaded_moved);
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn snapshots_keep_the_log() -> Result<()> {
+    let (game, _) = hand_battlefield_setup(vec![], vec!["Staunch Shieldmate"], None).await?;
+    game.log(Entry::Text("Logged before the snapshot"));
+    let path = snapshot_path("snapshots_keep_the_log");
+    game.autosave_snapshot(&path).await?;
+    let loaded = Game::load_snapshot(&path, get_db(), |_| PlayerCon::default())?;
+    std::fs::remove_file(&path)?;
+    let log = |game: &Game| -> Vec<(usize, String)> {
+        let viewer = Viewer::Player(game.active_player);
+        (game.log_for(viewer).into_iter())
+            .map(|message| (message.index, message.text))
+            .collect()
+    };
+    assert!(log(&loaded).contains(&(
+        game.get_log().entries.len() - 1,
+        "Logged before the snapshot".to_owned()
+    )));
+    assert_eq!(log(&loaded), log(&game));
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn snapshots_keep_shuffling_the_same() -> Result<()> {
+    let mut game = test_state_w_decks(vec!["Plains"; 10])?;
+    let player = game.active_player;
+    game.shuffle(player);
+    let path = snapshot_path("snapshots_keep_shuffling_the_same");
+    game.save_snapshot(&path)?;
+    let mut loaded = Game::load_snapshot(&path, get_db(), |_| PlayerCon::default())?;
+    std::fs::remove_file(&path)?;
+    game.shuffle(player);
+    loaded.shuffle(player);
+    assert_eq!(
+        game.players.get(player).unwrap().library,
+        loaded.players.get(player).unwrap().library
+    );
+    Ok(())
+}
@@ -0,0 +1,60 @@
+use crate::{
+    client_message::{ClientMessage, GameState},
+    game::serialize_game::Viewer,
+    spectator::{Spectator, SpectatorView},
+    tests::common_test::hand_battlefield_setup,
+};
+use anyhow::Result;
+use std::time::Duration;
+
+fn hand_names(state: &GameState) -> Vec<String> {
+    let mut names: Vec<String> = state
+        .players
+        .values()
+        .flat_map(|player| player.hand.iter())
+        .map(|id| {
+            state
+                .cards
+                .get(id)
+                .expect("hand cards are sent")
+                .name
+                .clone()
+        })
+        .collect();
+    names.sort();
+    names
+}
+#[test_log::test(tokio::test)]
+async fn public_view_hides_hands() -> Result<()> {
+    let (game, _) = hand_battlefield_setup(vec!["Murder"], vec!["Plains"], None).await?;
+    let public = game.state_for(Viewer::Spectator(SpectatorView::Public));
+    assert_eq!(public.player, None);
+    assert_eq!(hand_names(&public), vec![String::new()]);
+    let plains = game
+        .battlefield
+        .iter()
+        .next()
+        .expect("plains is on the battlefield");
+    assert_eq!(public.cards.get(plains).unwrap().name, "Plains");
+    assert!(public.cards.values().all(|card| card.printed.is_none()));
+    let owner = game.state_for(Viewer::Player(game.active_player));
+    assert_eq!(hand_names(&owner), vec!["Murder".to_owned()]);
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn omniscient_spectators_are_delayed() -> Result<()> {
+    let (mut game, _) = hand_battlefield_setup(vec!["Murder"], vec![], None).await?;
+    let delay = Duration::from_millis(50);
+    let (spectator, mut states) = Spectator::channel(SpectatorView::Omniscient { delay });
+    game.spectators.add(spectator);
+    game.send_state().await;
+    assert!(states.try_recv().is_err());
+    let message = tokio::time::timeout(delay * 20, states.recv())
+        .await?
+        .expect("state is sent");
+    let ClientMessage::GameState(state) = serde_json::from_value(message)? else {
+        panic!("spectators are only sent states");
+    };
+    assert_eq!(hand_names(&state), vec!["Murder".to_owned()]);
+    Ok(())
+}
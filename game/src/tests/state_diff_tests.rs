@@ -0,0 +1,109 @@
+use crate::{
+    game::serialize_game::Viewer,
+    state_diff::{StateDiff, StateStream},
+    tests::common_test::hand_battlefield_setup,
+};
+use anyhow::Result;
+use common::zones::Zone;
+use serde_json::Value;
+
+//Sets like known_to come back in any order once they've been through a diff
+fn unordered(value: Value) -> Value {
+    match value {
+        Value::Array(items) => {
+            let mut items: Vec<Value> = items.into_iter().map(unordered).collect();
+            items.sort_by_key(|item| item.to_string());
+            Value::Array(items)
+        }
+        Value::Object(fields) => Value::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key, unordered(value)))
+                .collect(),
+        ),
+        value => value,
+    }
+}
+#[test_log::test(tokio::test)]
+async fn diffs_rebuild_the_state() -> Result<()> {
+    let (mut game, hand) = hand_battlefield_setup(vec!["Plains", "Murder"], vec![], None).await?;
+    let viewer = Viewer::Player(game.opponents(game.active_player)[0]);
+    let mut stream = StateStream::default();
+    let first = stream.update(serde_json::to_value(game.state_for(viewer))?);
+    let mut client = first["GameState"].clone();
+    let plains = hand
+        .iter()
+        .copied()
+        .find(|&card| game.cards.get(card).unwrap().name == "Plains")
+        .unwrap();
+    let _ = game
+        .move_zones(vec![plains], Zone::Hand, Zone::Battlefield)
+        .await;
+    let latest = serde_json::to_value(game.state_for(viewer))?;
+    let message = stream.update(latest);
+    let diff: StateDiff = serde_json::from_value(message["StateDiff"].clone())?;
+    assert_eq!(diff.version, 2);
+    assert!(diff.game.contains_key("battlefield"));
+    diff.apply(&mut client)?;
+    let latest = stream.snapshot().unwrap()["GameState"].clone();
+    assert_eq!(unordered(client), unordered(latest));
+    //The opponent's hand is still hidden, under the same id as before
+    assert!(!diff.cards.is_empty());
+    assert!(diff.cards.values().all(|card| card.name == "Plains"));
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn skipped_versions_are_detected() -> Result<()> {
+    let (game, _) = hand_battlefield_setup(vec!["Murder"], vec![], None).await?;
+    let viewer = Viewer::Player(game.active_player);
+    let mut stream = StateStream::default();
+    let mut client =
+        stream.update(serde_json::to_value(game.state_for(viewer))?)["GameState"].clone();
+    stream.update(serde_json::to_value(game.state_for(viewer))?);
+    let third = stream.update(serde_json::to_value(game.state_for(viewer))?);
+    let diff: StateDiff = serde_json::from_value(third["StateDiff"].clone())?;
+    assert!(diff.apply(&mut client).is_err());
+    assert_eq!(client["version"], Value::from(1));
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn hidden_ids_are_stable() -> Result<()> {
+    let (game, _) = hand_battlefield_setup(vec!["Murder", "Plains"], vec![], None).await?;
+    let opponent = game.opponents(game.active_player)[0];
+    let hand_ids = || {
+        let state = game.state_for(Viewer::Player(opponent));
+        let hand = state.players[&game.active_player].hand.clone();
+        assert!(hand.iter().all(|id| state.cards[id].name.is_empty()));
+        hand
+    };
+    assert_eq!(hand_ids(), hand_ids());
+    Ok(())
+}
+#[test_log::test(tokio::test)]
+async fn shuffling_deals_new_hidden_ids() -> Result<()> {
+    let (mut game, hand) =
+        hand_battlefield_setup(vec!["Murder", "Murder"], vec!["Plains"], None).await?;
+    let player = game.active_player;
+    let viewer = Viewer::Player(game.opponents(player)[0]);
+    let plains = game.battlefield.iter().copied().next().unwrap();
+    let _ = game
+        .move_zones(hand.into_iter().collect(), Zone::Hand, Zone::Library)
+        .await;
+    let before = game.state_for(viewer).players[&player].library.clone();
+    let _ = game
+        .move_zones(vec![plains], Zone::Battlefield, Zone::Library)
+        .await;
+    let moved = game.state_for(viewer).players[&player].library.clone();
+    //The Plains was public, so the one new hidden id is it
+    let tracked: Vec<_> = moved.iter().filter(|id| !before.contains(id)).collect();
+    assert_eq!(tracked.len(), 1);
+    assert!(!moved.contains(&plains));
+    game.shuffle(player);
+    let state = game.state_for(viewer);
+    let shuffled = &state.players[&player].library;
+    assert_eq!(shuffled.len(), 3);
+    assert!(!shuffled.contains(tracked[0]));
+    assert!(!state.cards.contains_key(tracked[0]));
+    assert!(shuffled.iter().all(|id| !moved.contains(id)));
+    Ok(())
+}
@@ -0,0 +1,67 @@
+use anyhow::Result;
+use carddb::carddb::CardDB;
+use carddb::coverage::{coverage, Coverage};
+use std::collections::BTreeMap;
+
+const SHOWN_SPANS: usize = 25;
+
+//Tries to spawn every card in the oracle database and reports how much of it
+//the parser handles. Given a path, the full report is also written there as
+//JSON, which can be diffed against the report from another commit.
+//The database can be given with --cards
+fn main() -> Result<()> {
+    let (db, args) = CardDB::from_args(std::env::args().skip(1))?;
+    let db: &'static CardDB = Box::leak(Box::new(db));
+    let json = args.into_iter().next();
+    //Cards that make the parser panic are reported, not printed as they happen
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let report = coverage(db);
+    std::panic::set_hook(hook);
+
+    println!(
+        "{} of {} cards spawn ({:.1}%)",
+        report.total.spawned,
+        report.total.cards,
+        report.total.percent()
+    );
+    print_table("type", &report.by_type);
+    print_table("set", &report.by_set);
+    println!();
+    println!("{:<12} {:>8}", "failure", "cards");
+    for (kind, cards) in &report.by_kind {
+        println!("{:<12} {:>8}", kind, cards);
+    }
+    println!();
+    println!("{:>8}  {}", "cards", "stopped at");
+    for stopped in report.by_span.iter().take(SHOWN_SPANS) {
+        println!(
+            "{:>8}  {:<40} e.g. {}",
+            stopped.cards,
+            stopped.span,
+            stopped.examples.join(", ")
+        );
+    }
+    if let Some(path) = json {
+        std::fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+        println!();
+        println!("Wrote the full report to {}", path);
+    }
+    Ok(())
+}
+fn print_table(label: &str, rows: &BTreeMap<String, Coverage>) {
+    println!();
+    println!(
+        "{:<20} {:>8} {:>8} {:>8}",
+        label, "cards", "spawn", "percent"
+    );
+    for (name, coverage) in rows {
+        println!(
+            "{:<20} {:>8} {:>8} {:>7.1}%",
+            name,
+            coverage.cards,
+            coverage.spawned,
+            coverage.percent()
+        );
+    }
+}
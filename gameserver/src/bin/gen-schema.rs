@@ -0,0 +1,17 @@
+use game::protocol::{client_schema, server_schema, typescript};
+use std::path::PathBuf;
+
+//Writes the protocol's JSON schemas and TypeScript definitions for the ui.
+//Takes the ui directory as an optional argument
+fn main() -> std::io::Result<()> {
+    let ui = std::env::args()
+        .nth(1)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../ui"));
+    let pretty = |schema| serde_json::to_string_pretty(&schema).expect("schemas serialize");
+    std::fs::write(ui.join("rust_types.json"), pretty(server_schema()))?;
+    std::fs::write(ui.join("client_types.json"), pretty(client_schema()))?;
+    std::fs::write(ui.join("src/protocol.ts"), typescript())?;
+    println!("Wrote protocol types to {}", ui.display());
+    Ok(())
+}
@@ -0,0 +1,22 @@
+use anyhow::Result;
+use carddb::carddb::CardDB;
+use game::replay::{replay, Replay};
+use std::path::PathBuf;
+
+//Plays back a saved game and checks it ends the way it was recorded.
+//Takes the paths of the replay files to check, and the database with --cards
+#[tokio::main]
+async fn main() -> Result<()> {
+    let (db, args) = CardDB::from_args(std::env::args().skip(1))?;
+    let db: &'static CardDB = Box::leak(Box::new(db));
+    let paths: Vec<PathBuf> = args.into_iter().map(PathBuf::from).collect();
+    if paths.is_empty() {
+        anyhow::bail!("usage: replay [--cards <path>] <replay file>...");
+    }
+    for path in paths {
+        let recorded = Replay::load(&path)?;
+        let outcome = replay(db, &recorded).await?;
+        println!("{}: replayed to {:?}", path.display(), outcome);
+    }
+    Ok(())
+}
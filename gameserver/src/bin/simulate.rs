@@ -0,0 +1,122 @@
+use anyhow::Result;
+use carddb::carddb::CardDB;
+use carddb::decklist::DeckSubmission;
+use common::format::Format;
+use game::simulate::{Contestant, Policy, Simulation};
+use std::path::Path;
+
+const USAGE: &str = "usage: simulate <decklist>[:policy] <decklist>[:policy] \
+[--games n] [--seed n] [--max-turns n] [--parallel n] [--format casual|standard|limited|commander] \
+[--cards path]";
+
+//Plays two decks against each other with no one connected and reports how
+//they did. Each decklist can be followed by the policy that plays it, random
+//unless it's given. Crashed games are listed by seed, run again with
+//--seed and --games 1 to reproduce one
+#[tokio::main]
+async fn main() -> Result<()> {
+    let (db, args) = CardDB::from_args(std::env::args().skip(1))?;
+    let db: &'static CardDB = Box::leak(Box::new(db));
+    let mut args = args.into_iter();
+    let mut decks = Vec::new();
+    let mut options = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg.starts_with("--") {
+            let Some(value) = args.next() else {
+                anyhow::bail!("{} needs a value\n{}", arg, USAGE);
+            };
+            options.push((arg, value));
+        } else {
+            decks.push(arg);
+        }
+    }
+    if decks.len() != 2 {
+        anyhow::bail!(USAGE);
+    }
+    let mut contestants: Vec<Contestant> = Vec::new();
+    for deck in decks {
+        let (path, policy) = match deck.rsplit_once(':') {
+            Some((path, policy)) => (path.to_owned(), policy.parse()?),
+            None => (deck, Policy::Random),
+        };
+        let text = std::fs::read_to_string(&path)?;
+        let deck = match DeckSubmission::Text(text).resolve(db) {
+            Ok(deck) => deck,
+            Err(err) => anyhow::bail!("{} couldn't be read: {:?}", path, err),
+        };
+        let mut name = Path::new(&path)
+            .file_stem()
+            .map_or(path.clone(), |stem| stem.to_string_lossy().into_owned());
+        if contestants.iter().any(|contestant| contestant.name == name) {
+            name += " 2";
+        }
+        contestants.push(Contestant { name, deck, policy });
+    }
+    let mut simulation = Simulation::new(contestants, 100);
+    for (option, value) in options {
+        match option.as_str() {
+            "--games" => simulation.games = value.parse()?,
+            "--seed" => simulation.seed = value.parse()?,
+            "--max-turns" => simulation.max_turns = value.parse()?,
+            "--parallel" => simulation.parallel = value.parse()?,
+            "--format" => {
+                simulation.format = match value.as_str() {
+                    "casual" => Format::Casual,
+                    "standard" => Format::Standard,
+                    "limited" => Format::Limited,
+                    "commander" => Format::Commander,
+                    _ => anyhow::bail!("{} isn't a format\n{}", value, USAGE),
+                }
+            }
+            _ => anyhow::bail!("{} isn't an option\n{}", option, USAGE),
+        }
+    }
+    let players: Vec<String> = simulation
+        .contestants
+        .iter()
+        .map(|contestant| format!("{} ({})", contestant.name, contestant.policy))
+        .collect();
+    println!(
+        "Simulating {} games of {}",
+        simulation.games,
+        players.join(" against ")
+    );
+    let report = simulation.run(db).await;
+
+    println!();
+    println!("{} games, {} crashed", report.games, report.crashes.len());
+    for (name, wins) in &report.wins {
+        println!(
+            "{:<30} {:>6} wins {:>7.1}%",
+            name,
+            wins,
+            report.win_rate(name) * 100.0
+        );
+    }
+    println!("{:<30} {:>6}", "ties", report.ties);
+    println!("{:<30} {:>6}", "unfinished", report.unfinished);
+    println!("average length {:.1} turns", report.average_turns());
+    println!();
+    println!(
+        "{:<40} {:>6} {:>6} {:>8} {:>6}",
+        "card", "games", "wins", "win rate", "casts"
+    );
+    for card in &report.cards {
+        println!(
+            "{:<40} {:>6} {:>6} {:>7.1}% {:>6}",
+            card.card,
+            card.games,
+            card.wins,
+            card.wins as f64 / card.games.max(1) as f64 * 100.0,
+            card.casts
+        );
+    }
+    if !report.crashes.is_empty() {
+        println!();
+        println!("crashes:");
+        for crash in &report.crashes {
+            println!("seed {}: {}", crash.seed, crash.message);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,27 @@
+use anyhow::Result;
+use game::record::{win_rates, RecordSink};
+use std::path::PathBuf;
+
+//Reports how often decks playing each card won, from the games the server
+//recorded. Takes the record directory, the one GAME_RECORD_DIR was set to
+fn main() -> Result<()> {
+    let Some(dir) = std::env::args().nth(1).map(PathBuf::from) else {
+        anyhow::bail!("usage: win-rates <record directory>");
+    };
+    let records = RecordSink::new(dir).load_all()?;
+    println!("{} games", records.len());
+    println!(
+        "{:<40} {:>6} {:>6} {:>8}",
+        "card", "games", "wins", "win rate"
+    );
+    for rate in win_rates(&records) {
+        println!(
+            "{:<40} {:>6} {:>6} {:>7.1}%",
+            rate.card,
+            rate.games,
+            rate.wins,
+            rate.rate() * 100.0
+        );
+    }
+    Ok(())
+}
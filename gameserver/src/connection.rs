@@ -0,0 +1,347 @@
+use async_trait::async_trait;
+use common::actions::Action;
+use common::entities::{CardId, TargetId};
+use common::hashset_obj::HashSetObj;
+use futures::{SinkExt, StreamExt};
+use game::client_message::{
+    Ask, AskPair, AskResponse, AskSelectN, ClientMessage, ClientRequest, GameState, RequestId,
+};
+use game::decision::{Answer, AskContext, DecisionProvider, Gone};
+use game::log::LogMessage;
+use game::protocol::{decode, Encoding, Frame};
+use game::state_diff::StateStream;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Mutex;
+use warp::ws::{Message, WebSocket};
+
+pub const DEFAULT_RECONNECT_GRACE: Duration = Duration::from_secs(60);
+
+pub fn to_message(frame: Frame) -> Message {
+    match frame {
+        Frame::Text(text) => Message::text(text),
+        Frame::Binary(bytes) => Message::binary(bytes),
+    }
+}
+//Pings, pongs and closes aren't frames the game reads
+pub fn to_frame(message: &Message) -> Option<Frame> {
+    if message.is_text() {
+        Some(Frame::Text(message.to_str().ok()?.to_owned()))
+    } else if message.is_binary() {
+        Some(Frame::Binary(message.as_bytes().to_vec()))
+    } else {
+        None
+    }
+}
+
+//Sends a spectator the states and log messages meant for them until they leave
+pub async fn spectate(
+    mut socket: WebSocket,
+    encoding: Encoding,
+    mut states: UnboundedReceiver<Value>,
+) {
+    while let Some(state) = states.recv().await {
+        let Ok(frame) = encoding.encode(&state) else {
+            break;
+        };
+        if socket.send(to_message(frame)).await.is_err() {
+            break;
+        }
+    }
+}
+
+//Close frames can only carry a short reason
+const MAX_CLOSE_REASON: usize = 123;
+fn close_reason(reason: &str) -> String {
+    let mut end = reason.len().min(MAX_CLOSE_REASON);
+    while !reason.is_char_boundary(end) {
+        end -= 1;
+    }
+    reason[..end].to_owned()
+}
+
+//The socket a player is currently connected with, along with
+//what a reconnecting client needs to be sent to catch back up
+struct Connection {
+    socket: Option<WebSocket>, //None while the player is disconnected
+    encoding: Encoding,
+    reconnects: UnboundedReceiver<(WebSocket, Encoding)>,
+    states: StateStream,
+    pending_ask: Option<Value>,
+}
+enum Incoming {
+    Message(Message),
+    Closed,
+    Reconnect(WebSocket, Encoding),
+}
+impl Connection {
+    //A failed send isn't an error, the next receive waits for the player to reconnect
+    async fn send(&mut self, message: &Value) {
+        let Some(socket) = &mut self.socket else {
+            return;
+        };
+        let frame = match self.encoding.encode(message) {
+            Ok(frame) => frame,
+            Err(err) => {
+                log::error!("Failed to encode message: {}", err);
+                return;
+            }
+        };
+        if socket.send(to_message(frame)).await.is_err() {
+            log::warn!("Connection broke on send");
+        }
+    }
+    async fn send_message(&mut self, message: &ClientMessage) {
+        match serde_json::to_value(message) {
+            Ok(message) => self.send(&message).await,
+            Err(err) => log::error!("Failed to serialize message: {}", err),
+        }
+    }
+    async fn resync(&mut self) {
+        if let Some(snapshot) = self.states.snapshot() {
+            self.send(&snapshot).await;
+        }
+    }
+    //The new socket may have negotiated a different encoding than the old one
+    async fn replace(&mut self, socket: WebSocket, encoding: Encoding) {
+        self.socket = Some(socket);
+        self.encoding = encoding;
+        self.resync().await;
+        if let Some(ask) = self.pending_ask.clone() {
+            self.send(&ask).await;
+        }
+    }
+    async fn take_reconnects(&mut self) {
+        while let Ok((socket, encoding)) = self.reconnects.try_recv() {
+            self.replace(socket, encoding).await;
+        }
+    }
+    //Waits for the answer to request id. Answers to earlier asks are stale and
+    //dropped, ones that can't be read are rejected and the ask stays open
+    async fn receive(&mut self, id: RequestId, grace: Duration) -> Answer<Value> {
+        self.take_reconnects().await;
+        loop {
+            let Connection {
+                socket, reconnects, ..
+            } = self;
+            let incoming = match socket {
+                None => Incoming::Closed,
+                Some(socket) => tokio::select! {
+                    recieved = socket.next() => match recieved {
+                        Some(Ok(msg)) if !msg.is_close() => Incoming::Message(msg),
+                        _ => Incoming::Closed,
+                    },
+                    Some((socket, encoding)) = reconnects.recv() => {
+                        Incoming::Reconnect(socket, encoding)
+                    }
+                },
+            };
+            let message = match incoming {
+                Incoming::Message(msg) => msg,
+                Incoming::Closed => {
+                    self.wait_for_reconnect(grace).await?;
+                    continue;
+                }
+                Incoming::Reconnect(socket, encoding) => {
+                    self.replace(socket, encoding).await;
+                    continue;
+                }
+            };
+            let Some(frame) = to_frame(&message) else {
+                continue;
+            };
+            let message: Value = match decode(&frame) {
+                Ok(message) => message,
+                Err(err) => {
+                    let reason = format!("malformed response: {}", err);
+                    self.reject(id, reason).await;
+                    continue;
+                }
+            };
+            if let Ok(ClientRequest::Resync) = serde_json::from_value(message.clone()) {
+                self.resync().await;
+                continue;
+            }
+            match serde_json::from_value::<AskResponse<Value>>(message) {
+                Ok(response) if response.id == id => return Ok(response.response),
+                Ok(response) => log::warn!("dropped stale response to {}", response.id),
+                Err(err) => {
+                    let reason = format!("malformed response: {}", err);
+                    self.reject(id, reason).await;
+                }
+            }
+        }
+    }
+    async fn wait_for_reconnect(&mut self, grace: Duration) -> Answer<()> {
+        self.socket = None;
+        log::info!("Waiting {:?} for a reconnect", grace);
+        match tokio::time::timeout(grace, self.reconnects.recv()).await {
+            Ok(Some((socket, encoding))) => {
+                self.replace(socket, encoding).await;
+                Ok(())
+            }
+            _ => {
+                log::info!("Player didn't reconnect in time");
+                Err(Gone)
+            }
+        }
+    }
+    //Tells the client why its answer was rejected, the ask stays open
+    async fn reject(&mut self, id: RequestId, reason: String) {
+        self.send_message(&ClientMessage::InvalidResponse { id, reason })
+            .await;
+    }
+}
+
+//A player on the other end of a websocket. Clones share the connection, so
+//the server can keep one to hand the player a new socket if theirs drops
+#[derive(Clone)]
+pub struct WebSocketPlayer {
+    connection: Arc<Mutex<Connection>>,
+    reconnects: UnboundedSender<(WebSocket, Encoding)>,
+    grace: Duration, //How long a disconnected player has to reconnect before forfeiting
+}
+impl WebSocketPlayer {
+    pub fn new(socket: WebSocket, encoding: Encoding, grace: Duration) -> Self {
+        WebSocketPlayer::connect(Some(socket), encoding, grace)
+    }
+    //Waits for the player to reconnect, like after a restart
+    pub fn disconnected(grace: Duration) -> Self {
+        WebSocketPlayer::connect(None, Encoding::Json, grace)
+    }
+    fn connect(socket: Option<WebSocket>, encoding: Encoding, grace: Duration) -> Self {
+        let (sender, reconnects) = unbounded_channel();
+        WebSocketPlayer {
+            connection: Arc::new(Mutex::new(Connection {
+                socket,
+                encoding,
+                reconnects,
+                states: StateStream::default(),
+                pending_ask: None,
+            })),
+            reconnects: sender,
+            grace,
+        }
+    }
+    //Takes over the connection with a new socket, which is sent
+    //the latest game state and whatever question is still unanswered
+    pub async fn reconnect(&self, socket: WebSocket, encoding: Encoding) {
+        match self.connection.try_lock() {
+            Ok(mut connection) => connection.replace(socket, encoding).await,
+            //Whoever holds the connection picks the socket up
+            Err(_) => {
+                let _ = self.reconnects.send((socket, encoding));
+            }
+        }
+    }
+    pub async fn send(&self, message: &ClientMessage) {
+        let mut connection = self.connection.lock().await;
+        connection.take_reconnects().await;
+        connection.send_message(message).await;
+    }
+    //Ends the connection for good, telling the client why
+    pub async fn close(&self, code: u16, reason: &str) {
+        let mut connection = self.connection.lock().await;
+        connection.take_reconnects().await;
+        if let Some(mut socket) = connection.socket.take() {
+            let _ = socket
+                .send(Message::close_with(code, close_reason(reason)))
+                .await;
+        }
+    }
+    async fn ask<T: DeserializeOwned>(&self, id: RequestId, ask: Ask) -> Answer<T> {
+        let mut connection = self.connection.lock().await;
+        connection.take_reconnects().await;
+        match serde_json::to_value(ClientMessage::AskUser { id, ask }) {
+            Ok(ask) => {
+                connection.send(&ask).await;
+                connection.pending_ask = Some(ask);
+            }
+            Err(err) => log::error!("Failed to serialize ask {}: {}", id, err),
+        }
+        loop {
+            let response = connection.receive(id, self.grace).await?;
+            match serde_json::from_value(response) {
+                Ok(response) => return Ok(response),
+                Err(err) => {
+                    let reason = format!("malformed response: {}", err);
+                    connection.reject(id, reason).await;
+                }
+            }
+        }
+    }
+}
+#[async_trait]
+impl DecisionProvider for WebSocketPlayer {
+    //States go out as diffs against the last one this player was sent
+    async fn state(&mut self, state: &GameState) {
+        let state = match serde_json::to_value(state) {
+            Ok(state) => state,
+            Err(err) => {
+                log::error!("Failed to serialize state: {}", err);
+                return;
+            }
+        };
+        let mut connection = self.connection.lock().await;
+        connection.take_reconnects().await;
+        let message = connection.states.update(state);
+        connection.send(&message).await;
+    }
+    async fn log(&mut self, messages: &[LogMessage]) {
+        self.send(&ClientMessage::Log(messages.to_vec())).await;
+    }
+    async fn select_action(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<Action>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.ask(ctx.id, Ask::Action(ask.clone())).await
+    }
+    async fn select_targets(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<TargetId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.ask(ctx.id, Ask::Target(ask.clone())).await
+    }
+    async fn select_attackers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<TargetId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<TargetId>>> {
+        self.ask(ctx.id, Ask::Attackers(ask.clone())).await
+    }
+    async fn select_blockers(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskPair<CardId>,
+    ) -> Answer<HashMap<CardId, HashSetObj<CardId>>> {
+        self.ask(ctx.id, Ask::Blockers(ask.clone())).await
+    }
+    async fn discard_to_hand_size(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.ask(ctx.id, Ask::DiscardToHandSize(ask.clone())).await
+    }
+    async fn command_zone(
+        &mut self,
+        ctx: AskContext<'_>,
+        ask: &AskSelectN<CardId>,
+    ) -> Answer<HashSetObj<usize>> {
+        self.ask(ctx.id, Ask::CommandZone(ask.clone())).await
+    }
+    async fn rejected(&mut self, id: RequestId, reason: &str) {
+        let mut connection = self.connection.lock().await;
+        connection.reject(id, reason.to_owned()).await;
+    }
+    //The pending question was answered and shouldn't be resent on reconnect
+    async fn accepted(&mut self, _id: RequestId) {
+        self.connection.lock().await.pending_ask = None;
+    }
+}
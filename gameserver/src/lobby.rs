@@ -0,0 +1,326 @@
+use crate::connection::DEFAULT_RECONNECT_GRACE;
+use carddb::decklist::ResolvedDeck;
+use common::format::Format;
+use game::protocol::Encoding;
+use game::spectator::{SpectatorView, Spectators};
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use warp::http::StatusCode;
+use warp::ws::WebSocket;
+
+pub const MIN_PLAYERS: usize = 2;
+pub const MAX_PLAYERS: usize = 8;
+//Omniscient views are held back at least this long, so a player
+//who gets hold of one can't use it to help them in the moment
+pub const MIN_OMNISCIENT_DELAY_SECS: u64 = 30;
+const QUICK_MATCH_NAME: &str = "Quick match";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RoomId(u64);
+
+impl From<u64> for RoomId {
+    fn from(x: u64) -> Self {
+        Self(x)
+    }
+}
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RoomSettings {
+    pub name: String,
+    pub players: usize,
+    #[serde(default)]
+    pub format: Format,
+    #[serde(default)]
+    pub password: Option<String>,
+    //How long a dropped player has to reconnect before forfeiting
+    #[serde(default = "default_reconnect_grace")]
+    pub reconnect_grace_secs: u64,
+    //Lets spectators see every card, delayed by this many seconds.
+    //Left out, spectators only get the public view
+    #[serde(default)]
+    pub omniscient_delay_secs: Option<u64>,
+    //Seats taken by computer players, the game starts once the rest are filled
+    #[serde(default)]
+    pub ai_players: usize,
+    //Lets whoever created the room judge it. Issued by the lobby,
+    //whatever a client sends here is replaced
+    #[serde(default)]
+    pub judge_token: Option<String>,
+}
+fn default_reconnect_grace() -> u64 {
+    DEFAULT_RECONNECT_GRACE.as_secs()
+}
+//What clients get to see about a room, the password is never sent back
+#[derive(Clone, Debug, Serialize)]
+pub struct RoomInfo {
+    pub id: RoomId,
+    pub name: String,
+    pub players: usize,
+    pub seated: Vec<String>,
+    pub ai_players: usize,
+    pub format: Format,
+    pub has_password: bool,
+}
+//Only the room's creator is sent its judge token
+#[derive(Clone, Debug, Serialize)]
+pub struct CreatedRoom {
+    #[serde(flatten)]
+    pub info: RoomInfo,
+    pub judge_token: Option<String>,
+}
+//Where a seated player's connection is sent once the game starts. Until then
+//the connection stays with the player, so it's noticed if they leave
+pub type SocketClaim = oneshot::Sender<WebSocket>;
+pub struct Seat {
+    pub name: String,
+    pub deck: ResolvedDeck,
+    pub encoding: Encoding,
+    pub claim: oneshot::Sender<SocketClaim>,
+}
+pub struct Room {
+    pub id: RoomId,
+    pub settings: RoomSettings,
+    pub seats: Vec<Seat>,
+    //Judges watch from the lobby on, so the game is launched with them already watching
+    pub spectators: Spectators,
+    judge_connected: bool,
+    quick_match: bool, //Made by the lobby for /gamesetup, clients can't make these
+}
+impl Room {
+    pub fn info(&self) -> RoomInfo {
+        RoomInfo {
+            id: self.id,
+            name: self.settings.name.clone(),
+            players: self.settings.players,
+            seated: self.seats.iter().map(|seat| seat.name.clone()).collect(),
+            ai_players: self.settings.ai_players,
+            format: self.settings.format,
+            has_password: self.settings.password.is_some(),
+        }
+    }
+    fn is_full(&self) -> bool {
+        self.seats.len() + self.settings.ai_players >= self.settings.players
+    }
+    //The settings the game is launched with. The judge token only stays valid
+    //if the judge connected while the room was open, otherwise it could be used
+    //by someone who took a seat to watch their own game
+    pub fn launch_settings(&self) -> RoomSettings {
+        let mut settings = self.settings.clone();
+        if !self.judge_connected {
+            settings.judge_token = None;
+        }
+        settings
+    }
+    //Players who don't pick a name are named after their seat
+    pub fn default_name(&self) -> String {
+        format!("p{}", self.seats.len())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LobbyError {
+    RoomNotFound,
+    WrongPassword,
+    NameTaken,
+    EmptyRoomName,
+    InvalidPlayerCount,
+    NoHumanSeats,
+    OmniscientDelayTooShort,
+    NotAJudge,
+}
+impl LobbyError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::RoomNotFound => StatusCode::NOT_FOUND,
+            Self::WrongPassword | Self::NotAJudge => StatusCode::FORBIDDEN,
+            Self::NameTaken => StatusCode::CONFLICT,
+            Self::EmptyRoomName
+            | Self::InvalidPlayerCount
+            | Self::NoHumanSeats
+            | Self::OmniscientDelayTooShort => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+impl fmt::Display for LobbyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::RoomNotFound => write!(f, "room not found"),
+            Self::WrongPassword => write!(f, "wrong password"),
+            Self::NameTaken => write!(f, "a player with that name is already seated"),
+            Self::EmptyRoomName => write!(f, "rooms must have a name"),
+            Self::InvalidPlayerCount => write!(
+                f,
+                "rooms hold between {} and {} players",
+                MIN_PLAYERS, MAX_PLAYERS
+            ),
+            Self::NoHumanSeats => write!(f, "at least one seat has to be left for a person"),
+            Self::OmniscientDelayTooShort => write!(
+                f,
+                "omniscient spectators have to be delayed at least {} seconds",
+                MIN_OMNISCIENT_DELAY_SECS
+            ),
+            Self::NotAJudge => write!(f, "judging a room needs its judge token"),
+        }
+    }
+}
+
+//Holds the rooms that are waiting for players. Once a room fills
+//it is removed from the lobby and handed off to be launched
+#[derive(Default)]
+pub struct Lobby {
+    rooms: HashMap<RoomId, Room>,
+    next_id: u64,
+}
+//For session and judge tokens
+pub fn new_token() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(32)
+        .map(char::from)
+        .collect()
+}
+impl Lobby {
+    pub fn create(&mut self, settings: RoomSettings) -> Result<CreatedRoom, LobbyError> {
+        self.open(settings, false)
+    }
+    fn open(
+        &mut self,
+        mut settings: RoomSettings,
+        quick_match: bool,
+    ) -> Result<CreatedRoom, LobbyError> {
+        if settings.name.trim().is_empty() {
+            return Err(LobbyError::EmptyRoomName);
+        }
+        if settings.players < MIN_PLAYERS || settings.players > MAX_PLAYERS {
+            return Err(LobbyError::InvalidPlayerCount);
+        }
+        if settings.ai_players >= settings.players {
+            return Err(LobbyError::NoHumanSeats);
+        }
+        if let Some(delay) = settings.omniscient_delay_secs
+        && delay < MIN_OMNISCIENT_DELAY_SECS {
+            return Err(LobbyError::OmniscientDelayTooShort);
+        }
+        settings.judge_token = settings.omniscient_delay_secs.map(|_| new_token());
+        self.next_id += 1;
+        let id = RoomId(self.next_id);
+        let judge_token = settings.judge_token.clone();
+        let room = Room {
+            id,
+            settings,
+            seats: Vec::new(),
+            spectators: Spectators::default(),
+            judge_connected: false,
+            quick_match,
+        };
+        let info = room.info();
+        self.rooms.insert(id, room);
+        Ok(CreatedRoom { info, judge_token })
+    }
+    pub fn list(&self) -> Vec<RoomInfo> {
+        let mut rooms: Vec<RoomInfo> = self.rooms.values().map(|room| room.info()).collect();
+        rooms.sort_by_key(|room| room.id.0);
+        rooms
+    }
+    pub fn get(&self, id: RoomId) -> Option<&Room> {
+        self.rooms.get(&id)
+    }
+    //Checks if a player could join without taking their socket,
+    //so bad requests can be turned away before the websocket upgrade
+    pub fn check_join(
+        &self,
+        id: RoomId,
+        name: Option<&str>,
+        password: Option<&str>,
+    ) -> Result<(), LobbyError> {
+        let room = self.rooms.get(&id).ok_or(LobbyError::RoomNotFound)?;
+        if let Some(expected) = &room.settings.password
+        && Some(expected.as_str()) != password {
+            return Err(LobbyError::WrongPassword);
+        }
+        if let Some(name) = name
+        && room.seats.iter().any(|seat| seat.name == name) {
+            return Err(LobbyError::NameTaken);
+        }
+        Ok(())
+    }
+    //Checks a judge's token without connecting them, like check_join
+    pub fn check_judge(&self, id: RoomId, token: &str) -> Result<&Room, LobbyError> {
+        let room = self.rooms.get(&id).ok_or(LobbyError::RoomNotFound)?;
+        match &room.settings.judge_token {
+            Some(expected) if expected == token => Ok(room),
+            _ => Err(LobbyError::NotAJudge),
+        }
+    }
+    //Judges connect while the room is still open, on a connection that is only
+    //ever a spectator. Returns the view they get and where to add them
+    pub fn connect_judge(
+        &mut self,
+        id: RoomId,
+        token: &str,
+    ) -> Result<(SpectatorView, Spectators), LobbyError> {
+        self.check_judge(id, token)?;
+        let room = self.rooms.get_mut(&id).expect("checked room exists");
+        let delay = room
+            .settings
+            .omniscient_delay_secs
+            .expect("judged rooms have a delay");
+        room.judge_connected = true;
+        let view = SpectatorView::Omniscient {
+            delay: Duration::from_secs(delay),
+        };
+        Ok((view, room.spectators.clone()))
+    }
+    //Seats a player. If that fills the room, the room is
+    //removed from the lobby and returned so the game can start
+    pub fn join(
+        &mut self,
+        id: RoomId,
+        password: Option<&str>,
+        seat: Seat,
+    ) -> Result<Option<Room>, LobbyError> {
+        self.check_join(id, Some(&seat.name), password)?;
+        let room = self.rooms.get_mut(&id).expect("checked room exists");
+        room.seats.push(seat);
+        if room.is_full() {
+            Ok(self.rooms.remove(&id))
+        } else {
+            Ok(None)
+        }
+    }
+    //Gives up a seat in a room that hasn't started. Rooms
+    //are closed once the last person seated in them leaves
+    pub fn leave(&mut self, id: RoomId, name: &str) {
+        let Some(room) = self.rooms.get_mut(&id) else {
+            return;
+        };
+        room.seats.retain(|seat| seat.name != name);
+        if room.seats.is_empty() {
+            self.rooms.remove(&id);
+        }
+    }
+    //Finds an open quick match room, making one if needed
+    pub fn quick_match(&mut self) -> RoomId {
+        if let Some(room) = self.rooms.values().find(|room| room.quick_match) {
+            return room.id;
+        }
+        let settings = RoomSettings {
+            name: QUICK_MATCH_NAME.to_owned(),
+            players: MIN_PLAYERS,
+            format: Format::default(),
+            password: None,
+            reconnect_grace_secs: default_reconnect_grace(),
+            omniscient_delay_secs: None,
+            ai_players: 0,
+            judge_token: None,
+        };
+        self.open(settings, true)
+            .expect("quick match settings are valid")
+            .info
+            .id
+    }
+}
@@ -2,58 +2,405 @@
 #![feature(const_option)]
 #![feature(let_chains)]
 #![deny(unused_must_use)]
-use crate::write_schema::write_types;
+use crate::connection::{spectate, to_frame, to_message, WebSocketPlayer};
+use crate::lobby::{new_token, Lobby, Room, RoomId, RoomSettings, Seat};
+use crate::registry::{GameId, GameRegistry};
+use crate::saved::{SavedGame, SavedSeat};
+use ::carddb::carddb::ScryfallEntry;
+use ::carddb::deck_validation::validate_deck;
+use ::carddb::decklist::{DeckError, DeckSubmission, ResolvedDeck};
+use ::carddb::query::{CardQuery, CardSummary};
 use anyhow::Result;
 use carddb::carddb;
-use common::entities::PlayerId;
+use common::format::Format;
+use futures::{SinkExt, StreamExt};
+use game::client_message::ClientMessage;
 use game::game::build_game::GameBuilder;
+use game::game::Game;
 use game::player::PlayerCon;
+use game::protocol::Frame;
+use game::protocol::{
+    client_schema, decode, negotiate, server_schema, subprotocol, Encoding, MIN_PROTOCOL_VERSION,
+    PROTOCOL_VERSION,
+};
+use game::record::RecordSink;
+use game::spectator::{Spectator, Spectators};
 use once_cell::sync::OnceCell;
-use std::mem;
+use serde::Deserialize;
+use std::convert::Infallible;
+use std::future::Future;
+use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
-use warp::ws::WebSocket;
-use warp::Filter;
-mod write_schema;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::oneshot;
+use warp::http::{StatusCode, Uri};
+use warp::reply::Response;
+use warp::ws::{Message, WebSocket, Ws};
+use warp::{Filter, Reply};
+mod connection;
+mod lobby;
+mod registry;
+mod saved;
+#[cfg(test)]
+mod tests {
+    mod lobby_tests;
+    mod registry_tests;
+}
 pub static CARDDB: OnceCell<carddb::CardDB> = OnceCell::new();
 
-type Pairing = Arc<Mutex<Option<WebSocket>>>;
+type SharedLobby = Arc<Mutex<Lobby>>;
+type SharedRegistry = Arc<Mutex<GameRegistry>>;
+const SUBPROTOCOL_HEADER: &str = "sec-websocket-protocol";
+const REPLAY_DIR: &str = "replays";
+//Finished games are recorded for analysis into this directory when it's set
+const RECORD_DIR_VAR: &str = "GAME_RECORD_DIR";
+const USAGE: &str = "usage: gameserver [--cards <oracle-cards.json>]";
+const CARD_PAGE: usize = 60;
+const MAX_CARD_PAGE: usize = 500;
+const SUGGESTIONS: usize = 5;
+
+#[derive(Deserialize)]
+struct JoinQuery {
+    name: Option<String>,
+    password: Option<String>,
+}
+#[derive(Deserialize)]
+struct JudgeQuery {
+    token: String, //The judge token the room was created with
+}
+#[derive(Deserialize)]
+struct SpectateQuery {
+    #[serde(default)]
+    omniscient: bool,
+    judge: Option<String>,   //The judge token the room was created with
+    session: Option<String>, //Set by players, who are never shown the omniscient view
+}
+#[derive(Deserialize)]
+struct ReconnectQuery {
+    token: String,
+}
+#[derive(Deserialize)]
+struct CardPage {
+    #[serde(default)]
+    offset: usize,
+    limit: Option<usize>,
+}
+#[derive(Deserialize)]
+struct CardName {
+    name: String,
+    #[serde(default)]
+    fuzzy: bool,
+}
+//A seated player once their game has started
+struct Session {
+    token: String,
+    name: String,
+    deck: ResolvedDeck,
+    con: PlayerCon,
+    socket: Option<WebSocketPlayer>, //None for computer players
+}
 #[tokio::main]
 async fn main() {
-    write_types();
-    let pairer = Pairing::default();
-    let pairer = warp::any().map(move || pairer.clone());
+    env_logger::init();
+    //Includes the parser panics caught while checking which cards spawn
+    std::panic::set_hook(Box::new(|info| log::error!("{}", info)));
+    load_card_db();
+    //Searches for playable cards need every card spawned once
+    tokio::task::spawn_blocking(|| card_db().check_spawnable());
+    let lobby = SharedLobby::default();
+    let lobby = warp::any().map(move || lobby.clone());
+    let registry = SharedRegistry::default();
+    resume_saved_games(&registry);
+    let registry = warp::any().map(move || registry.clone());
     let hello = warp::path!("hello" / String).map(|name| format!("Hello, {}!", name));
     let static_files = warp::path("static").and(warp::fs::dir("static"));
-    let game_setup =
-        warp::path("gamesetup")
-            .and(warp::ws())
-            .and(pairer)
-            .map(|ws: warp::ws::Ws, users| {
-                // This will call our function if the handshake succeeds.
-                ws.on_upgrade(move |socket| user_connected(socket, users))
+    let list_rooms = warp::path!("rooms")
+        .and(warp::get())
+        .and(lobby.clone())
+        .map(|lobby: SharedLobby| warp::reply::json(&lobby.lock().unwrap().list()));
+    let create_room = warp::path!("rooms")
+        .and(warp::post())
+        .and(warp::body::json())
+        .and(lobby.clone())
+        .map(|settings: RoomSettings, lobby: SharedLobby| {
+            match lobby.lock().unwrap().create(settings) {
+                Ok(created) => {
+                    warp::reply::with_status(warp::reply::json(&created), StatusCode::CREATED)
+                        .into_response()
+                }
+                Err(err) => error_reply(err.status(), &err.to_string()),
+            }
+        });
+    let join_room = warp::path!("rooms" / u64 / "join")
+        .and(websocket())
+        .and(warp::query::<JoinQuery>())
+        .and(lobby.clone())
+        .and(registry.clone())
+        .map(
+            |id: u64, ws, protocol, query: JoinQuery, lobby: SharedLobby, registry| {
+                let id = RoomId::from(id);
+                let checked = lobby.lock().unwrap().check_join(
+                    id,
+                    query.name.as_deref(),
+                    query.password.as_deref(),
+                );
+                match checked {
+                    Ok(()) => upgrade(ws, protocol, move |socket, encoding| {
+                        room_connected(socket, encoding, id, query, lobby, registry)
+                    }),
+                    Err(err) => error_reply(err.status(), &err.to_string()),
+                }
+            },
+        );
+    //Judges watch every card from before the game starts. The connection
+    //is only ever a spectator, so the judge can't take a seat with it
+    let judge_room = warp::path!("rooms" / u64 / "judge")
+        .and(websocket())
+        .and(warp::query::<JudgeQuery>())
+        .and(lobby.clone())
+        .map(
+            |id: u64, ws, protocol, query: JudgeQuery, lobby: SharedLobby| {
+                let id = RoomId::from(id);
+                let checked = lobby.lock().unwrap().check_judge(id, &query.token).err();
+                match checked {
+                    None => upgrade(ws, protocol, move |socket, encoding| {
+                        judge_connected(socket, encoding, id, query, lobby)
+                    }),
+                    Some(err) => error_reply(err.status(), &err.to_string()),
+                }
+            },
+        );
+    let list_games = warp::path!("games")
+        .and(warp::get())
+        .and(registry.clone())
+        .map(|registry: SharedRegistry| warp::reply::json(&registry.lock().unwrap().list()));
+    let spectate = warp::path!("games" / u64 / "spectate")
+        .and(websocket())
+        .and(warp::query::<SpectateQuery>())
+        .and(registry.clone())
+        .map(
+            |id: u64, ws, protocol, query: SpectateQuery, registry: SharedRegistry| {
+                let id = GameId::from(id);
+                let view = registry.lock().unwrap().spectate(
+                    id,
+                    query.omniscient,
+                    query.judge.as_deref(),
+                    query.session.as_deref(),
+                );
+                match view {
+                    Ok(view) => upgrade(ws, protocol, move |socket, encoding| async move {
+                        let (spectator, states) = Spectator::channel(view);
+                        registry.lock().unwrap().add_spectator(id, spectator);
+                        spectate(socket, encoding, states).await;
+                    }),
+                    Err(err) => error_reply(err.status(), &err.to_string()),
+                }
+            },
+        );
+    //Takes over a seat in a running game after the player's connection dropped
+    let reconnect = warp::path!("reconnect")
+        .and(websocket())
+        .and(warp::query::<ReconnectQuery>())
+        .and(registry.clone())
+        .map(
+            |ws, protocol, query: ReconnectQuery, registry: SharedRegistry| {
+                let session = registry.lock().unwrap().session(&query.token);
+                match session {
+                    Some(con) => upgrade(ws, protocol, move |socket, encoding| async move {
+                        con.reconnect(socket, encoding).await
+                    }),
+                    None => error_reply(StatusCode::NOT_FOUND, "no running game has that session"),
+                }
+            },
+        );
+    //Joins whatever two player room is open, for clients that don't use the lobby
+    let game_setup = warp::path("gamesetup")
+        .and(websocket())
+        .and(lobby)
+        .and(registry)
+        .map(|ws, protocol, lobby: SharedLobby, registry| {
+            // This will call our function if the handshake succeeds.
+            upgrade(ws, protocol, move |socket, encoding| {
+                let id = lobby.lock().unwrap().quick_match();
+                let query = JoinQuery {
+                    name: None,
+                    password: None,
+                };
+                user_connected(socket, encoding, default_deck(), id, query, lobby, registry)
+            })
+        });
+
+    //Card data for deck builders
+    let search_cards = warp::path!("cards")
+        .and(warp::get())
+        .and(warp::query::<CardQuery>())
+        .and(warp::query::<CardPage>())
+        .and_then(|query: CardQuery, page: CardPage| {
+            blocking(move || {
+                let Some(found) = card_db().search(&query) else {
+                    return still_checking();
+                };
+                let limit = page.limit.unwrap_or(CARD_PAGE).min(MAX_CARD_PAGE);
+                let cards: Vec<CardSummary> = found
+                    .iter()
+                    .skip(page.offset)
+                    .take(limit)
+                    .map(|&entry| CardSummary::from(entry))
+                    .collect();
+                let body = serde_json::json!({ "total": found.len(), "cards": cards });
+                warp::reply::json(&body).into_response()
+            })
+        });
+    let named_card = warp::path!("cards" / "named")
+        .and(warp::get())
+        .and(warp::query::<CardName>())
+        .and_then(|query: CardName| {
+            blocking(move || match find_card(&query) {
+                Ok(entry) => warp::reply::json(&CardSummary::from(entry)).into_response(),
+                Err(reply) => reply,
+            })
+        });
+    let card_image = warp::path!("cards" / "image")
+        .and(warp::get())
+        .and(warp::query::<CardName>())
+        .and_then(|query: CardName| {
+            blocking(move || {
+                let entry = match find_card(&query) {
+                    Ok(entry) => entry,
+                    Err(reply) => return reply,
+                };
+                match CardSummary::from(entry).image.map(|url| url.parse::<Uri>()) {
+                    Some(Ok(uri)) => warp::redirect::temporary(uri).into_response(),
+                    _ => error_reply(StatusCode::NOT_FOUND, "the card has no image"),
+                }
+            })
+        });
+    let spawnable_cards =
+        warp::path!("cards" / "spawnable")
+            .and(warp::get())
+            .map(|| match card_db().spawnable() {
+                Some(names) => warp::reply::json(&names).into_response(),
+                None => still_checking(),
             });
 
-    warp::serve(hello.or(static_files).or(game_setup))
-        .run(([127, 0, 0, 1], 3030))
-        .await;
+    let protocol = warp::path!("protocol").and(warp::get()).map(|| {
+        warp::reply::json(&serde_json::json!({
+            "version": PROTOCOL_VERSION,
+            "min_version": MIN_PROTOCOL_VERSION,
+            "server": server_schema(),
+            "client": client_schema(),
+        }))
+    });
+
+    warp::serve(
+        hello
+            .or(static_files)
+            .or(protocol)
+            .or(list_rooms)
+            .or(create_room)
+            .or(join_room)
+            .or(judge_room)
+            .or(list_games)
+            .or(spectate)
+            .or(reconnect)
+            .or(search_cards)
+            .or(named_card)
+            .or(card_image)
+            .or(spawnable_cards)
+            .or(game_setup),
+    )
+    .run(([127, 0, 0, 1], 3030))
+    .await;
+}
+
+//A websocket handshake along with the subprotocols the client offered
+fn websocket() -> impl Filter<Extract = (Ws, Option<String>), Error = warp::Rejection> + Clone {
+    warp::ws().and(warp::header::optional::<String>(SUBPROTOCOL_HEADER))
+}
+//Upgrades the connection once both sides agree on a protocol version,
+//the socket is handed over along with the encoding the client asked for
+fn upgrade<F, U>(ws: Ws, offered: Option<String>, on_upgrade: F) -> Response
+where
+    F: FnOnce(WebSocket, Encoding) -> U + Send + 'static,
+    U: Future<Output = ()> + Send + 'static,
+{
+    match negotiate(offered.as_deref()) {
+        Ok(Some(negotiated)) => warp::reply::with_header(
+            ws.on_upgrade(move |socket| on_upgrade(socket, negotiated.encoding)),
+            SUBPROTOCOL_HEADER,
+            subprotocol(negotiated.version, negotiated.encoding),
+        )
+        .into_response(),
+        Ok(None) => ws
+            .on_upgrade(|socket| on_upgrade(socket, Encoding::Json))
+            .into_response(),
+        Err(err) => error_reply(StatusCode::BAD_REQUEST, &err.to_string()),
+    }
+}
+fn error_reply(status: StatusCode, message: &str) -> Response {
+    let body = serde_json::json!({ "error": message });
+    warp::reply::with_status(warp::reply::json(&body), status).into_response()
 }
 
-async fn user_connected(ws1: WebSocket, pair: Pairing) {
-    let mut state = pair.lock().unwrap();
-    let mut current = None;
-    mem::swap(&mut *state, &mut current);
-    current = match current {
-        None => Some(ws1),
-        Some(ws2) => {
-            tokio::task::spawn(launch_game(vec![ws1, ws2]));
-            None
+//Card queries scan the whole database, so they're kept off the workers
+//that run games. A query that panics gets a 500
+async fn blocking<F>(query: F) -> Result<Response, Infallible>
+where
+    F: FnOnce() -> Response + Send + 'static,
+{
+    Ok(tokio::task::spawn_blocking(query)
+        .await
+        .unwrap_or_else(|_| error_reply(StatusCode::INTERNAL_SERVER_ERROR, "the query failed")))
+}
+//Until every card has been spawned once at boot
+fn still_checking() -> Response {
+    error_reply(
+        StatusCode::SERVICE_UNAVAILABLE,
+        "still checking which cards can be played",
+    )
+}
+pub fn card_db() -> &'static carddb::CardDB {
+    CARDDB.get_or_init(|| carddb::CardDB::new())
+}
+//The card a client asked for by name, or a 404 suggesting names close to it
+fn find_card(query: &CardName) -> Result<&'static ScryfallEntry, Response> {
+    let db = card_db();
+    let found = if query.fuzzy {
+        db.fuzzy(&query.name, 1).pop()
+    } else {
+        db.find(&query.name)
+    };
+    found.ok_or_else(|| {
+        let suggestions: Vec<&str> = (db.fuzzy(&query.name, SUGGESTIONS).iter())
+            .map(|entry| &*entry.name)
+            .collect();
+        let body = serde_json::json!({
+            "error": format!("no card named {}", query.name),
+            "suggestions": suggestions,
+        });
+        warp::reply::with_status(warp::reply::json(&body), StatusCode::NOT_FOUND).into_response()
+    })
+}
+//Loads the database before taking connections, from --cards if it's given.
+//Otherwise it's found the way CardDB::new finds it
+fn load_card_db() {
+    let mut args = std::env::args().skip(1);
+    let path = match (args.next().as_deref(), args.next(), args.next()) {
+        (None, _, _) => None,
+        (Some("--cards"), Some(path), None) => Some(PathBuf::from(path)),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
         }
     };
-    mem::swap(&mut *state, &mut current);
+    if let Some(path) = path {
+        let db = carddb::CardDB::from_path(&path)
+            .unwrap_or_else(|err| panic!("Couldn't read {}: {}", path.display(), err));
+        let _ = CARDDB.set(db);
+    }
+    card_db();
 }
-async fn launch_game(sockets: Vec<WebSocket>) -> Result<()> {
-    let db: &carddb::CardDB = CARDDB.get_or_init(|| carddb::CardDB::new());
-    let mut gamebuild = GameBuilder::new();
+fn default_deck() -> ResolvedDeck {
     let mut deck = Vec::new();
     for _ in 0..10 {
         deck.push("Anointed Chorister");
@@ -64,16 +411,304 @@ async fn launch_game(sockets: Vec<WebSocket>) -> Result<()> {
     for _ in 0..30 {
         deck.push("Plains");
     }
-    sockets
-        .into_iter()
-        .enumerate()
-        .map(|(i, socket)| {
-            gamebuild.add_player(&format!("p{}", i), &db, &deck, PlayerCon::new(socket))
-        })
-        .collect::<Result<Vec<PlayerId>>>()?;
-    let mut game = gamebuild.build(&db)?;
+    ResolvedDeck {
+        commander: None,
+        cards: deck,
+    }
+}
+//The first message sent after joining a room is the player's deck, either
+//encoded like any other message or as plain decklist text. Rejected decks are sent
+//back with the reasons and the player can try again. Returns None if the socket closes first
+async fn receive_deck(
+    socket: &mut WebSocket,
+    encoding: Encoding,
+    format: Format,
+) -> Option<ResolvedDeck> {
+    while let Some(Ok(message)) = socket.next().await {
+        let Some(frame) = to_frame(&message) else {
+            continue;
+        };
+        let submission = match (decode(&frame), frame) {
+            (Ok(submission), _) => submission,
+            (Err(_), Frame::Text(text)) => DeckSubmission::Text(text),
+            (Err(_), Frame::Binary(_)) => continue,
+        };
+        let deck = submission.resolve(card_db()).and_then(|deck| {
+            validate_deck(card_db(), format, &deck.cards, deck.commander)
+                .map(|()| deck)
+                .map_err(DeckError::Illegal)
+        });
+        match deck {
+            Ok(deck) => return Some(deck),
+            Err(err) => {
+                let rejected = encoding
+                    .encode(&ClientMessage::DeckRejected(err))
+                    .expect("deck errors serialize");
+                socket.send(to_message(rejected)).await.ok()?;
+            }
+        }
+    }
+    None
+}
+async fn room_connected(
+    mut socket: WebSocket,
+    encoding: Encoding,
+    id: RoomId,
+    query: JoinQuery,
+    lobby: SharedLobby,
+    registry: SharedRegistry,
+) {
+    let format = match lobby.lock().unwrap().get(id) {
+        Some(room) => room.settings.format,
+        None => return,
+    };
+    if let Some(deck) = receive_deck(&mut socket, encoding, format).await {
+        user_connected(socket, encoding, deck, id, query, lobby, registry).await;
+    }
+}
+async fn user_connected(
+    mut socket: WebSocket,
+    encoding: Encoding,
+    deck: ResolvedDeck,
+    id: RoomId,
+    query: JoinQuery,
+    lobby: SharedLobby,
+    registry: SharedRegistry,
+) {
+    let (claim, claimed) = oneshot::channel();
+    let (name, joined) = {
+        let mut lobby = lobby.lock().unwrap();
+        let name = match (query.name, lobby.get(id)) {
+            (Some(name), _) => name,
+            (None, Some(room)) => room.default_name(),
+            (None, None) => String::new(),
+        };
+        let seat = Seat {
+            name: name.clone(),
+            deck,
+            encoding,
+            claim,
+        };
+        (name, lobby.join(id, query.password.as_deref(), seat))
+    };
+    match joined {
+        Ok(Some(room)) => {
+            tokio::task::spawn(start_game(room, registry));
+        }
+        Ok(None) => {}
+        //The room filled or closed between the handshake and now
+        Err(err) => {
+            let _ = socket
+                .send(Message::close_with(1008u16, err.to_string()))
+                .await;
+            return;
+        }
+    }
+    //Waits for the room to fill, giving up the seat if the player leaves first
+    let started = tokio::select! {
+        started = claimed => started.ok(),
+        () = closed(&mut socket) => None,
+    };
+    match started {
+        Some(claim) => {
+            let _ = claim.send(socket);
+        }
+        None => lobby.lock().unwrap().leave(id, &name),
+    }
+}
+//Reads from a socket until it closes, anything sent is dropped
+async fn closed(socket: &mut WebSocket) {
+    while let Some(Ok(_)) = socket.next().await {}
+}
+async fn judge_connected(
+    mut socket: WebSocket,
+    encoding: Encoding,
+    id: RoomId,
+    query: JudgeQuery,
+    lobby: SharedLobby,
+) {
+    let connected = lobby.lock().unwrap().connect_judge(id, &query.token);
+    match connected {
+        Ok((view, spectators)) => {
+            let (spectator, states) = Spectator::channel(view);
+            spectators.add(spectator);
+            spectate(socket, encoding, states).await;
+        }
+        //The game started between the handshake and now
+        Err(err) => {
+            let _ = socket
+                .send(Message::close_with(1008u16, err.to_string()))
+                .await;
+        }
+    }
+}
+async fn start_game(room: Room, registry: SharedRegistry) {
+    let settings = room.launch_settings();
+    let grace = Duration::from_secs(settings.reconnect_grace_secs);
+    let mut sessions: Vec<Session> = Vec::new();
+    for seat in room.seats {
+        let (claim, claimed) = oneshot::channel();
+        let _ = seat.claim.send(claim);
+        //Someone who left just as the room filled forfeits once their grace is up
+        let socket = match claimed.await {
+            Ok(socket) => WebSocketPlayer::new(socket, seat.encoding, grace),
+            Err(_) => WebSocketPlayer::disconnected(grace),
+        };
+        sessions.push(Session {
+            token: new_token(),
+            name: seat.name,
+            deck: seat.deck,
+            con: PlayerCon::new(socket.clone()),
+            socket: Some(socket),
+        });
+    }
+    //Computer players play a copy of the first player's deck, so it's legal in the format
+    let deck = sessions[0].deck.clone();
+    for seat in 1..=settings.ai_players {
+        sessions.push(Session {
+            token: new_token(),
+            name: format!("AI {}", seat),
+            deck: deck.clone(),
+            con: PlayerCon::new_ai(),
+            socket: None,
+        });
+    }
+    let players = sessions
+        .iter()
+        .map(|session| session.name.clone())
+        .collect();
+    let tokens = sessions
+        .iter()
+        .filter_map(|session| Some((session.token.clone(), session.socket.clone()?)))
+        .collect();
+    let spectators = room.spectators;
+    let mut games = registry.lock().unwrap();
+    let id = games.next_id();
+    let task = tokio::task::spawn(run_game(
+        id,
+        settings.clone(),
+        sessions,
+        spectators.clone(),
+        registry.clone(),
+    ));
+    games.register(id, &settings, players, tokens, spectators, task);
+}
+async fn run_game(
+    id: GameId,
+    settings: RoomSettings,
+    sessions: Vec<Session>,
+    spectators: Spectators,
+    registry: SharedRegistry,
+) {
+    let sockets: Vec<WebSocketPlayer> = sessions
+        .iter()
+        .filter_map(|session| session.socket.clone())
+        .collect();
+    if let Err(err) = launch_game(id, settings, sessions, spectators).await {
+        log::error!("Game {} failed: {}", id, err);
+        //Players would otherwise be left waiting on a game that's gone
+        let reason = format!("the game failed: {}", err);
+        for socket in sockets {
+            socket.close(1011u16, &reason).await;
+        }
+    }
+    registry.lock().unwrap().finish(id);
+}
+async fn launch_game(
+    id: GameId,
+    settings: RoomSettings,
+    sessions: Vec<Session>,
+    spectators: Spectators,
+) -> Result<()> {
+    let db = card_db();
+    let mut gamebuild = GameBuilder::new();
+    gamebuild.set_format(settings.format);
+    let mut seats = Vec::new();
+    for session in sessions {
+        let token = session.token;
+        if let Some(socket) = &session.socket {
+            socket
+                .send(&ClientMessage::Session {
+                    token: token.clone(),
+                })
+                .await;
+        }
+        let player = gamebuild.add_player(&session.name, db, &session.deck.cards, session.con)?;
+        if let Some(commander) = session.deck.commander {
+            gamebuild.set_commander(player, db, commander)?;
+        }
+        seats.push(SavedSeat {
+            token,
+            name: session.name,
+            player,
+            ai: session.socket.is_none(),
+        });
+    }
+    let mut game = gamebuild.build(db)?;
+    let started = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let saved = SavedGame {
+        key: format!("{}-{}", started, id),
+        started,
+        settings,
+        seats,
+    };
+    //A game that can't be saved still gets played, it just won't survive a restart
+    match saved.save() {
+        Ok(()) => game.autosave = Some(saved.snapshot_path()),
+        Err(err) => log::warn!(
+            "Couldn't save game {}, it won't be resumed: {}",
+            saved.key,
+            err
+        ),
+    }
+    game.spectators = spectators;
     println!("Launching game!");
     game.run().await;
     //Fix this to make it print the winners name
+    saved.remove();
+    save_finished(id, &saved, &game)
+}
+//Games that were running when the server last stopped carry on from
+//their latest snapshot, waiting for their players to reconnect
+fn resume_saved_games(registry: &SharedRegistry) {
+    for saved in SavedGame::load_all() {
+        let (mut game, tokens) = match saved.load_game() {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                println!("Couldn't resume saved game {}: {}", saved.key, err);
+                continue;
+            }
+        };
+        let mut games = registry.lock().unwrap();
+        let id = games.next_id();
+        let players = saved.seats.iter().map(|seat| seat.name.clone()).collect();
+        let spectators = Spectators::default();
+        game.spectators = spectators.clone();
+        let settings = saved.settings.clone();
+        let registry = registry.clone();
+        let task = tokio::task::spawn(async move {
+            println!("Resuming game {}", saved.key);
+            game.resume().await;
+            saved.remove();
+            if let Err(err) = save_finished(id, &saved, &game) {
+                log::error!("Game {} failed: {}", id, err);
+            }
+            registry.lock().unwrap().finish(id);
+        });
+        games.register(id, &settings, players, tokens, spectators, task);
+    }
+}
+//Finished games are kept so they can be played back with the replay binary
+fn save_finished(id: GameId, saved: &SavedGame, game: &Game) -> Result<()> {
+    let finished = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = PathBuf::from(REPLAY_DIR).join(format!("{}-{}.json", finished, id));
+    std::fs::create_dir_all(REPLAY_DIR)?;
+    game.replay()?.save(&path)?;
+    println!("Saved replay to {}", path.display());
+    if let Some(dir) = std::env::var_os(RECORD_DIR_VAR) {
+        let duration = Duration::from_secs(finished.saturating_sub(saved.started));
+        let path = RecordSink::new(PathBuf::from(dir)).write(&saved.key, game, duration)?;
+        println!("Recorded game to {}", path.display());
+    }
     Ok(())
 }
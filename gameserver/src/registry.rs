@@ -0,0 +1,165 @@
+use crate::connection::WebSocketPlayer;
+use crate::lobby::RoomSettings;
+use common::format::Format;
+use game::spectator::{Spectator, SpectatorView, Spectators};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::task::JoinHandle;
+use warp::http::StatusCode;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize)]
+pub struct GameId(u64);
+
+impl From<u64> for GameId {
+    fn from(x: u64) -> Self {
+        Self(x)
+    }
+}
+impl fmt::Display for GameId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+#[derive(Clone, Debug, Serialize)]
+pub struct GameInfo {
+    pub id: GameId,
+    pub room: String,
+    pub format: Format,
+    pub players: Vec<String>,
+    pub started_at: u64, //Seconds since the unix epoch
+}
+struct RunningGame {
+    info: GameInfo,
+    #[allow(dead_code)] //Kept so the game's task stays reachable from the registry
+    task: JoinHandle<()>,
+    sessions: HashMap<String, WebSocketPlayer>, //Keyed by session token
+    spectators: Spectators,
+    omniscient_delay: Option<Duration>,
+    judge_token: Option<String>,
+}
+
+//Tracks every game that has been launched and is still running
+#[derive(Default)]
+pub struct GameRegistry {
+    games: HashMap<GameId, RunningGame>,
+    next_id: u64,
+}
+impl GameRegistry {
+    pub fn next_id(&mut self) -> GameId {
+        self.next_id += 1;
+        GameId(self.next_id)
+    }
+    pub fn register(
+        &mut self,
+        id: GameId,
+        settings: &RoomSettings,
+        players: Vec<String>,
+        sessions: HashMap<String, WebSocketPlayer>,
+        spectators: Spectators,
+        task: JoinHandle<()>,
+    ) {
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_secs())
+            .unwrap_or(0);
+        let info = GameInfo {
+            id,
+            room: settings.name.clone(),
+            format: settings.format,
+            players,
+            started_at,
+        };
+        let omniscient_delay = settings.omniscient_delay_secs.map(Duration::from_secs);
+        self.games.insert(
+            id,
+            RunningGame {
+                info,
+                task,
+                sessions,
+                spectators,
+                omniscient_delay,
+                judge_token: settings.judge_token.clone(),
+            },
+        );
+    }
+    pub fn finish(&mut self, id: GameId) {
+        self.games.remove(&id);
+    }
+    //The connection a session token belongs to, if its game is still running
+    pub fn session(&self, token: &str) -> Option<WebSocketPlayer> {
+        self.games
+            .values()
+            .find_map(|game| game.sessions.get(token))
+            .cloned()
+    }
+    //Picks the view a spectator gets. Omniscient views need the room to allow them
+    //and the room's judge token, and are never given to a player in the game.
+    //The token is only kept when the judge connected before the game started,
+    //this is how they get back in if their connection drops
+    pub fn spectate(
+        &self,
+        id: GameId,
+        omniscient: bool,
+        judge: Option<&str>,
+        session: Option<&str>,
+    ) -> Result<SpectatorView, SpectateError> {
+        let game = self.games.get(&id).ok_or(SpectateError::GameNotFound)?;
+        if !omniscient {
+            return Ok(SpectatorView::Public);
+        }
+        let Some(delay) = game.omniscient_delay else {
+            return Err(SpectateError::OmniscientDisabled);
+        };
+        let seated =
+            |token: Option<&str>| token.map_or(false, |token| game.sessions.contains_key(token));
+        if seated(session) || seated(judge) {
+            return Err(SpectateError::SeatedPlayer);
+        }
+        match (judge, &game.judge_token) {
+            (Some(judge), Some(expected)) if judge == expected => {
+                Ok(SpectatorView::Omniscient { delay })
+            }
+            _ => Err(SpectateError::NotAJudge),
+        }
+    }
+    pub fn add_spectator(&self, id: GameId, spectator: Spectator) {
+        if let Some(game) = self.games.get(&id) {
+            game.spectators.add(spectator);
+        }
+    }
+    pub fn list(&self) -> Vec<GameInfo> {
+        let mut games: Vec<GameInfo> = self.games.values().map(|game| game.info.clone()).collect();
+        games.sort_by_key(|game| game.id.0);
+        games
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpectateError {
+    GameNotFound,
+    OmniscientDisabled,
+    NotAJudge,
+    SeatedPlayer,
+}
+impl SpectateError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            Self::GameNotFound => StatusCode::NOT_FOUND,
+            Self::OmniscientDisabled | Self::NotAJudge | Self::SeatedPlayer => {
+                StatusCode::FORBIDDEN
+            }
+        }
+    }
+}
+impl fmt::Display for SpectateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::GameNotFound => write!(f, "game not found"),
+            Self::OmniscientDisabled => write!(f, "this game doesn't allow omniscient spectators"),
+            Self::NotAJudge => write!(f, "omniscient spectators need the room's judge token"),
+            Self::SeatedPlayer => write!(f, "players can't watch their own game omnisciently"),
+        }
+    }
+}
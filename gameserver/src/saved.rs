@@ -0,0 +1,98 @@
+use crate::connection::WebSocketPlayer;
+use crate::lobby::RoomSettings;
+use anyhow::Result;
+use common::entities::PlayerId;
+use game::game::Game;
+use game::player::PlayerCon;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+const SAVE_DIR: &str = "saved_games";
+
+//A running game kept on disk, so it can be picked back up if the server restarts.
+//The game itself is snapshotted as it runs, this is what the server needs to
+//let the players back in
+#[derive(Serialize, Deserialize)]
+pub struct SavedGame {
+    pub key: String,
+    pub started: u64, //Seconds since the epoch
+    pub settings: RoomSettings,
+    pub seats: Vec<SavedSeat>,
+}
+#[derive(Serialize, Deserialize)]
+pub struct SavedSeat {
+    pub token: String,
+    pub name: String,
+    pub player: PlayerId,
+    #[serde(default)]
+    pub ai: bool,
+}
+impl SavedGame {
+    fn path(key: &str, kind: &str) -> PathBuf {
+        PathBuf::from(SAVE_DIR).join(format!("{}.{}.json", key, kind))
+    }
+    pub fn snapshot_path(&self) -> PathBuf {
+        SavedGame::path(&self.key, "snapshot")
+    }
+    pub fn save(&self) -> Result<()> {
+        std::fs::create_dir_all(SAVE_DIR)?;
+        std::fs::write(
+            SavedGame::path(&self.key, "game"),
+            serde_json::to_vec(self)?,
+        )?;
+        Ok(())
+    }
+    //Once the game is over there's nothing to resume
+    pub fn remove(&self) {
+        let _ = std::fs::remove_file(SavedGame::path(&self.key, "game"));
+        let _ = std::fs::remove_file(self.snapshot_path());
+    }
+    //Every game that was still running when the server stopped. A game is
+    //saved before its first snapshot, so if the server stopped in between
+    //there's nothing to resume and it's removed
+    pub fn load_all() -> Vec<SavedGame> {
+        let Ok(entries) = std::fs::read_dir(SAVE_DIR) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.to_string_lossy().ends_with(".game.json"))
+            .filter_map(|path| match std::fs::read(path) {
+                Ok(contents) => serde_json::from_slice(&contents).ok(),
+                Err(_) => None,
+            })
+            .filter(|saved: &SavedGame| {
+                let started = saved.snapshot_path().exists();
+                if !started {
+                    log::info!("Removing game {}, it has no snapshot", saved.key);
+                    saved.remove();
+                }
+                started
+            })
+            .collect()
+    }
+    //The game picks up with nobody connected, players get their seats
+    //back by reconnecting with their session tokens, which are returned
+    //along with the connection each one takes over
+    pub fn load_game(&self) -> Result<(Game, HashMap<String, WebSocketPlayer>)> {
+        let grace = Duration::from_secs(self.settings.reconnect_grace_secs);
+        let mut tokens = HashMap::new();
+        let mut game = Game::load_snapshot(&self.snapshot_path(), crate::card_db(), |player| {
+            let seat = self.seats.iter().find(|seat| seat.player == player);
+            match seat {
+                Some(seat) if !seat.ai => {
+                    let socket = WebSocketPlayer::disconnected(grace);
+                    tokens.insert(seat.token.clone(), socket.clone());
+                    PlayerCon::new(socket)
+                }
+                Some(_) => PlayerCon::new_ai(),
+                None => PlayerCon::default(),
+            }
+        })?;
+        game.autosave = Some(self.snapshot_path());
+        Ok((game, tokens))
+    }
+}
@@ -0,0 +1,50 @@
+use crate::lobby::{Lobby, RoomSettings, Seat};
+use carddb::decklist::ResolvedDeck;
+use common::format::Format;
+use game::protocol::Encoding;
+use tokio::sync::oneshot;
+
+fn settings(name: &str, players: usize) -> RoomSettings {
+    RoomSettings {
+        name: name.to_owned(),
+        players,
+        format: Format::default(),
+        password: None,
+        reconnect_grace_secs: 60,
+        omniscient_delay_secs: None,
+        ai_players: 0,
+        judge_token: None,
+    }
+}
+fn seat(name: &str) -> Seat {
+    let (claim, _) = oneshot::channel();
+    Seat {
+        name: name.to_owned(),
+        deck: ResolvedDeck::default(),
+        encoding: Encoding::Json,
+        claim,
+    }
+}
+#[test]
+fn quick_match_skips_rooms_named_like_it() {
+    let mut lobby = Lobby::default();
+    let mut imitation = settings("Quick match", 4);
+    imitation.ai_players = 2;
+    let imitation = lobby.create(imitation).unwrap().info.id;
+    let id = lobby.quick_match();
+    assert_ne!(id, imitation);
+    assert_eq!(lobby.get(id).unwrap().settings.players, 2);
+    assert_eq!(lobby.quick_match(), id);
+}
+#[test]
+fn rooms_close_when_everyone_leaves() {
+    let mut lobby = Lobby::default();
+    let id = lobby.create(settings("Waiting", 3)).unwrap().info.id;
+    assert!(lobby.join(id, None, seat("p1")).unwrap().is_none());
+    assert!(lobby.join(id, None, seat("p2")).unwrap().is_none());
+    lobby.leave(id, "p1");
+    assert_eq!(lobby.get(id).unwrap().info().seated, vec!["p2".to_owned()]);
+    lobby.leave(id, "p2");
+    assert!(lobby.get(id).is_none());
+    assert!(lobby.list().is_empty());
+}
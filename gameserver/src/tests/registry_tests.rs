@@ -0,0 +1,131 @@
+use crate::connection::WebSocketPlayer;
+use crate::lobby::{Lobby, LobbyError, RoomSettings, MIN_OMNISCIENT_DELAY_SECS};
+use crate::registry::{GameId, GameRegistry, SpectateError};
+use common::format::Format;
+use game::spectator::{SpectatorView, Spectators};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const SESSION: &str = "p1-session";
+
+fn settings(omniscient_delay_secs: Option<u64>) -> RoomSettings {
+    RoomSettings {
+        name: "Judged".to_owned(),
+        players: 2,
+        format: Format::default(),
+        password: None,
+        reconnect_grace_secs: 60,
+        omniscient_delay_secs,
+        ai_players: 0,
+        judge_token: None,
+    }
+}
+//A running game with one seated player, along with the room's judge token.
+//The judge connects while the room is open if judged is set
+fn running_game(
+    omniscient_delay_secs: Option<u64>,
+    judged: bool,
+) -> (GameRegistry, GameId, Option<String>) {
+    let mut lobby = Lobby::default();
+    let created = lobby.create(settings(omniscient_delay_secs)).unwrap();
+    let id = created.info.id;
+    if let Some(token) = &created.judge_token
+    && judged {
+        lobby.connect_judge(id, token).unwrap();
+    }
+    let room = lobby.get(id).unwrap();
+    let mut registry = GameRegistry::default();
+    let id = registry.next_id();
+    let mut sessions = HashMap::new();
+    let socket = WebSocketPlayer::disconnected(Duration::from_secs(60));
+    sessions.insert(SESSION.to_owned(), socket);
+    let task = tokio::spawn(async {});
+    registry.register(
+        id,
+        &room.launch_settings(),
+        vec!["p1".to_owned()],
+        sessions,
+        Spectators::default(),
+        task,
+    );
+    (registry, id, created.judge_token)
+}
+#[test_log::test(tokio::test)]
+async fn judges_get_the_omniscient_view() {
+    let (registry, id, judge) = running_game(Some(30), true);
+    let view = registry.spectate(id, true, judge.as_deref(), None);
+    assert_eq!(
+        view,
+        Ok(SpectatorView::Omniscient {
+            delay: Duration::from_secs(30)
+        })
+    );
+    assert_eq!(
+        registry.spectate(id, false, None, None),
+        Ok(SpectatorView::Public)
+    );
+}
+#[test_log::test(tokio::test)]
+async fn omniscient_views_need_the_judge_token() {
+    let (registry, id, judge) = running_game(Some(30), true);
+    assert!(judge.is_some());
+    assert_eq!(
+        registry.spectate(id, true, None, None),
+        Err(SpectateError::NotAJudge)
+    );
+    assert_eq!(
+        registry.spectate(id, true, Some("guessed"), None),
+        Err(SpectateError::NotAJudge)
+    );
+    let (registry, id, judge) = running_game(None, true);
+    assert!(judge.is_none());
+    assert_eq!(
+        registry.spectate(id, true, Some("guessed"), None),
+        Err(SpectateError::OmniscientDisabled)
+    );
+}
+#[test_log::test(tokio::test)]
+async fn seated_players_cant_watch_omnisciently() {
+    let (registry, id, judge) = running_game(Some(30), true);
+    assert_eq!(
+        registry.spectate(id, true, judge.as_deref(), Some(SESSION)),
+        Err(SpectateError::SeatedPlayer)
+    );
+    assert_eq!(
+        registry.spectate(id, true, Some(SESSION), None),
+        Err(SpectateError::SeatedPlayer)
+    );
+}
+#[test_log::test(tokio::test)]
+async fn creators_who_take_a_seat_cant_judge() {
+    //The creator never connects as the judge and plays instead
+    let (registry, id, judge) = running_game(Some(30), false);
+    assert!(judge.is_some());
+    assert_eq!(
+        registry.spectate(id, true, judge.as_deref(), None),
+        Err(SpectateError::NotAJudge)
+    );
+}
+#[test]
+fn judges_need_a_delay_and_their_token() {
+    let mut lobby = Lobby::default();
+    let short = lobby.create(settings(Some(MIN_OMNISCIENT_DELAY_SECS - 1)));
+    assert_eq!(short.err(), Some(LobbyError::OmniscientDelayTooShort));
+    let created = lobby
+        .create(settings(Some(MIN_OMNISCIENT_DELAY_SECS)))
+        .unwrap();
+    let id = created.info.id;
+    assert_eq!(
+        lobby.connect_judge(id, "guessed").err(),
+        Some(LobbyError::NotAJudge)
+    );
+    let (view, _) = lobby
+        .connect_judge(id, created.judge_token.as_deref().unwrap())
+        .unwrap();
+    assert_eq!(
+        view,
+        SpectatorView::Omniscient {
+            delay: Duration::from_secs(MIN_OMNISCIENT_DELAY_SECS)
+        }
+    );
+}
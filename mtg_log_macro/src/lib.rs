@@ -6,7 +6,9 @@ use proc_macro2::{TokenStream, Ident};
 fn log_name(id: &Ident)->Ident{
     quote::format_ident!("Log{}", id)
 }
-#[proc_macro_derive(MTGLoggable)]
+//The generated types are sent to clients, so crates deriving this need serde and schemars.
+//They also implement LogCards, #[log_group] marks a type whose cards are all the same card
+#[proc_macro_derive(MTGLoggable, attributes(log_group))]
 pub fn derive_mtg_log(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     // Parse the string representation
     let ast = parse_macro_input!(input as DeriveInput);
@@ -24,12 +26,12 @@ pub fn derive_mtg_log(input: proc_macro::TokenStream) -> proc_macro::TokenStream
             let fields = modify_fields(data.fields);
             if needs_semicolon{
                 quote::quote!{
-                    #[derive(Debug)]
+                    #[derive(Debug, Clone, ::serde::Serialize, ::serde::Deserialize, ::schemars::JsonSchema)]
                     #vis struct #name #generics #fields ;
                 }
             } else {
                 quote::quote!{
-                    #[derive(Debug)]
+                    #[derive(Debug, Clone, ::serde::Serialize, ::serde::Deserialize, ::schemars::JsonSchema)]
                     #vis struct #name #generics #fields
                 }
             }
@@ -37,7 +39,7 @@ pub fn derive_mtg_log(input: proc_macro::TokenStream) -> proc_macro::TokenStream
         Data::Enum(data) => {
             let fields=modify_fields_enum(data);
             quote::quote!{
-                #[derive(Debug)]
+                #[derive(Debug, Clone, ::serde::Serialize, ::serde::Deserialize, ::schemars::JsonSchema)]
                 #vis enum #name #generics #fields
             }
         }
@@ -45,6 +47,7 @@ pub fn derive_mtg_log(input: proc_macro::TokenStream) -> proc_macro::TokenStream
     };
     let implement=impl_mtglog(&name, &pass_ast);
     res.extend(implement.into_iter());
+    res.extend(impl_log_cards(&name, &pass_ast));
     res.into()
 }
 
@@ -241,4 +244,68 @@ fn impl_for_enum(variants:Punctuated<Variant, Comma>) -> TokenStream{
             )*
         }
     )
-}
\ No newline at end of file
+}
+//Collects the cards each field of the log type names.
+//The parameter is named so it can't be shadowed by a field
+fn impl_log_cards(name: &Ident, ast:&DeriveInput) -> TokenStream{
+    let generics: &syn::Generics=&ast.generics;
+    let body=match ast.data.clone(){
+        Data::Struct(data) => {
+            let names=extract_names(&data.fields);
+            let visits=visit_fields(&data.fields);
+            quote::quote!(
+                let #name #names = self;
+                #( #visits )*
+            )
+        },
+        Data::Enum(data)=>{
+            let mut arms=Vec::new();
+            for variant in data.variants{
+                let ident=variant.ident;
+                let names=extract_names(&variant.fields);
+                let visits=visit_fields(&variant.fields);
+                arms.push(quote::quote!(
+                    Self:: #ident #names => { #( #visits )* }
+                ))
+            }
+            quote::quote!(
+                match self{
+                    #( #arms , )*
+                }
+            )
+        }
+        _=>unimplemented!()
+    };
+    let grouped=ast.attrs.iter().any(|attr| attr.path().is_ident("log_group"));
+    let body=if grouped {
+        quote::quote!(
+            let mut log_card_group=Vec::new();
+            {
+                let log_card_groups=&mut log_card_group;
+                #body
+            }
+            let log_card_group=log_card_group.concat();
+            if !log_card_group.is_empty(){
+                log_card_groups.push(log_card_group);
+            }
+        )
+    } else {
+        body
+    };
+    quote::quote!{
+        impl #generics LogCards for #name #generics{
+            #[allow(unused_variables)]
+            fn log_cards(&self, log_card_groups: &mut Vec<Vec<CardId>>){
+                #body
+            }
+        }
+    }
+}
+fn visit_fields(fields: &Fields) -> Vec<TokenStream>{
+    let names:Vec<Ident>=match fields{
+        Fields::Named(fields)=>fields.named.iter().map(|field| field.ident.clone().expect("This is a named field")).collect(),
+        Fields::Unnamed(fields)=>(0..fields.unnamed.len()).map(|i| format_ident!("log_field_{}",i)).collect(),
+        Fields::Unit=>Vec::new(),
+    };
+    names.into_iter().map(|name| quote::quote!( LogCards::log_cards(#name, log_card_groups); )).collect()
+}
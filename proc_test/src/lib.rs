@@ -11,6 +11,13 @@ pub trait MTGLog{
         panic!()
     }
 }
+pub struct CardId;
+pub trait LogCards{
+    fn log_cards(&self, cards: &mut Vec<Vec<CardId>>){
+    }
+}
+impl LogCards for (){
+}
 impl MTGLog for i32{
     type LogType = ();
 }
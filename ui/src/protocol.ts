@@ -0,0 +1,216 @@
+/* tslint:disable */
+// Generated by `cargo run --bin gen-schema`, don't edit by hand
+
+export const PROTOCOL_VERSION = 1;
+
+export type Ability = ({ Activated: ActivatedAbility } | { Triggered: TriggeredAbility } | { Static: StaticAbility } | { Replacement: ReplacementAbility });
+
+export type AbilityTrigger = { constraint: Constraint[]; trigger: AbilityTriggerType };
+
+export type AbilityTriggerType = ("Attacks" | { ZoneMove: ZoneMoveTrigger });
+
+export type Action = ({ Cast: CastingOption } | { PlayLand: CardId } | { ActivateAbility: { index: number; source: CardId } });
+
+export type ActionFilter = "None";
+
+export type ActivatedAbility = { costs: Cost[]; effect: Clause[]; keyword?: (KeywordAbility | null); restrictions?: (Constraint | null) };
+
+export type Affected = (("Controller" | "Cardname" | "All" | "EquippedOrEnchanted") | { Target: (TargetId | null) } | { ManuallySet: TargetId[] } | { UpToXTarget: [number, TargetId[]] });
+
+export type Ask = ({ Attackers: AskPair_for_TargetId } | { Blockers: AskPair_for_CardId } | { DiscardToHandSize: AskSelectN_for_CardId } | { Action: AskSelectN_for_Action } | { Target: AskSelectN_for_TargetId } | { CommandZone: AskSelectN_for_CardId });
+
+export type AskPairItem_for_CardId = { items: { [key: string]: null }; max: number; min: number };
+
+export type AskPairItem_for_TargetId = { items: { [key: string]: null }; max: number; min: number };
+
+export type AskPair_for_CardId = { pairs: { [key: string]: AskPairItem_for_CardId } };
+
+export type AskPair_for_TargetId = { pairs: { [key: string]: AskPairItem_for_TargetId } };
+
+export type AskResponse_for_Map_of_Map_of_Null = { id: number; response: { [key: string]: { [key: string]: null } } };
+
+export type AskResponse_for_Map_of_Null = { id: number; response: { [key: string]: null } };
+
+export type AskSelectN_for_Action = { ents: Action[]; max: number; min: number };
+
+export type AskSelectN_for_CardId = { ents: CardId[]; max: number; min: number };
+
+export type AskSelectN_for_TargetId = { ents: TargetId[]; max: number; min: number };
+
+export type CardEnt = { abilities: Ability[]; already_dealt_damage: boolean; art_url?: (string | null); attacking?: (TargetId | null); blocked: CardId[]; blocking: CardId[]; cast: boolean; colors: { [key: string]: null }; commander: boolean; controller?: (PlayerId | null); costs: Cost[]; counters: Counter[]; damaged: number; effect: Clause[]; enchanting_or_equipping?: (TargetId | null); ent_type: EntType; etb_this_cycle: boolean; known_to: PlayerId[]; name: string; owner: PlayerId; printed?: (CardEnt | null); pt?: (PT | null); source_of_ability?: (CardId | null); subtypes: { [key: string]: null }; supertypes: { [key: string]: null }; tapped: boolean; types: { [key: string]: null } };
+
+export type CardError = { name: string; reason: CardErrorReason };
+
+export type CardErrorReason = ("NotFound" | { MalformedLine: number } | { ParseFailed: string } | { TooManyCopies: number });
+
+export type CardId = number;
+
+export type CardIdContext = { card_id: CardId; controller?: (PlayerId | null); name?: (string | null) };
+
+export type CastingOption = { costs: Cost[]; filter: ActionFilter; player: PlayerId; possible_to_take: boolean; source_card: CardId; zone: Zone };
+
+export type Clause = { affected: Affected; constraints: Constraint[]; effect: ClauseEffect };
+
+export type ClauseEffect = (("Destroy" | "Exile" | "DrawCard" | "Tap") | { AddMana: ManaCostSymbol[] } | { GainLife: number } | { Compound: Clause[] } | { SetTargetController: Clause } | { CreateToken: TokenAttribute[] } | { UntilEndTurn: ContEffect } | { MultClause: [ClauseEffect, NumberComputer] } | { PutCounter: [Counter, number] });
+
+export type ClientMessage = ({ GameState: GameState } | { StateDiff: StateDiff } | { Log: LogMessage[] } | { AskUser: { ask: Ask; id: number } } | { InvalidResponse: { id: number; reason: string } } | { DeckRejected: DeckError } | { Session: { token: string } });
+
+export type ClientRequest = "Resync";
+
+export type ClientToServer = (DeckSubmission | ClientRequest | AskResponse_for_Map_of_Null | AskResponse_for_Map_of_Map_of_Null | AskResponse_for_Map_of_Map_of_Null);
+
+export type Color = ("White" | "Blue" | "Black" | "Red" | "Green" | "Colorless");
+
+export type Constraint = (("IsTapped" | "IsCardname" | "YouControl" | "Multicolored" | "NonToken" | "NotCast" | "Permanent" | "Other") | { CardType: Type } | { And: Constraint[] } | { Or: Constraint[] } | { ControlWith: [Constraint[], number] } | { HasKeyword: KeywordAbility } | { Subtype: Subtype } | { HasCounter: Counter });
+
+export type ContDuration = ("Perpetual" | "EndOfTurn");
+
+export type ContEffect = (("CantAttackOrBlock" | "CantActivateNonManaAbil") | { ModifyPT: PT } | { HasAbility: Ability } | { AddSubtype: Subtype[] });
+
+export type Continuous = { affected: Affected; constraints: Constraint[]; duration: ContDuration; effect: ContEffect; source: CardId };
+
+export type Cost = ("Selftap" | { Mana: ManaCostSymbol });
+
+export type Counter = "Plus1Plus1";
+
+export type DeckEntry = { count: number; name: string };
+
+export type DeckError = ("Empty" | { TooManyCards: number } | { Cards: CardError[] } | { Illegal: DeckViolation[] });
+
+export type DeckSubmission = (string | DeckEntry[] | { commander: string; deck: DeckEntry[] });
+
+export type DeckViolation = ("MissingCommander" | { CardNotFound: { name: string } } | { TooFewCards: { found: number; min: number } } | { WrongSize: { found: number; size: number } } | { TooManyCopies: { found: number; max: number; name: string } } | { NotLegal: { name: string } } | { Banned: { name: string } } | { OutsideColorIdentity: { name: string } } | { InvalidCommander: { name: string } });
+
+export type EntMap_for_ManaId_and_Mana = { [key: string]: unknown };
+
+export type EntType = ("RealCard" | "TokenCard" | "ActivatedAbility" | "TriggeredAbility");
+
+export type Format = ("Casual" | "Standard" | "Limited" | "Commander");
+
+export type GameOutcome = (("Ongoing" | "Tie") | { Winner: PlayerId });
+
+export type GameState = { active_player: PlayerId; battlefield: { [key: string]: null }; cards: { [key: string]: CardEnt }; command: { [key: string]: null }; cont_effects: Continuous[]; exile: { [key: string]: null }; extra_turns: PlayerId[]; format: Format; land_play_limit: number; lands_played_this_turn: number; mana: EntMap_for_ManaId_and_Mana; outcome: GameOutcome; panic_on_restore: boolean; phase?: (Phase | null); phases: Phase[]; player?: (PlayerId | null); players: { [key: string]: PlayerView }; priority: PlayerId; stack: CardId[]; subphase?: (Subphase | null); subphases: Subphase[]; turn: number; turn_order: PlayerId[]; version?: number };
+
+export type KeywordAbility = ("FirstStrike" | "Haste" | "Vigilance" | "DoubleStrike" | "Flying" | "Prowess" | "Lifelink" | "Trample" | "Reach" | "Protection" | "Flash" | "Enchant");
+
+export type LogAbility = ({ Activated: LogActivatedAbility } | { Triggered: LogTriggeredAbility } | { Static: LogStaticAbility } | { Replacement: LogReplacementAbility });
+
+export type LogAbilityTrigger = { constraint: LogConstraint[]; trigger: LogAbilityTriggerType };
+
+export type LogAbilityTriggerType = ("Attacks" | { ZoneMove: LogZoneMoveTrigger });
+
+export type LogActionFilter = "None";
+
+export type LogActivatedAbility = { costs: LogCost[]; effect: LogClause[]; keyword?: (LogKeywordAbility | null); restrictions?: (LogConstraint | null) };
+
+export type LogAffected = (("Controller" | "Cardname" | "All" | "EquippedOrEnchanted") | { Target: (LogTargetId | null) } | { ManuallySet: LogTargetId[] } | { UpToXTarget: [number, LogTargetId[]] });
+
+export type LogClause = { affected: LogAffected; constraints: LogConstraint[]; effect: LogClauseEffect };
+
+export type LogClauseEffect = (("Destroy" | "Exile" | "DrawCard" | "Tap") | { AddMana: LogManaCostSymbol[] } | { GainLife: number } | { Compound: LogClause[] } | { SetTargetController: LogClause } | { CreateToken: LogTokenAttribute[] } | { UntilEndTurn: LogContEffect } | { MultClause: [LogClauseEffect, LogNumberComputer] } | { PutCounter: [LogCounter, number] });
+
+export type LogColor = ("White" | "Blue" | "Black" | "Red" | "Green" | "Colorless");
+
+export type LogConstraint = (("IsTapped" | "IsCardname" | "YouControl" | "Multicolored" | "NonToken" | "NotCast" | "Permanent" | "Other") | { CardType: LogType } | { And: LogConstraint[] } | { Or: LogConstraint[] } | { ControlWith: [LogConstraint[], number] } | { HasKeyword: LogKeywordAbility } | { Subtype: LogSubtype } | { HasCounter: LogCounter });
+
+export type LogContEffect = (("CantAttackOrBlock" | "CantActivateNonManaAbil") | { ModifyPT: LogPT } | { HasAbility: LogAbility } | { AddSubtype: LogSubtype[] });
+
+export type LogCost = ("Selftap" | { Mana: LogManaCostSymbol });
+
+export type LogCounter = "Plus1Plus1";
+
+export type LogDamageReason = ("Combat" | { SpellAbility: CardIdContext });
+
+export type LogEntry = ({ DiesFromZeroOrLessToughness: CardIdContext } | { DestroyFromDamage: CardIdContext } | { DetachedEnchantmentDies: CardIdContext } | { EnchantmentFallsOff: CardIdContext } | { CastFailedFromRestriction: CardIdContext } | { ManaCostNotPaid: CardIdContext } | { Cast: [LogStackActionOption, LogTargetId[]] } | { Resolve: [CardIdContext, LogClause[]] } | { Text: string } | { TriggeredAbil: CardIdContext } | { Lose: PlayerId } | { LoseFromLife: PlayerId } | { LoseFromCommanderDamage: PlayerId } | { LoseFromDisconnect: PlayerId } | { Event: LogEvent } | { EventResult: LogEventResult } | { Prevented: LogEvent } | { Replaced: [CardIdContext, LogEvent, LogEvent[]] });
+
+export type LogEvent = ({ Draw: { player: PlayerId } } | { Damage: { amount: number; reason: LogDamageReason; source: CardIdContext; target: LogTargetId } } | { Destroy: { perms: CardIdContext[] } } | { Discard: { cards: CardIdContext[]; player: PlayerId } } | { Block: { blocker: CardIdContext } } | { Blocked: { attacker: CardIdContext } } | { BlockedBy: { attacker: CardIdContext; blocker: CardIdContext } } | { Cast: { player: PlayerId; spell: CardIdContext } } | { Activate: { ability: CardIdContext; controller: PlayerId } } | { MoveZones: { dest: LogZone; ents: CardIdContext[]; origin?: (LogZone | null) } } | { Lose: { player: PlayerId } } | { Tap: { ent: CardIdContext } } | { Subphase: { subphase: LogSubphase } } | { PlayLand: { land: CardIdContext; player: PlayerId } } | { Phase: { phase: LogPhase } } | { Turn: { extra: boolean; player: PlayerId } } | { Untap: { ent: CardIdContext } } | { GainLife: { amount: number; player: PlayerId } } | { TriggeredAbil: { effect: LogClause[]; event: LogEventResult; source: CardIdContext } } | { PutCounter: { affected: LogTargetId; counter: LogCounter; quantity: number } });
+
+export type LogEventResult = ({ Draw: CardIdContext } | { MoveZones: LogMoveZonesResult[] } | { Tap: CardIdContext } | { Untap: CardIdContext } | { Attacks: [CardIdContext, LogTargetId][] });
+
+export type LogKeywordAbility = ("FirstStrike" | "Haste" | "Vigilance" | "DoubleStrike" | "Flying" | "Prowess" | "Lifelink" | "Trample" | "Reach" | "Protection" | "Flash" | "Enchant");
+
+export type LogManaCostSymbol = ("White" | "Blue" | "Black" | "Red" | "Green" | "Colorless" | "Generic");
+
+export type LogMessage = { entry: LogEntry; index: number; stamp: LogStamp; text: string };
+
+export type LogMoveZonesResult = { dest: LogZone; newent?: (CardIdContext | null); oldent: CardIdContext; source?: (LogZone | null) };
+
+export type LogNumberComputer = { NumPermanents: LogConstraint[] };
+
+export type LogPT = { power: number; toughness: number };
+
+export type LogPhase = ("Begin" | "FirstMain" | "Combat" | "SecondMain" | "Ending");
+
+export type LogReplacement = { ZoneMoveReplacement: { constraints: LogConstraint[]; new_effect: LogClause; trigger: LogZoneMoveTrigger } };
+
+export type LogReplacementAbility = { effect: LogReplacement; keyword?: (LogKeywordAbility | null) };
+
+export type LogStackActionOption = { costs: LogCost[]; filter: LogActionFilter; keyword?: (LogKeywordAbility | null); player: PlayerId; stack_ent: CardIdContext };
+
+export type LogStamp = { active_player: PlayerId; phase?: (Phase | null); subphase?: (Subphase | null); turn: number };
+
+export type LogStaticAbility = { effect: LogStaticAbilityEffect; keyword?: (LogKeywordAbility | null) };
+
+export type LogStaticAbilityEffect = ("GivenByKeyword" | { Protection: LogConstraint } | { Enchant: LogConstraint[] } | { Cont: LogStaticContEffect });
+
+export type LogStaticContEffect = { affected: LogAffected; constraints: LogConstraint[]; effects: LogContEffect[] };
+
+export type LogSubphase = ("Untap" | "Upkeep" | "Draw" | "BeginCombat" | "Attackers" | "Blockers" | "FirstStrikeDamage" | "Damage" | "EndCombat" | "EndStep" | "Cleanup");
+
+export type LogSubtype = ("Advisor" | "Aetherborn" | "Ally" | "Angel" | "Antelope" | "Ape" | "Archer" | "Archon" | "Army" | "Artificer" | "Assassin" | "AssemblyWorker" | "Atog" | "Aurochs" | "Avatar" | "Azra" | "Badger" | "Barbarian" | "Bard" | "Basilisk" | "Bat" | "Bear" | "Beast" | "Beeble" | "Beholder" | "Berserker" | "Bird" | "Blinkmoth" | "Boar" | "Bringer" | "Brushwagg" | "Camarid" | "Camel" | "Caribou" | "Carrier" | "Cat" | "Centaur" | "Cephalid" | "Chimera" | "Citizen" | "Cleric" | "Cockatrice" | "Construct" | "Coward" | "Crab" | "Crocodile" | "Cyclops" | "Dauthi" | "Demigod" | "Demon" | "Deserter" | "Devil" | "Dinosaur" | "Djinn" | "Dog" | "Dragon" | "Drake" | "Dreadnought" | "Drone" | "Druid" | "Dryad" | "Dwarf" | "Efreet" | "Egg" | "Elder" | "Eldrazi" | "Elemental" | "Elephant" | "Elf" | "Elk" | "Eye" | "Faerie" | "Ferret" | "Fish" | "Flagbearer" | "Fox" | "Fractal" | "Frog" | "Fungus" | "Gargoyle" | "Germ" | "Giant" | "Gnoll" | "Gnome" | "Goat" | "Goblin" | "God" | "Golem" | "Gorgon" | "Graveborn" | "Gremlin" | "Griffin" | "Hag" | "Halfling" | "Hamster" | "Harpy" | "Hellion" | "Hippo" | "Hippogriff" | "Homarid" | "Homunculus" | "Horror" | "Horse" | "Human" | "Hydra" | "Hyena" | "Illusion" | "Imp" | "Incarnation" | "Inkling" | "Insect" | "Jackal" | "Jellyfish" | "Juggernaut" | "Kavu" | "Kirin" | "Kithkin" | "Knight" | "Kobold" | "Kor" | "Kraken" | "Lamia" | "Lammasu" | "Leech" | "Leviathan" | "Lhurgoyf" | "Licid" | "Lizard" | "Manticore" | "Masticore" | "Mercenary" | "Merfolk" | "Metathran" | "Minion" | "Minotaur" | "Mole" | "Monger" | "Mongoose" | "Monk" | "Monkey" | "Moonfolk" | "Mouse" | "Mutant" | "Myr" | "Mystic" | "Naga" | "Nautilus" | "Nephilim" | "Nightmare" | "Nightstalker" | "Ninja" | "Noble" | "Noggle" | "Nomad" | "Nymph" | "Octopus" | "Ogre" | "Ooze" | "Orb" | "Orc" | "Orgg" | "Otter" | "Ouphe" | "Ox" | "Oyster" | "Pangolin" | "Peasant" | "Pegasus" | "Pentavite" | "Pest" | "Phelddagrif" | "Phoenix" | "Phyrexian" | "Pilot" | "Pincher" | "Pirate" | "Plant" | "Praetor" | "Prism" | "Processor" | "Rabbit" | "Ranger" | "Rat" | "Rebel" | "Reflection" | "Rhino" | "Rigger" | "Rogue" | "Sable" | "Salamander" | "Samurai" | "Sand" | "Saproling" | "Satyr" | "Scarecrow" | "Scion" | "Scorpion" | "Scout" | "Sculpture" | "Serf" | "Serpent" | "Servo" | "Shade" | "Shaman" | "Shapeshifter" | "Shark" | "Sheep" | "Siren" | "Skeleton" | "Slith" | "Sliver" | "Slug" | "Snake" | "Soldier" | "Soltari" | "Spawn" | "Specter" | "Spellshaper" | "Sphinx" | "Spider" | "Spike" | "Spirit" | "Splinter" | "Sponge" | "Squid" | "Squirrel" | "Starfish" | "Surrakar" | "Survivor" | "Tentacle" | "Tetravite" | "Thalakos" | "Thopter" | "Thrull" | "Tiefling" | "Treefolk" | "Trilobite" | "Triskelavite" | "Troll" | "Turtle" | "Unicorn" | "Vampire" | "Vedalken" | "Viashino" | "Volver" | "Wall" | "Warlock" | "Warrior" | "Weird" | "Werewolf" | "Whale" | "Wizard" | "Wolf" | "Wolverine" | "Wombat" | "Worm" | "Wraith" | "Wurm" | "Yeti" | "Zombie" | "Zubera" | "Plains" | "Island" | "Swamp" | "Mountain" | "Forest" | "Aura");
+
+export type LogTargetId = ({ Player: PlayerId } | { Card: CardIdContext });
+
+export type LogTokenAttribute = ("EntersTappedAndAttacking" | { PT: LogPT } | { HasColor: LogColor } | { Type: LogType } | { Subtype: LogSubtype } | { Ability: LogAbility });
+
+export type LogTriggeredAbility = { effect: LogClause[]; keyword?: (LogKeywordAbility | null); trigger: LogAbilityTrigger };
+
+export type LogType = ("Artifact" | "Enchantment" | "Planeswalker" | "Land" | "Creature" | "Instant" | "Sorcery");
+
+export type LogZone = ("Hand" | "Library" | "Exile" | "Battlefield" | "Graveyard" | "Command" | "Stack");
+
+export type LogZoneMoveTrigger = { dest?: (LogZone | null); origin?: (LogZone | null) };
+
+export type Mana = { color: Color; restriction?: (ManaRestriction | null) };
+
+export type ManaCostSymbol = ("White" | "Blue" | "Black" | "Red" | "Green" | "Colorless" | "Generic");
+
+export type ManaRestriction = { [key: string]: unknown };
+
+export type NumberComputer = { NumPermanents: Constraint[] };
+
+export type PT = { power: number; toughness: number };
+
+export type Phase = ("Begin" | "FirstMain" | "Combat" | "SecondMain" | "Ending");
+
+export type PlayerId = number;
+
+export type PlayerView = { commander_casts: number; commander_damage: { [key: string]: number }; counters: Counter[]; graveyard: CardId[]; hand: CardId[]; library: CardId[]; life: number; mana_pool: { [key: string]: null }; max_handsize: number; name: string };
+
+export type Replacement = { ZoneMoveReplacement: { constraints: Constraint[]; new_effect: Clause; trigger: ZoneMoveTrigger } };
+
+export type ReplacementAbility = { effect: Replacement; keyword?: (KeywordAbility | null) };
+
+export type StateDiff = { cards: { [key: string]: CardEnt }; game: { [key: string]: unknown }; players: { [key: string]: PlayerView }; removed_cards: CardId[]; version: number };
+
+export type StaticAbility = { effect: StaticAbilityEffect; keyword?: (KeywordAbility | null) };
+
+export type StaticAbilityEffect = ("GivenByKeyword" | { Protection: Constraint } | { Enchant: Constraint[] } | { Cont: StaticContEffect });
+
+export type StaticContEffect = { affected: Affected; constraints: Constraint[]; effects: ContEffect[] };
+
+export type Subphase = ("Untap" | "Upkeep" | "Draw" | "BeginCombat" | "Attackers" | "Blockers" | "FirstStrikeDamage" | "Damage" | "EndCombat" | "EndStep" | "Cleanup");
+
+export type Subtype = ("Advisor" | "Aetherborn" | "Ally" | "Angel" | "Antelope" | "Ape" | "Archer" | "Archon" | "Army" | "Artificer" | "Assassin" | "AssemblyWorker" | "Atog" | "Aurochs" | "Avatar" | "Azra" | "Badger" | "Barbarian" | "Bard" | "Basilisk" | "Bat" | "Bear" | "Beast" | "Beeble" | "Beholder" | "Berserker" | "Bird" | "Blinkmoth" | "Boar" | "Bringer" | "Brushwagg" | "Camarid" | "Camel" | "Caribou" | "Carrier" | "Cat" | "Centaur" | "Cephalid" | "Chimera" | "Citizen" | "Cleric" | "Cockatrice" | "Construct" | "Coward" | "Crab" | "Crocodile" | "Cyclops" | "Dauthi" | "Demigod" | "Demon" | "Deserter" | "Devil" | "Dinosaur" | "Djinn" | "Dog" | "Dragon" | "Drake" | "Dreadnought" | "Drone" | "Druid" | "Dryad" | "Dwarf" | "Efreet" | "Egg" | "Elder" | "Eldrazi" | "Elemental" | "Elephant" | "Elf" | "Elk" | "Eye" | "Faerie" | "Ferret" | "Fish" | "Flagbearer" | "Fox" | "Fractal" | "Frog" | "Fungus" | "Gargoyle" | "Germ" | "Giant" | "Gnoll" | "Gnome" | "Goat" | "Goblin" | "God" | "Golem" | "Gorgon" | "Graveborn" | "Gremlin" | "Griffin" | "Hag" | "Halfling" | "Hamster" | "Harpy" | "Hellion" | "Hippo" | "Hippogriff" | "Homarid" | "Homunculus" | "Horror" | "Horse" | "Human" | "Hydra" | "Hyena" | "Illusion" | "Imp" | "Incarnation" | "Inkling" | "Insect" | "Jackal" | "Jellyfish" | "Juggernaut" | "Kavu" | "Kirin" | "Kithkin" | "Knight" | "Kobold" | "Kor" | "Kraken" | "Lamia" | "Lammasu" | "Leech" | "Leviathan" | "Lhurgoyf" | "Licid" | "Lizard" | "Manticore" | "Masticore" | "Mercenary" | "Merfolk" | "Metathran" | "Minion" | "Minotaur" | "Mole" | "Monger" | "Mongoose" | "Monk" | "Monkey" | "Moonfolk" | "Mouse" | "Mutant" | "Myr" | "Mystic" | "Naga" | "Nautilus" | "Nephilim" | "Nightmare" | "Nightstalker" | "Ninja" | "Noble" | "Noggle" | "Nomad" | "Nymph" | "Octopus" | "Ogre" | "Ooze" | "Orb" | "Orc" | "Orgg" | "Otter" | "Ouphe" | "Ox" | "Oyster" | "Pangolin" | "Peasant" | "Pegasus" | "Pentavite" | "Pest" | "Phelddagrif" | "Phoenix" | "Phyrexian" | "Pilot" | "Pincher" | "Pirate" | "Plant" | "Praetor" | "Prism" | "Processor" | "Rabbit" | "Ranger" | "Rat" | "Rebel" | "Reflection" | "Rhino" | "Rigger" | "Rogue" | "Sable" | "Salamander" | "Samurai" | "Sand" | "Saproling" | "Satyr" | "Scarecrow" | "Scion" | "Scorpion" | "Scout" | "Sculpture" | "Serf" | "Serpent" | "Servo" | "Shade" | "Shaman" | "Shapeshifter" | "Shark" | "Sheep" | "Siren" | "Skeleton" | "Slith" | "Sliver" | "Slug" | "Snake" | "Soldier" | "Soltari" | "Spawn" | "Specter" | "Spellshaper" | "Sphinx" | "Spider" | "Spike" | "Spirit" | "Splinter" | "Sponge" | "Squid" | "Squirrel" | "Starfish" | "Surrakar" | "Survivor" | "Tentacle" | "Tetravite" | "Thalakos" | "Thopter" | "Thrull" | "Tiefling" | "Treefolk" | "Trilobite" | "Triskelavite" | "Troll" | "Turtle" | "Unicorn" | "Vampire" | "Vedalken" | "Viashino" | "Volver" | "Wall" | "Warlock" | "Warrior" | "Weird" | "Werewolf" | "Whale" | "Wizard" | "Wolf" | "Wolverine" | "Wombat" | "Worm" | "Wraith" | "Wurm" | "Yeti" | "Zombie" | "Zubera" | "Plains" | "Island" | "Swamp" | "Mountain" | "Forest" | "Aura");
+
+export type TargetId = number;
+
+export type TokenAttribute = ("EntersTappedAndAttacking" | { PT: PT } | { HasColor: Color } | { Type: Type } | { Subtype: Subtype } | { Ability: Ability });
+
+export type TriggeredAbility = { effect: Clause[]; keyword?: (KeywordAbility | null); trigger: AbilityTrigger };
+
+export type Type = ("Artifact" | "Enchantment" | "Planeswalker" | "Land" | "Creature" | "Instant" | "Sorcery");
+
+export type Zone = ("Hand" | "Library" | "Exile" | "Battlefield" | "Graveyard" | "Command" | "Stack");
+
+export type ZoneMoveTrigger = { dest?: (Zone | null); origin?: (Zone | null) };